@@ -30,12 +30,12 @@ mod Store {
 
     #[derive(Drop, Serde, Debug)]
     pub struct MMRSnapshot {
-        batch_index: u64,
-        latest_mmr_block: u64,
-        latest_mmr_block_hash: u256,
-        root_hash: u256,
-        leaves_count: u64,
-        ipfs_hash: ByteArray,
+        pub batch_index: u64,
+        pub latest_mmr_block: u64,
+        pub latest_mmr_block_hash: u256,
+        pub root_hash: u256,
+        pub leaves_count: u64,
+        pub ipfs_hash: ByteArray,
     }
 
     #[storage]
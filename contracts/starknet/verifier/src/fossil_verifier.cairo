@@ -3,14 +3,26 @@ pub trait IFossilVerifier<TContractState> {
     fn verify_mmr_proof(
         ref self: TContractState, proof: Span<felt252>, ipfs_hash: ByteArray,
     ) -> bool;
+    fn verify_mmr_chain_proof(
+        ref self: TContractState, proof: Span<felt252>, ipfs_hashes: Array<ByteArray>,
+    ) -> bool;
+    fn verify_fee_proof(ref self: TContractState, proof: Span<felt252>) -> bool;
+    fn get_base_fee(self: @TContractState, block_number: u64) -> Option<(u64, u64)>;
     fn get_verifier_address(self: @TContractState) -> starknet::ContractAddress;
     fn get_fossil_store_address(self: @TContractState) -> starknet::ContractAddress;
 }
 
 #[starknet::contract]
 mod FossilVerifier {
+    use core::starknet::storage::{
+        Map, StorageMapReadAccess, StorageMapWriteAccess, StoragePointerReadAccess,
+        StoragePointerWriteAccess,
+    };
     use fossil_store::{IFossilStoreDispatcher, IFossilStoreDispatcherTrait};
-    use verifier::decode_journal;
+    use verifier::{
+        MmrChainJournal, decode_fee_journal, decode_journal, decode_mmr_chain_journal,
+        mmr_elements_count, pack_image_id, proof_image_id,
+    };
     use verifier::groth16_verifier::{
         IRisc0Groth16VerifierBN254Dispatcher, IRisc0Groth16VerifierBN254DispatcherTrait,
     };
@@ -19,12 +31,19 @@ mod FossilVerifier {
     struct Storage {
         bn254_verifier: IRisc0Groth16VerifierBN254Dispatcher,
         fossil_store: IFossilStoreDispatcher,
+        // block number -> (timestamp, base fee per gas) of fee proofs verified so far
+        base_fees: Map<u64, (u64, u64)>,
+        // Packed image IDs of the guests whose proofs are accepted, one per entrypoint
+        mmr_append_image_id: u256,
+        mmr_chain_image_id: u256,
+        fee_image_id: u256,
     }
 
     #[event]
     #[derive(Drop, starknet::Event)]
     enum Event {
         MmrProofVerified: MmrProofVerified,
+        FeeProofVerified: FeeProofVerified,
     }
 
     #[derive(Drop, starknet::Event)]
@@ -35,28 +54,41 @@ mod FossilVerifier {
         new_mmr_root: u256,
     }
 
+    #[derive(Drop, starknet::Event)]
+    struct FeeProofVerified {
+        batch_index: u64,
+        from_block: u64,
+        to_block: u64,
+        fees_count: u32,
+        mmr_root: u256,
+    }
+
+    const FEE_JOURNAL_VERSION: u32 = 2;
+    const MMR_CHAIN_JOURNAL_VERSION: u32 = 1;
+
     #[constructor]
     fn constructor(
         ref self: ContractState,
         verifier_address: starknet::ContractAddress,
         fossil_store_address: starknet::ContractAddress,
+        mmr_append_image_id: Span<u32>,
+        mmr_chain_image_id: Span<u32>,
+        fee_image_id: Span<u32>,
     ) {
         self
             .bn254_verifier
             .write(IRisc0Groth16VerifierBN254Dispatcher { contract_address: verifier_address });
         self.fossil_store.write(IFossilStoreDispatcher { contract_address: fossil_store_address });
+        self.mmr_append_image_id.write(pack_image_id(mmr_append_image_id));
+        self.mmr_chain_image_id.write(pack_image_id(mmr_chain_image_id));
+        self.fee_image_id.write(pack_image_id(fee_image_id));
     }
 
     #[external(v0)]
     fn verify_mmr_proof(
-        ref self: ContractState, mut proof: Span<felt252>, ipfs_hash: ByteArray,
+        ref self: ContractState, proof: Span<felt252>, ipfs_hash: ByteArray,
     ) -> bool {
-        let _ = proof.pop_front();
-        let journal = self
-            .bn254_verifier
-            .read()
-            .verify_groth16_proof_bn254(proof)
-            .expect('Failed to verify proof');
+        let journal = self.verify_groth16_proof(proof, self.mmr_append_image_id.read());
 
         let journal = decode_journal(journal);
 
@@ -74,4 +106,127 @@ mod FossilVerifier {
 
         true
     }
+
+    #[external(v0)]
+    fn verify_mmr_chain_proof(
+        ref self: ContractState, proof: Span<felt252>, mut ipfs_hashes: Array<ByteArray>,
+    ) -> bool {
+        let journal = self.verify_groth16_proof(proof, self.mmr_chain_image_id.read());
+
+        let journal = decode_mmr_chain_journal(journal);
+        assert(journal.version == MMR_CHAIN_JOURNAL_VERSION, 'Unsupported journal version');
+        // The chaining guest only vouches for the appends it verified, which must be ours
+        assert(
+            journal.append_image_id == self.mmr_append_image_id.read(),
+            'Unexpected append image ID',
+        );
+        assert(journal.batches.len() == ipfs_hashes.len(), 'IPFS hashes count mismatch');
+
+        self.check_chain_start(@journal);
+        let fossil_store = self.fossil_store.read();
+
+        // One store update per batch, in batch order, as separate MMR proofs would do
+        for batch in journal.batches.span() {
+            let batch = *batch;
+            fossil_store.update_mmr_state(batch, ipfs_hashes.pop_front().unwrap());
+
+            self
+                .emit(
+                    MmrProofVerified {
+                        batch_index: batch.batch_index,
+                        latest_mmr_block: batch.latest_mmr_block,
+                        new_leaves_count: batch.leaves_count,
+                        new_mmr_root: batch.root_hash,
+                    },
+                );
+        };
+
+        true
+    }
+
+    #[external(v0)]
+    fn verify_fee_proof(ref self: ContractState, proof: Span<felt252>) -> bool {
+        let journal = self.verify_groth16_proof(proof, self.fee_image_id.read());
+
+        let journal = decode_fee_journal(journal);
+        assert(journal.version == FEE_JOURNAL_VERSION, 'Unsupported journal version');
+        assert(journal.ok, 'Blocks rejected by guest');
+
+        // The blocks were proven against the batch MMR, which must be the stored one
+        let mmr_state = self.fossil_store.read().get_mmr_state(journal.batch_index);
+        assert(journal.mmr_root == mmr_state.root_hash, 'MMR root mismatch');
+        assert(
+            journal.elements_count == mmr_elements_count(mmr_state.leaves_count),
+            'MMR size mismatch',
+        );
+
+        for fee in journal.fees.span() {
+            self.base_fees.write(*fee.block_number, (*fee.timestamp, *fee.base_fee_per_gas));
+        };
+
+        self
+            .emit(
+                FeeProofVerified {
+                    batch_index: journal.batch_index,
+                    from_block: journal.from_block,
+                    to_block: journal.to_block,
+                    fees_count: journal.fees.len(),
+                    mmr_root: journal.mmr_root,
+                },
+            );
+
+        true
+    }
+
+    #[generate_trait]
+    impl InternalImpl of InternalTrait {
+        // Checks that the chain starts from the stored state of each batch it touches: its first
+        // batch continues from the stored root, and every later batch is still empty.
+        fn check_chain_start(self: @ContractState, journal: @MmrChainJournal) {
+            let fossil_store = self.fossil_store.read();
+            let mut batches = journal.batches.span();
+            let first = batches.pop_front().expect('Empty MMR chain');
+
+            let mmr_state = fossil_store.get_mmr_state(*first.batch_index);
+            assert(mmr_state.leaves_count == *journal.initial_leaves_count, 'MMR size mismatch');
+            if *journal.initial_leaves_count > 0 {
+                assert(mmr_state.root_hash == *journal.initial_root_hash, 'MMR root mismatch');
+            } else if *first.batch_index > 0 {
+                // A new batch links to the last block of the previous one, when that is stored
+                let batch_link = (*journal.batch_link).expect('Missing batch link');
+                let previous = fossil_store.get_mmr_state(*first.batch_index - 1);
+                if previous.latest_mmr_block + 1 == *journal.first_block {
+                    assert(batch_link == previous.latest_mmr_block_hash, 'Batch link mismatch');
+                }
+            }
+
+            for batch in batches {
+                let mmr_state = fossil_store.get_mmr_state(*batch.batch_index);
+                assert(mmr_state.leaves_count == 0, 'MMR size mismatch');
+            };
+        }
+
+        // Verifies a Groth16 proof, which must be of the program `image_id`, and returns its
+        // journal. Any program can produce a journal that decodes, so the image is checked first.
+        fn verify_groth16_proof(
+            self: @ContractState, mut proof: Span<felt252>, image_id: u256,
+        ) -> Span<u8> {
+            let _ = proof.pop_front();
+            assert(proof_image_id(proof) == image_id, 'Unexpected image ID');
+            self
+                .bn254_verifier
+                .read()
+                .verify_groth16_proof_bn254(proof)
+                .expect('Failed to verify proof')
+        }
+    }
+
+    #[external(v0)]
+    fn get_base_fee(self: @ContractState, block_number: u64) -> Option<(u64, u64)> {
+        let (timestamp, base_fee_per_gas) = self.base_fees.read(block_number);
+        if timestamp == 0 {
+            return Option::None;
+        }
+        Option::Some((timestamp, base_fee_per_gas))
+    }
 }
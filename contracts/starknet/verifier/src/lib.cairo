@@ -3,6 +3,7 @@ pub mod groth16_verifier;
 mod groth16_verifier_constants;
 pub mod universal_ecip;
 use core::num::traits::{Bounded, WideMul};
+use garaga::utils::calldata::deserialize_full_proof_with_hints_risc0;
 
 #[derive(Drop, Copy, Serde)]
 pub struct Journal {
@@ -99,6 +100,197 @@ pub(crate) fn decode_journal(journal_bytes: Span<u8>) -> Journal {
     Journal { batch_index, latest_mmr_block, latest_mmr_block_hash, root_hash, leaves_count }
 }
 
+#[derive(Drop, Copy, Serde)]
+pub struct BlockFee {
+    pub block_number: u64,
+    pub timestamp: u64,
+    pub base_fee_per_gas: u64,
+}
+
+#[derive(Drop, Serde)]
+pub struct FeeJournal {
+    pub version: u32,
+    pub ok: bool,
+    pub mmr_root: u256,
+    pub elements_count: u64,
+    pub batch_index: u64,
+    pub from_block: u64,
+    pub to_block: u64,
+    pub fees: Array<BlockFee>,
+}
+
+// Decodes the RISC Zero serde encoding of the fee guest's `FeeProofJournal`:
+// little-endian words, strings as a length word followed by bytes padded to a word.
+pub(crate) fn decode_fee_journal(journal_bytes: Span<u8>) -> FeeJournal {
+    let mut offset = 0;
+
+    let version = read_u32(journal_bytes, ref offset);
+    let ok = read_u32(journal_bytes, ref offset) == 1;
+    let mmr_root = read_hex_u256(journal_bytes, ref offset);
+    let elements_count = read_u64(journal_bytes, ref offset);
+    let batch_index = read_u64(journal_bytes, ref offset);
+    let from_block = read_u64(journal_bytes, ref offset);
+    let to_block = read_u64(journal_bytes, ref offset);
+
+    let fees_len = read_u32(journal_bytes, ref offset);
+    let mut fees = array![];
+    let mut i = 0;
+    while i < fees_len {
+        let block_number = read_u64(journal_bytes, ref offset);
+        let timestamp = read_u64(journal_bytes, ref offset);
+        let base_fee_per_gas = read_u64(journal_bytes, ref offset);
+        // Skip gas_used and gas_limit
+        offset += 16;
+        // Skip blob_gas_used and excess_blob_gas
+        skip_option_u64(journal_bytes, ref offset);
+        skip_option_u64(journal_bytes, ref offset);
+
+        fees.append(BlockFee { block_number, timestamp, base_fee_per_gas });
+        i += 1;
+    };
+
+    FeeJournal {
+        version, ok, mmr_root, elements_count, batch_index, from_block, to_block, fees,
+    }
+}
+
+#[derive(Drop, Serde)]
+pub struct MmrChainJournal {
+    pub version: u32,
+    pub append_image_id: u256,
+    pub first_block: u64,
+    pub batch_link: Option<u256>,
+    pub initial_leaves_count: u64,
+    pub initial_root_hash: u256,
+    pub batches: Array<Journal>,
+}
+
+// Decodes the RISC Zero serde encoding of the chaining guest's `MmrChainJournal`, whose
+// batches share the layout of the append guest's journal.
+pub(crate) fn decode_mmr_chain_journal(journal_bytes: Span<u8>) -> MmrChainJournal {
+    let mut offset = 0;
+
+    let version = read_u32(journal_bytes, ref offset);
+    let mut append_image_id = array![];
+    while append_image_id.len() < 8 {
+        append_image_id.append(read_u32(journal_bytes, ref offset));
+    };
+    let append_image_id = pack_image_id(append_image_id.span());
+    let first_block = read_u64(journal_bytes, ref offset);
+    let batch_link = if read_u32(journal_bytes, ref offset) == 1 {
+        Option::Some(read_hex_u256(journal_bytes, ref offset))
+    } else {
+        Option::None
+    };
+    let initial_leaves_count = read_u64(journal_bytes, ref offset);
+    let initial_root_hash = read_hex_u256(journal_bytes, ref offset);
+
+    let batches_len = read_u32(journal_bytes, ref offset);
+    let mut batches = array![];
+    let mut i = 0;
+    while i < batches_len {
+        let batch_index = read_u64(journal_bytes, ref offset);
+        let latest_mmr_block = read_u64(journal_bytes, ref offset);
+        let latest_mmr_block_hash = read_hex_u256(journal_bytes, ref offset);
+        let root_hash = read_hex_u256(journal_bytes, ref offset);
+        let leaves_count = read_u64(journal_bytes, ref offset);
+
+        batches
+            .append(
+                Journal {
+                    batch_index, latest_mmr_block, latest_mmr_block_hash, root_hash, leaves_count,
+                },
+            );
+        i += 1;
+    };
+
+    MmrChainJournal {
+        version,
+        append_image_id,
+        first_block,
+        batch_link,
+        initial_leaves_count,
+        initial_root_hash,
+        batches,
+    }
+}
+
+// Image ID of the program a RISC Zero Groth16 proof was generated for, read from the proof
+// calldata the Groth16 verifier checks it against.
+pub fn proof_image_id(proof: Span<felt252>) -> u256 {
+    pack_image_id(deserialize_full_proof_with_hints_risc0(proof).image_id)
+}
+
+// Packs the eight words of a RISC Zero image ID, first word highest, so it can be stored and
+// compared as a single value.
+pub fn pack_image_id(image_id: Span<u32>) -> u256 {
+    assert(image_id.len() == 8, 'Invalid image ID length');
+    let mut packed: u256 = 0;
+    for word in image_id {
+        packed = packed * 0x100000000 + (*word).into();
+    };
+    packed
+}
+
+// Number of nodes in an MMR with `leaves_count` leaves: every merge adds one parent, and a
+// leaf count with `k` set bits leaves `k` peaks unmerged.
+pub fn mmr_elements_count(leaves_count: u64) -> u64 {
+    let mut set_bits = 0;
+    let mut rest = leaves_count;
+    while rest != 0 {
+        set_bits += rest % 2;
+        rest /= 2;
+    };
+    2 * leaves_count - set_bits
+}
+
+fn read_u32(bytes: Span<u8>, ref offset: usize) -> u32 {
+    let mut value: u32 = 0;
+    let mut i = 4;
+    while i > 0 {
+        i -= 1;
+        value = value * 256 + (*bytes.at(offset + i)).into();
+    };
+    offset += 4;
+    value
+}
+
+fn read_u64(bytes: Span<u8>, ref offset: usize) -> u64 {
+    let mut value: u64 = 0;
+    let mut i = 8;
+    while i > 0 {
+        i -= 1;
+        value = value * 256 + (*bytes.at(offset + i)).into();
+    };
+    offset += 8;
+    value
+}
+
+fn read_hex_u256(bytes: Span<u8>, ref offset: usize) -> u256 {
+    let len: usize = read_u32(bytes, ref offset).try_into().unwrap();
+    let mut value: u256 = 0;
+    let mut i = offset + 2; // Skip "0x" prefix
+    let end = offset + len;
+    while i < end {
+        let c: u256 = (*bytes.at(i)).into();
+        let digit = if c < 58 { // '0'-'9' vs 'a'-'f'
+            c - 48
+        } else {
+            c - 87
+        };
+        value = value * 16 + digit;
+        i += 1;
+    };
+    offset += (len + 3) / 4 * 4;
+    value
+}
+
+fn skip_option_u64(bytes: Span<u8>, ref offset: usize) {
+    if read_u32(bytes, ref offset) == 1 {
+        offset += 8;
+    }
+}
+
 trait BitShift<T> {
     fn shl(x: T, n: T) -> T;
     fn shr(x: T, n: T) -> T;
@@ -152,7 +344,32 @@ fn pow<T, +Sub<T>, +Mul<T>, +Div<T>, +Rem<T>, +PartialEq<T>, +Into<u8, T>, +Drop
 
 #[cfg(test)]
 mod tests {
-    use super::decode_journal;
+    use super::{decode_journal, mmr_elements_count, pack_image_id};
+
+    #[test]
+    fn pack_image_id_test() {
+        assert_eq!(pack_image_id(array![0, 0, 0, 0, 0, 0, 0, 1].span()), 1);
+        assert_eq!(
+            pack_image_id(array![1, 0, 0, 0, 0, 0, 0, 2].span()),
+            0x0000000100000000000000000000000000000000000000000000000000000002,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected: ('Invalid image ID length',))]
+    fn pack_image_id_rejects_short_ids_test() {
+        pack_image_id(array![1, 2, 3].span());
+    }
+
+    #[test]
+    fn mmr_elements_count_test() {
+        assert_eq!(mmr_elements_count(0), 0);
+        assert_eq!(mmr_elements_count(1), 1);
+        assert_eq!(mmr_elements_count(2), 3);
+        assert_eq!(mmr_elements_count(3), 4);
+        assert_eq!(mmr_elements_count(4), 7);
+        assert_eq!(mmr_elements_count(1024), 2047);
+    }
 
     #[test]
     fn decode_journal_test() {
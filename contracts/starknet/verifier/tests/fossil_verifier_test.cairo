@@ -0,0 +1,78 @@
+use snforge_std::{ContractClassTrait, DeclareResultTrait, declare};
+use verifier::fossil_verifier::{
+    IFossilVerifierSafeDispatcher, IFossilVerifierSafeDispatcherTrait,
+};
+use verifier::{pack_image_id, proof_image_id};
+use super::fixtures::calldata_default;
+
+// Image ID of the program the fixture proof was generated for
+fn fixture_image_id() -> Span<u32> {
+    array![
+        127869937, 1051204574, 296306962, 3135236313, 760996115, 1261213979, 2572544449,
+        2302786153,
+    ]
+        .span()
+}
+
+fn other_image_id() -> Span<u32> {
+    array![0, 0, 0, 0, 0, 0, 0, 1].span()
+}
+
+fn deploy(
+    mmr_append_image_id: Span<u32>, mmr_chain_image_id: Span<u32>, fee_image_id: Span<u32>,
+) -> IFossilVerifierSafeDispatcher {
+    let contract = declare("FossilVerifier").unwrap().contract_class();
+
+    // Neither contract is called when the image ID check fails
+    let verifier_address: starknet::ContractAddress = 0x1.try_into().unwrap();
+    let fossil_store_address: starknet::ContractAddress = 0x2.try_into().unwrap();
+
+    let mut calldata = array![];
+    Serde::serialize(@verifier_address, ref calldata);
+    Serde::serialize(@fossil_store_address, ref calldata);
+    Serde::serialize(@mmr_append_image_id, ref calldata);
+    Serde::serialize(@mmr_chain_image_id, ref calldata);
+    Serde::serialize(@fee_image_id, ref calldata);
+    let (contract_address, _) = contract.deploy(@calldata).unwrap();
+
+    IFossilVerifierSafeDispatcher { contract_address }
+}
+
+// The fixture calldata as submitted to the Fossil Verifier, behind its length
+fn proof_calldata() -> Span<felt252> {
+    let fixture = calldata_default();
+    let mut proof = array![fixture.len().into()];
+    proof.append_span(fixture.span());
+    proof.span()
+}
+
+#[test]
+fn test_proof_image_id() {
+    assert_eq!(proof_image_id(calldata_default().span()), pack_image_id(fixture_image_id()));
+    assert_eq!(
+        pack_image_id(fixture_image_id()),
+        0x079f23f13ea81bde11a94912badfe8d92d5be1134b2c991b9955e9c18941ba69,
+    );
+}
+
+#[test]
+#[feature("safe_dispatcher")]
+fn test_fee_proof_of_another_image_is_rejected() {
+    let fossil_verifier = deploy(fixture_image_id(), fixture_image_id(), other_image_id());
+
+    match fossil_verifier.verify_fee_proof(proof_calldata()) {
+        Result::Ok(_) => panic!("Fee proof of another image was accepted"),
+        Result::Err(panic_data) => assert_eq!(*panic_data.at(0), 'Unexpected image ID'),
+    }
+}
+
+#[test]
+#[feature("safe_dispatcher")]
+fn test_mmr_chain_proof_of_another_image_is_rejected() {
+    let fossil_verifier = deploy(fixture_image_id(), other_image_id(), fixture_image_id());
+
+    match fossil_verifier.verify_mmr_chain_proof(proof_calldata(), array![]) {
+        Result::Ok(_) => panic!("MMR chain proof of another image was accepted"),
+        Result::Err(panic_data) => assert_eq!(*panic_data.at(0), 'Unexpected image ID'),
+    }
+}
@@ -0,0 +1,3 @@
+mod fixtures;
+mod fossil_verifier_test;
+mod verifier_test;
@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+
+/// Way in which the Fossil Store and the local database of a batch disagree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchDivergence {
+    /// Only the local database holds the batch
+    MissingOnchain,
+    /// Only the Fossil Store holds the batch
+    MissingLocal,
+    LeavesCountMismatch,
+    RootMismatch,
+}
+
+/// Batch MMR state held by the Fossil Store.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OnchainBatch {
+    pub latest_mmr_block: u64,
+    pub latest_mmr_block_hash: String,
+    pub leaves_count: u64,
+    pub root_hash: String,
+    /// CID of the batch database uploaded to IPFS
+    pub ipfs_cid: String,
+}
+
+/// Batch MMR state of the local `batch_N.db` file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LocalBatch {
+    pub leaves_count: u64,
+    pub elements_count: u64,
+    pub root_hash: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchInfo {
+    pub batch_index: u64,
+    /// First and last block a batch of this index can hold
+    pub block_range: (u64, u64),
+    pub onchain: Option<OnchainBatch>,
+    pub local: Option<LocalBatch>,
+    /// Empty when both sides agree
+    pub divergence: Vec<BatchDivergence>,
+}
+
+impl BatchInfo {
+    /// Compares both sides of the batch `batch_index`, either of which may be missing.
+    pub fn new(
+        batch_index: u64,
+        batch_size: u64,
+        onchain: Option<OnchainBatch>,
+        local: Option<LocalBatch>,
+    ) -> Self {
+        let divergence = match (&onchain, &local) {
+            (None, Some(_)) => vec![BatchDivergence::MissingOnchain],
+            (Some(_), None) => vec![BatchDivergence::MissingLocal],
+            (Some(onchain), Some(local)) => {
+                let mut divergence = Vec::new();
+                if onchain.leaves_count != local.leaves_count {
+                    divergence.push(BatchDivergence::LeavesCountMismatch);
+                }
+                if onchain.root_hash != local.root_hash {
+                    divergence.push(BatchDivergence::RootMismatch);
+                }
+                divergence
+            }
+            (None, None) => Vec::new(),
+        };
+
+        let first_block = batch_index * batch_size;
+        Self {
+            batch_index,
+            block_range: (first_block, first_block + batch_size - 1),
+            onchain,
+            local,
+            divergence,
+        }
+    }
+
+    pub fn is_known(&self) -> bool {
+        self.onchain.is_some() || self.local.is_some()
+    }
+}
+
+/// Every batch known to the Fossil Store or found in the local database directory.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchListing {
+    pub batch_size: u64,
+    pub min_mmr_block: u64,
+    pub latest_mmr_block: u64,
+    pub batches: Vec<BatchInfo>,
+}
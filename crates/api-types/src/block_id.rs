@@ -0,0 +1,83 @@
+use crate::errors::RequestError;
+use std::fmt;
+use std::str::FromStr;
+
+/// Identifies a block either by number or by hash.
+///
+/// The textual form is a decimal block number, or a `0x`-prefixed 32-byte hash.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlockId {
+    Number(u64),
+    Hash(String),
+}
+
+impl fmt::Display for BlockId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Number(number) => write!(f, "{}", number),
+            Self::Hash(hash) => write!(f, "{}", hash),
+        }
+    }
+}
+
+impl FromStr for BlockId {
+    type Err = RequestError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+            Some(hex) if hex.len() == 64 && hex.chars().all(|c| c.is_ascii_hexdigit()) => {
+                // Headers are indexed with lowercase hashes
+                Ok(Self::Hash(format!("0x{}", hex.to_ascii_lowercase())))
+            }
+            Some(_) => Err(RequestError::InvalidBlockId(format!(
+                "'{}' is not a 32-byte hex hash",
+                s
+            ))),
+            None => s.parse().map(Self::Number).map_err(|_| {
+                RequestError::InvalidBlockId(format!(
+                    "'{}' is neither a block number nor a hash",
+                    s
+                ))
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_block_number() {
+        assert_eq!(
+            "19426587".parse::<BlockId>().unwrap(),
+            BlockId::Number(19426587)
+        );
+    }
+
+    #[test]
+    fn test_parse_block_hash() {
+        let hash = format!("0x{}", "AB".repeat(32));
+        assert_eq!(
+            hash.parse::<BlockId>().unwrap(),
+            BlockId::Hash(format!("0x{}", "ab".repeat(32)))
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        for invalid in [
+            "",
+            "-1",
+            "0x1234",
+            "latest",
+            &format!("0x{}", "zz".repeat(32)),
+        ] {
+            assert!(matches!(
+                invalid.parse::<BlockId>(),
+                Err(RequestError::InvalidBlockId(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,19 @@
+use thiserror::Error;
+
+/// Invalid block range, sampling mode or block identifier of a request.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum RequestError {
+    #[error(
+        "Invalid block range: start block {start_block} is greater than end block {end_block}"
+    )]
+    InvalidBlockRange { start_block: u64, end_block: u64 },
+    #[error("Invalid sampling mode: {0}")]
+    InvalidSampling(String),
+    #[error("Invalid block identifier: {0}")]
+    InvalidBlockId(String),
+}
+
+/// Image id of a [`crate::Stark`] that is not 32 bytes long.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("Invalid image id of {0} bytes, expected 32")]
+pub struct InvalidImageId(pub usize);
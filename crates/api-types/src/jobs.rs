@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+
+use crate::errors::RequestError;
+use crate::sampling::SamplingMode;
+
+/// Stage a proof request is in, in the order stages are reached.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "stage", rename_all = "snake_case")]
+pub enum ProvingStage {
+    FetchingHeaders,
+    /// Downloading the database of a batch MMR from IPFS
+    DownloadingBatch {
+        batch_index: u64,
+    },
+    /// Checking the batch MMR roots against the Fossil Store
+    VerifyingRoots,
+    /// Proving batch `batch` of `total`, counting from 1
+    ProvingBatch {
+        batch: usize,
+        total: usize,
+    },
+    /// Folding the batch proofs into a single receipt
+    Aggregating,
+    /// Encoding the proofs for the caller; reported by callers, not by the validator
+    Serializing,
+}
+
+/// Which proof a job produces, matching the synchronous `/verify-blocks` routes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    /// One fee proof per batch, as a bincode-encoded `Vec<Stark>`
+    #[default]
+    VerifyBlocks,
+    /// A single aggregated fee proof, as a bincode-encoded `Stark`
+    VerifyBlocksAggregated,
+}
+
+/// Body of `POST /jobs`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct JobRequest {
+    pub from_block: u64,
+    pub to_block: u64,
+    #[serde(default)]
+    pub kind: JobKind,
+    /// Optional override for skip_proof_verification from CLI
+    pub skip_proof_verification: Option<bool>,
+    /// Block sampling mode, e.g. `every_nth:10`; defaults to one block per hour
+    pub sampling: Option<String>,
+}
+
+impl JobRequest {
+    /// Sampling mode of the request, checked against its block range.
+    pub fn sampling(&self) -> Result<SamplingMode, RequestError> {
+        let sampling = match self.sampling.as_deref() {
+            Some(sampling) => sampling.parse()?,
+            None => SamplingMode::default(),
+        };
+        sampling.validate(self.from_block, self.to_block)?;
+        Ok(sampling)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Proving,
+    Done,
+    Failed,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct JobProgress {
+    pub proven_batches: usize,
+    pub total_batches: usize,
+}
+
+/// State of a proving job, as returned by `GET /jobs/{id}` and its event stream.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct JobInfo {
+    pub id: String,
+    pub kind: JobKind,
+    pub from_block: u64,
+    pub to_block: u64,
+    pub status: JobStatus,
+    pub progress: JobProgress,
+    /// Stage of a job that is proving, e.g. `{"stage": "proving_batch", "batch": 2, "total": 5}`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "utoipa", schema(value_type = Option<Object>))]
+    pub stage: Option<ProvingStage>,
+    /// Seconds until every batch is proven, from the average batch proving time so far
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub eta_secs: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Unix timestamps, in seconds
+    pub created_at: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<u64>,
+}
+
+impl JobInfo {
+    pub fn is_finished(&self) -> bool {
+        matches!(self.status, JobStatus::Done | JobStatus::Failed)
+    }
+}
+
+/// Body of every error response. `code` is a stable, machine-readable
+/// identifier; `error` is the human-readable message.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct ErrorResponse {
+    pub code: String,
+    pub error: String,
+}
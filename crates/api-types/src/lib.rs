@@ -0,0 +1,19 @@
+//! Types shared by the state proof API and its clients: proof requests and
+//! jobs, batch listings, block inclusion proofs and the `Stark` receipts the
+//! API returns.
+
+mod batches;
+mod block_id;
+mod errors;
+mod jobs;
+mod proof;
+mod sampling;
+mod stark;
+
+pub use batches::*;
+pub use block_id::*;
+pub use errors::*;
+pub use jobs::*;
+pub use proof::*;
+pub use sampling::*;
+pub use stark::*;
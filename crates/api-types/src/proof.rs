@@ -0,0 +1,12 @@
+use guest_types::GuestProof;
+use serde::{Deserialize, Serialize};
+
+/// MMR inclusion proof of a block, in the [`GuestProof`] shape plus the batch
+/// whose on-chain root it verifies against.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockInclusionProof {
+    pub block_number: u64,
+    pub batch_index: u64,
+    #[serde(flatten)]
+    pub proof: GuestProof,
+}
@@ -0,0 +1,213 @@
+use crate::errors::RequestError;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+const DEFAULT_BUCKET_SECS: u64 = 3600;
+
+/// Selects which blocks of a range are proven when extracting fees.
+///
+/// The textual form accepted by [`FromStr`] and produced by [`fmt::Display`] is
+/// used by the CLI and the API query string:
+///
+/// - `every_block`
+/// - `every_nth:<step>` — the first block of the range and every `step`-th after it
+/// - `time_bucket:<seconds>` — the first block of every `seconds`-long window
+/// - `blocks:<n1>,<n2>,...` — an explicit list of block numbers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum SamplingMode {
+    EveryBlock,
+    EveryNth { step: u64 },
+    TimeBucket { bucket_secs: u64 },
+    Blocks { blocks: Vec<u64> },
+}
+
+impl Default for SamplingMode {
+    /// One block per hour, the historical behaviour of fee extraction
+    fn default() -> Self {
+        Self::TimeBucket {
+            bucket_secs: DEFAULT_BUCKET_SECS,
+        }
+    }
+}
+
+impl SamplingMode {
+    pub fn hourly() -> Self {
+        Self::default()
+    }
+
+    /// Checks the mode is usable for the given block range.
+    pub fn validate(&self, start_block: u64, end_block: u64) -> Result<(), RequestError> {
+        if start_block > end_block {
+            return Err(RequestError::InvalidBlockRange {
+                start_block,
+                end_block,
+            });
+        }
+
+        match self {
+            Self::EveryBlock => Ok(()),
+            Self::EveryNth { step: 0 } => Err(RequestError::InvalidSampling(
+                "step must be greater than 0".to_string(),
+            )),
+            Self::TimeBucket { bucket_secs: 0 } => Err(RequestError::InvalidSampling(
+                "bucket size must be greater than 0".to_string(),
+            )),
+            Self::Blocks { blocks } if blocks.is_empty() => Err(RequestError::InvalidSampling(
+                "block list cannot be empty".to_string(),
+            )),
+            Self::Blocks { blocks } => {
+                match blocks
+                    .iter()
+                    .find(|&&block| block < start_block || block > end_block)
+                {
+                    Some(block) => Err(RequestError::InvalidSampling(format!(
+                        "block {} is outside the range {} to {}",
+                        block, start_block, end_block
+                    ))),
+                    None => Ok(()),
+                }
+            }
+            Self::EveryNth { .. } | Self::TimeBucket { .. } => Ok(()),
+        }
+    }
+
+    /// Explicit block numbers, sorted and deduplicated
+    pub fn sorted_blocks(blocks: &[u64]) -> Vec<u64> {
+        let mut blocks = blocks.to_vec();
+        blocks.sort_unstable();
+        blocks.dedup();
+        blocks
+    }
+}
+
+impl fmt::Display for SamplingMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EveryBlock => write!(f, "every_block"),
+            Self::EveryNth { step } => write!(f, "every_nth:{}", step),
+            Self::TimeBucket { bucket_secs } => write!(f, "time_bucket:{}", bucket_secs),
+            Self::Blocks { blocks } => {
+                let blocks = blocks
+                    .iter()
+                    .map(|block| block.to_string())
+                    .collect::<Vec<_>>()
+                    .join(",");
+                write!(f, "blocks:{}", blocks)
+            }
+        }
+    }
+}
+
+impl FromStr for SamplingMode {
+    type Err = RequestError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (mode, value) = match s.split_once(':') {
+            Some((mode, value)) => (mode, Some(value)),
+            None => (s, None),
+        };
+
+        let parse_u64 = |value: Option<&str>, name: &str| -> Result<u64, RequestError> {
+            value
+                .ok_or_else(|| RequestError::InvalidSampling(format!("{} requires a value", name)))?
+                .trim()
+                .parse()
+                .map_err(|e| {
+                    RequestError::InvalidSampling(format!("invalid {} value: {}", name, e))
+                })
+        };
+
+        match mode.trim() {
+            "every_block" => Ok(Self::EveryBlock),
+            "every_nth" => Ok(Self::EveryNth {
+                step: parse_u64(value, "every_nth")?,
+            }),
+            "hourly" => Ok(Self::hourly()),
+            "time_bucket" => Ok(Self::TimeBucket {
+                bucket_secs: parse_u64(value, "time_bucket")?,
+            }),
+            "blocks" => {
+                let blocks = value
+                    .ok_or_else(|| {
+                        RequestError::InvalidSampling("blocks requires a value".to_string())
+                    })?
+                    .split(',')
+                    .filter(|block| !block.trim().is_empty())
+                    .map(|block| parse_u64(Some(block), "blocks"))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(Self::Blocks { blocks })
+            }
+            other => Err(RequestError::InvalidSampling(format!(
+                "unknown sampling mode '{}'",
+                other
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_is_hourly() {
+        assert_eq!(
+            SamplingMode::default(),
+            SamplingMode::TimeBucket { bucket_secs: 3600 }
+        );
+    }
+
+    #[test]
+    fn test_parse_and_display_roundtrip() {
+        let modes = vec![
+            SamplingMode::EveryBlock,
+            SamplingMode::EveryNth { step: 10 },
+            SamplingMode::TimeBucket { bucket_secs: 900 },
+            SamplingMode::Blocks {
+                blocks: vec![5, 1, 3],
+            },
+        ];
+
+        for mode in modes {
+            let parsed: SamplingMode = mode.to_string().parse().unwrap();
+            assert_eq!(parsed, mode);
+        }
+
+        assert_eq!(
+            "hourly".parse::<SamplingMode>().unwrap(),
+            SamplingMode::hourly()
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!("".parse::<SamplingMode>().is_err());
+        assert!("every_nth".parse::<SamplingMode>().is_err());
+        assert!("every_nth:abc".parse::<SamplingMode>().is_err());
+        assert!("blocks:1,x".parse::<SamplingMode>().is_err());
+        assert!("daily".parse::<SamplingMode>().is_err());
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(SamplingMode::EveryBlock.validate(1, 10).is_ok());
+        assert!(SamplingMode::EveryBlock.validate(10, 1).is_err());
+        assert!(SamplingMode::EveryNth { step: 0 }.validate(1, 10).is_err());
+        assert!(SamplingMode::TimeBucket { bucket_secs: 0 }
+            .validate(1, 10)
+            .is_err());
+        assert!(SamplingMode::Blocks { blocks: vec![] }
+            .validate(1, 10)
+            .is_err());
+        assert!(SamplingMode::Blocks { blocks: vec![11] }
+            .validate(1, 10)
+            .is_err());
+        assert!(SamplingMode::Blocks {
+            blocks: vec![1, 10]
+        }
+        .validate(1, 10)
+        .is_ok());
+    }
+}
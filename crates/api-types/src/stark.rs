@@ -0,0 +1,42 @@
+use risc0_zkvm::{Journal, Receipt};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::InvalidImageId;
+
+/// STARK receipt of a guest, with the image id and method id it was proven
+/// for. The API returns these bincode-encoded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Stark {
+    receipt: Receipt,
+    image_id: Vec<u8>,
+    method_id: [u32; 8],
+}
+
+impl Stark {
+    pub fn new(receipt: Receipt, image_id: Vec<u8>, method_id: [u32; 8]) -> Self {
+        Self {
+            receipt: receipt.clone(),
+            image_id,
+            method_id,
+        }
+    }
+
+    pub fn receipt(&self) -> Receipt {
+        self.receipt.clone()
+    }
+
+    pub fn journal(&self) -> Journal {
+        self.receipt.journal.clone()
+    }
+
+    pub fn method_id(&self) -> [u32; 8] {
+        self.method_id
+    }
+
+    pub fn image_id(&self) -> Result<[u8; 32], InvalidImageId> {
+        self.image_id
+            .clone()
+            .try_into()
+            .map_err(|image_id: Vec<u8>| InvalidImageId(image_id.len()))
+    }
+}
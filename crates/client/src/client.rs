@@ -1,11 +1,14 @@
-use common::get_env_var;
+use common::{get_env_var, get_ethereum_chain_id};
 use mmr_utils::{create_database_file, ensure_directory_exists};
+use publisher::db::DbConnection;
 use starknet::{
     core::types::{BlockId, EventFilter, Felt},
     macros::selector,
     providers::Provider as EventProvider,
 };
+use starknet_handler::account::starknet_chain_id_from_env;
 use starknet_handler::provider::StarknetProvider;
+use std::sync::Arc;
 use tokio::time::{self, Duration};
 use tracing::{error, info, instrument};
 
@@ -68,13 +71,18 @@ pub enum LightClientError {
     ChainIdError(#[from] std::num::ParseIntError),
     #[error("Felt conversion error: {0}")]
     FeltConversion(#[from] starknet::core::types::FromStrError),
+    #[error("Database error: {0}")]
+    Database(#[from] publisher::errors::DbError),
 }
 
 pub struct LightClient {
     starknet_provider: StarknetProvider,
     l2_store_addr: String,
     verifier_addr: String,
+    /// Ethereum chain id, checked by header validation
     chain_id: u64,
+    /// Starknet chain id MMR updates are signed for; the provider's when unset
+    starknet_chain_id: Option<Felt>,
     latest_processed_events_block: u64,
     latest_processed_mmr_block: u64,
     starknet_private_key: String,
@@ -82,6 +90,7 @@ pub struct LightClient {
     polling_interval: Duration,
     batch_size: u64,
     blocks_per_run: u64,
+    db_connection: Arc<DbConnection>,
 }
 
 impl LightClient {
@@ -103,7 +112,8 @@ impl LightClient {
         let verifier_addr = get_env_var("FOSSIL_VERIFIER")?;
         let starknet_private_key = get_env_var("STARKNET_PRIVATE_KEY")?;
         let starknet_account_address = get_env_var("STARKNET_ACCOUNT_ADDRESS")?;
-        let chain_id = get_env_var("CHAIN_ID")?.parse::<u64>()?;
+        let chain_id = get_ethereum_chain_id()?;
+        let starknet_chain_id = starknet_chain_id_from_env()?;
 
         // Initialize providers
         let starknet_provider = StarknetProvider::new(&starknet_rpc_url)?;
@@ -117,11 +127,15 @@ impl LightClient {
             return Err(LightClientError::ConfigError(db_file));
         }
 
+        // Shared by every MMR update performed by this client
+        let db_connection = DbConnection::new().await?;
+
         Ok(Self {
             starknet_provider,
             l2_store_addr,
             verifier_addr,
             chain_id,
+            starknet_chain_id,
             latest_processed_events_block: start_block.saturating_sub(1),
             latest_processed_mmr_block: start_block.saturating_sub(1),
             starknet_private_key,
@@ -129,6 +143,7 @@ impl LightClient {
             polling_interval: Duration::from_secs(polling_interval),
             batch_size,
             blocks_per_run,
+            db_connection,
         })
     }
 
@@ -314,10 +329,12 @@ impl LightClient {
             &self.l2_store_addr,
             &self.starknet_private_key,
             &self.starknet_account_address,
+            self.starknet_chain_id,
             self.batch_size,
             latest_mmr_block + 1,
             latest_relayed_block,
             false,
+            self.db_connection.clone().into(),
         )
         .await?;
 
@@ -360,11 +377,14 @@ impl LightClient {
             return Err(LightClientError::ConfigError(db_file));
         }
 
+        let db_connection = DbConnection::unreachable_for_tests();
+
         Ok(Self {
             starknet_provider: deps.provider_factory.create_provider(&starknet_rpc_url)?,
             l2_store_addr,
             verifier_addr,
             chain_id,
+            starknet_chain_id: None,
             latest_processed_events_block: start_block.saturating_sub(1),
             latest_processed_mmr_block: start_block.saturating_sub(1),
             starknet_private_key,
@@ -372,6 +392,7 @@ impl LightClient {
             polling_interval: Duration::from_secs(polling_interval),
             batch_size,
             blocks_per_run,
+            db_connection,
         })
     }
 }
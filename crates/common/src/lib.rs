@@ -47,6 +47,16 @@ where
         .map_err(|e| UtilsError::ParseError(format!("{}: {}", name, e)))
 }
 
+/// Chain id of the Ethereum network whose headers are validated, from
+/// `ETHEREUM_CHAIN_ID`, or the legacy `CHAIN_ID` when it is unset. Starknet
+/// transactions are signed for a separate chain id, see `STARKNET_CHAIN_ID`.
+pub fn get_ethereum_chain_id() -> Result<u64, UtilsError> {
+    match get_env_var("ETHEREUM_CHAIN_ID") {
+        Ok(_) => get_var("ETHEREUM_CHAIN_ID"),
+        Err(_) => get_var("CHAIN_ID"),
+    }
+}
+
 /// Function to initialize logging and environment variables
 pub fn initialize_logger_and_env() -> Result<(), UtilsError> {
     let filter = tracing_subscriber::EnvFilter::try_from_default_env().unwrap_or_else(|_| {
@@ -88,14 +98,24 @@ pub fn felt(str: &str) -> Result<Felt, UtilsError> {
     Felt::from_hex(str).map_err(|_| UtilsError::FeltError(format!("Invalid hex string: {}", str)))
 }
 
-pub fn get_or_create_db_path(db_name: &str) -> Result<String, UtilsError> {
+/// Directory of the batch MMR databases: `DB_INSTANCES_DIR` when set,
+/// otherwise `db-instances` at the repository root.
+pub fn get_db_instances_dir() -> Result<PathBuf, UtilsError> {
+    if let Ok(dir) = get_env_var("DB_INSTANCES_DIR") {
+        return Ok(PathBuf::from(dir));
+    }
+
     // Get path to the db-instances directory relative to the test file
-    let test_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+    Ok(PathBuf::from(env!("CARGO_MANIFEST_DIR"))
         .parent()
         .ok_or_else(|| UtilsError::ParseError("Missing parent directory".to_string()))?
         .parent()
         .ok_or_else(|| UtilsError::ParseError("Missing root directory".to_string()))?
-        .join("db-instances");
+        .join("db-instances"))
+}
+
+pub fn get_or_create_db_path(db_name: &str) -> Result<String, UtilsError> {
+    let test_dir = get_db_instances_dir()?;
 
     // Ensure the directory exists
     if !test_dir.exists() {
@@ -165,6 +185,24 @@ mod tests {
         env::remove_var("TEST_INVALID");
     }
 
+    #[test]
+    fn test_get_ethereum_chain_id() {
+        env::remove_var("ETHEREUM_CHAIN_ID");
+        env::set_var("CHAIN_ID", "11155111");
+        assert_eq!(get_ethereum_chain_id().unwrap(), 11155111);
+
+        // The explicit setting wins over the legacy one
+        env::set_var("ETHEREUM_CHAIN_ID", "1");
+        assert_eq!(get_ethereum_chain_id().unwrap(), 1);
+
+        env::set_var("ETHEREUM_CHAIN_ID", "SN_MAIN");
+        assert!(get_ethereum_chain_id().is_err());
+
+        // Cleanup
+        env::remove_var("ETHEREUM_CHAIN_ID");
+        env::remove_var("CHAIN_ID");
+    }
+
     #[test]
     fn test_felt_conversion() {
         // Test valid hex string
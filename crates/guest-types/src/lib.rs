@@ -59,13 +59,19 @@ impl AppendResult {
 }
 
 // GuestOutput
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GuestOutput {
     batch_index: u64,
     latest_mmr_block: u64,
     latest_mmr_block_hash: String,
     root_hash: String,
     leaves_count: usize,
+    // Chaining fields, kept after the fields above so that decoders reading
+    // only the original layout keep working.
+    first_mmr_block: u64,
+    batch_link: Option<String>,
+    initial_leaves_count: usize,
+    initial_root_hash: String,
 }
 
 impl GuestOutput {
@@ -82,9 +88,30 @@ impl GuestOutput {
             latest_mmr_block_hash,
             root_hash,
             leaves_count,
+            first_mmr_block: latest_mmr_block,
+            batch_link: None,
+            initial_leaves_count: 0,
+            initial_root_hash: String::new(),
         }
     }
 
+    /// Records the first appended block, the batch link it was checked
+    /// against and the MMR state before the append, so that consecutive
+    /// outputs can be chained.
+    pub fn with_chain_link(
+        mut self,
+        first_mmr_block: u64,
+        batch_link: Option<String>,
+        initial_leaves_count: usize,
+        initial_root_hash: String,
+    ) -> Self {
+        self.first_mmr_block = first_mmr_block;
+        self.batch_link = batch_link;
+        self.initial_leaves_count = initial_leaves_count;
+        self.initial_root_hash = initial_root_hash;
+        self
+    }
+
     pub fn latest_mmr_block(&self) -> u64 {
         self.latest_mmr_block
     }
@@ -104,6 +131,156 @@ impl GuestOutput {
     pub fn leaves_count(&self) -> usize {
         self.leaves_count
     }
+
+    pub fn first_mmr_block(&self) -> u64 {
+        self.first_mmr_block
+    }
+
+    pub fn batch_link(&self) -> Option<&str> {
+        self.batch_link.as_deref()
+    }
+
+    pub fn initial_leaves_count(&self) -> usize {
+        self.initial_leaves_count
+    }
+
+    pub fn initial_root_hash(&self) -> &str {
+        &self.initial_root_hash
+    }
+}
+
+/// Input of the MMR chaining guest: the journals of consecutive `mmr-append`
+/// receipts, in block order, which the host adds as assumptions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MmrChainInput {
+    append_image_id: [u32; 8],
+    outputs: Vec<GuestOutput>,
+}
+
+impl MmrChainInput {
+    pub fn new(append_image_id: [u32; 8], outputs: Vec<GuestOutput>) -> Self {
+        Self {
+            append_image_id,
+            outputs,
+        }
+    }
+
+    pub fn append_image_id(&self) -> [u32; 8] {
+        self.append_image_id
+    }
+
+    pub fn outputs(&self) -> &Vec<GuestOutput> {
+        &self.outputs
+    }
+}
+
+/// MMR state of a batch after the last append of a chain. The layout matches
+/// the leading fields of [`GuestOutput`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchMmrState {
+    pub batch_index: u64,
+    pub latest_mmr_block: u64,
+    pub latest_mmr_block_hash: String,
+    pub root_hash: String,
+    pub leaves_count: usize,
+}
+
+/// Version of the [`MmrChainJournal`] layout.
+pub const MMR_CHAIN_JOURNAL_VERSION: u32 = 1;
+
+/// Journal of the MMR chaining guest: the final state of every batch touched
+/// by a run of consecutive appends, starting from `first_block`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MmrChainJournal {
+    pub version: u32,
+    pub append_image_id: [u32; 8],
+    pub first_block: u64,
+    pub batch_link: Option<String>,
+    pub initial_leaves_count: usize,
+    pub initial_root_hash: String,
+    pub batches: Vec<BatchMmrState>,
+}
+
+impl MmrChainJournal {
+    /// Chains append outputs given in block order. Each output must start
+    /// right after the previous one and either continue the same batch from
+    /// its root, or open the next batch with a link to its last block hash.
+    pub fn chain(append_image_id: [u32; 8], outputs: &[GuestOutput]) -> Result<Self, String> {
+        let first = outputs.first().ok_or("no append outputs to chain")?;
+
+        let mut batches: Vec<BatchMmrState> = Vec::new();
+        let mut previous: Option<&GuestOutput> = None;
+
+        for output in outputs {
+            if output.first_mmr_block > output.latest_mmr_block {
+                return Err(format!(
+                    "batch {} has an empty block range",
+                    output.batch_index
+                ));
+            }
+            if output.batch_index > 0 && output.batch_link.is_none() {
+                return Err(format!(
+                    "batch {} is missing its batch link",
+                    output.batch_index
+                ));
+            }
+
+            if let Some(previous) = previous {
+                if output.first_mmr_block != previous.latest_mmr_block + 1 {
+                    return Err(format!(
+                        "block {} does not follow block {}",
+                        output.first_mmr_block, previous.latest_mmr_block
+                    ));
+                }
+                if let Some(batch_link) = output.batch_link() {
+                    if batch_link != previous.latest_mmr_block_hash {
+                        return Err(format!(
+                            "batch link of block {} does not match the hash of block {}",
+                            output.first_mmr_block, previous.latest_mmr_block
+                        ));
+                    }
+                }
+
+                if output.batch_index == previous.batch_index {
+                    if output.initial_leaves_count != previous.leaves_count
+                        || output.initial_root_hash != previous.root_hash
+                    {
+                        return Err(format!(
+                            "batch {} does not continue from its previous root",
+                            output.batch_index
+                        ));
+                    }
+                    batches.pop();
+                } else if output.batch_index != previous.batch_index + 1
+                    || output.initial_leaves_count != 0
+                {
+                    return Err(format!(
+                        "batch {} does not start right after batch {}",
+                        output.batch_index, previous.batch_index
+                    ));
+                }
+            }
+
+            batches.push(BatchMmrState {
+                batch_index: output.batch_index,
+                latest_mmr_block: output.latest_mmr_block,
+                latest_mmr_block_hash: output.latest_mmr_block_hash.clone(),
+                root_hash: output.root_hash.clone(),
+                leaves_count: output.leaves_count,
+            });
+            previous = Some(output);
+        }
+
+        Ok(Self {
+            version: MMR_CHAIN_JOURNAL_VERSION,
+            append_image_id,
+            first_block: first.first_mmr_block,
+            batch_link: first.batch_link.clone(),
+            initial_leaves_count: first.initial_leaves_count,
+            initial_root_hash: first.initial_root_hash.clone(),
+            batches,
+        })
+    }
 }
 
 // CombinedInput
@@ -261,22 +438,28 @@ pub struct GuestProof {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BlocksValidityInput {
     chain_id: u64,
+    batch_size: u64,
     headers: Vec<BlockHeader>,
     mmr_input: MMRInput,
     proofs: Vec<GuestProof>,
+    strict: bool,
 }
 impl BlocksValidityInput {
     pub fn new(
         chain_id: u64,
+        batch_size: u64,
         headers: Vec<BlockHeader>,
         mmr_input: MMRInput,
         proofs: Vec<GuestProof>,
+        strict: bool,
     ) -> Self {
         Self {
             chain_id,
+            batch_size,
             headers,
             mmr_input,
             proofs,
+            strict,
         }
     }
 
@@ -284,6 +467,10 @@ impl BlocksValidityInput {
         self.chain_id
     }
 
+    pub fn batch_size(&self) -> u64 {
+        self.batch_size
+    }
+
     pub fn headers(&self) -> &Vec<BlockHeader> {
         &self.headers
     }
@@ -295,6 +482,524 @@ impl BlocksValidityInput {
     pub fn mmr_input(&self) -> &MMRInput {
         &self.mmr_input
     }
+
+    /// In strict mode the guest aborts on invalid blocks instead of committing
+    /// a journal with `ok == false`.
+    pub fn strict(&self) -> bool {
+        self.strict
+    }
+}
+
+/// Version of the [`FeeProofJournal`] layout committed by the fee extraction guest.
+pub const FEE_PROOF_JOURNAL_VERSION: u32 = 2;
+
+/// Journal of the fee extraction guest, committed exactly once per batch.
+///
+/// `mmr_root` and `elements_count` describe the batch MMR the blocks were proven
+/// against, so the journal can be matched with the root the Fossil Store holds for
+/// `batch_index`. When the blocks fail validation in non-strict mode, `ok` is
+/// `false` and `fees` is empty.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeProofJournal {
+    pub version: u32,
+    pub ok: bool,
+    pub mmr_root: String,
+    pub elements_count: usize,
+    pub batch_index: u64,
+    pub block_range: (u64, u64),
+    pub fees: Vec<BlockFeeRecord>,
+}
+
+impl FeeProofJournal {
+    pub fn valid(
+        mmr_root: String,
+        elements_count: usize,
+        batch_index: u64,
+        block_range: (u64, u64),
+        fees: Vec<BlockFeeRecord>,
+    ) -> Self {
+        Self {
+            version: FEE_PROOF_JOURNAL_VERSION,
+            ok: true,
+            mmr_root,
+            elements_count,
+            batch_index,
+            block_range,
+            fees,
+        }
+    }
+
+    pub fn invalid(
+        mmr_root: String,
+        elements_count: usize,
+        batch_index: u64,
+        block_range: (u64, u64),
+    ) -> Self {
+        Self {
+            version: FEE_PROOF_JOURNAL_VERSION,
+            ok: false,
+            mmr_root,
+            elements_count,
+            batch_index,
+            block_range,
+            fees: Vec::new(),
+        }
+    }
+}
+
+/// Fee-related fields of a single block, committed by the fee extraction guest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockFeeRecord {
+    pub block_number: u64,
+    pub timestamp: u64,
+    pub base_fee_per_gas: u64,
+    pub gas_used: u64,
+    pub gas_limit: u64,
+    /// `None` for pre-Cancun blocks
+    pub blob_gas_used: Option<u64>,
+    /// `None` for pre-Cancun blocks
+    pub excess_blob_gas: Option<u64>,
+}
+
+impl BlockFeeRecord {
+    pub fn from_header(header: &BlockHeader) -> Result<Self, String> {
+        let timestamp = header
+            .timestamp
+            .as_deref()
+            .ok_or_else(|| format!("block {} has no timestamp", header.number))?;
+
+        Ok(Self {
+            block_number: header.number as u64,
+            timestamp: parse_hex_u64(timestamp)?,
+            base_fee_per_gas: parse_optional_hex_u64(header.base_fee_per_gas.as_deref())?
+                .unwrap_or_default(),
+            gas_used: header.gas_used as u64,
+            gas_limit: header.gas_limit as u64,
+            blob_gas_used: parse_optional_hex_u64(header.blob_gas_used.as_deref())?,
+            excess_blob_gas: parse_optional_hex_u64(header.excess_blob_gas.as_deref())?,
+        })
+    }
+}
+
+fn parse_hex_u64(value: &str) -> Result<u64, String> {
+    let digits = value.trim_start_matches("0x");
+    if digits.is_empty() {
+        return Ok(0);
+    }
+    u64::from_str_radix(digits, 16).map_err(|e| format!("invalid hex value {}: {}", value, e))
+}
+
+// Missing and empty fields are both stored for headers that predate the fork
+fn parse_optional_hex_u64(value: Option<&str>) -> Result<Option<u64>, String> {
+    match value {
+        Some(value) if !value.is_empty() => parse_hex_u64(value).map(Some),
+        _ => Ok(None),
+    }
+}
+
+/// Input of the fee aggregation guest: the journals of the per-batch fee
+/// receipts, which the host adds as assumptions, and the image id that produced them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeeAggregationInput {
+    fee_image_id: [u32; 8],
+    journals: Vec<FeeProofJournal>,
+}
+
+impl FeeAggregationInput {
+    pub fn new(fee_image_id: [u32; 8], journals: Vec<FeeProofJournal>) -> Self {
+        Self {
+            fee_image_id,
+            journals,
+        }
+    }
+
+    pub fn fee_image_id(&self) -> [u32; 8] {
+        self.fee_image_id
+    }
+
+    pub fn journals(&self) -> &Vec<FeeProofJournal> {
+        &self.journals
+    }
+}
+
+/// MMR a batch of an aggregated fee proof was proven against.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchRoot {
+    pub batch_index: u64,
+    pub mmr_root: String,
+    pub elements_count: usize,
+}
+
+/// Version of the [`AggregatedFeeJournal`] layout.
+pub const AGGREGATED_FEE_JOURNAL_VERSION: u32 = 1;
+
+/// Journal of the fee aggregation guest: the fees of every batch of a range,
+/// in block order, with the MMR root each batch was proven against.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AggregatedFeeJournal {
+    pub version: u32,
+    pub fee_image_id: [u32; 8],
+    pub block_range: (u64, u64),
+    pub batches: Vec<BatchRoot>,
+    pub fees: Vec<BlockFeeRecord>,
+}
+
+impl AggregatedFeeJournal {
+    /// Concatenates per-batch journals, which must be valid and ordered by
+    /// strictly increasing batch index.
+    pub fn aggregate(fee_image_id: [u32; 8], journals: &[FeeProofJournal]) -> Result<Self, String> {
+        let first = journals.first().ok_or("no fee journals to aggregate")?;
+        let last = &journals[journals.len() - 1];
+
+        let mut batches = Vec::with_capacity(journals.len());
+        let mut fees = Vec::new();
+        let mut previous: Option<&FeeProofJournal> = None;
+
+        for journal in journals {
+            if journal.version != FEE_PROOF_JOURNAL_VERSION {
+                return Err(format!(
+                    "unsupported fee journal version {} for batch {}",
+                    journal.version, journal.batch_index
+                ));
+            }
+            if !journal.ok {
+                return Err(format!(
+                    "batch {} attests invalid blocks",
+                    journal.batch_index
+                ));
+            }
+            if let Some(previous) = previous {
+                if journal.batch_index <= previous.batch_index
+                    || journal.block_range.0 <= previous.block_range.1
+                {
+                    return Err(format!(
+                        "batch {} is out of order after batch {}",
+                        journal.batch_index, previous.batch_index
+                    ));
+                }
+            }
+
+            batches.push(BatchRoot {
+                batch_index: journal.batch_index,
+                mmr_root: journal.mmr_root.clone(),
+                elements_count: journal.elements_count,
+            });
+            fees.extend(journal.fees.iter().cloned());
+            previous = Some(journal);
+        }
+
+        Ok(Self {
+            version: AGGREGATED_FEE_JOURNAL_VERSION,
+            fee_image_id,
+            block_range: (first.block_range.0, last.block_range.1),
+            batches,
+            fees,
+        })
+    }
+}
+
+/// Scale of the fixed-point values in [`FeeStats`]: a value `v` stands for `v / FEE_STATS_SCALE` wei.
+pub const FEE_STATS_SCALE: u128 = 1_000_000;
+
+/// Blocks of the range a fee statistics proof must cover, counted from the
+/// first block of the range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FeeStatsSampling {
+    EveryBlock,
+    EveryNth { step: u64 },
+}
+
+impl FeeStatsSampling {
+    fn step(&self) -> Result<u64, String> {
+        match *self {
+            Self::EveryBlock => Ok(1),
+            Self::EveryNth { step: 0 } => Err("sampling step must be greater than 0".to_string()),
+            Self::EveryNth { step } => Ok(step),
+        }
+    }
+}
+
+/// Input of the fee statistics guest: the requested range and how it is
+/// sampled, the blocks of every batch touched by the range, each proven
+/// against its own MMR, and the percentiles to report.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeeStatsInput {
+    block_range: (u64, u64),
+    sampling: FeeStatsSampling,
+    batches: Vec<BlocksValidityInput>,
+    percentiles: Vec<u8>,
+}
+
+impl FeeStatsInput {
+    pub fn new(
+        block_range: (u64, u64),
+        sampling: FeeStatsSampling,
+        batches: Vec<BlocksValidityInput>,
+        percentiles: Vec<u8>,
+    ) -> Self {
+        Self {
+            block_range,
+            sampling,
+            batches,
+            percentiles,
+        }
+    }
+
+    pub fn block_range(&self) -> (u64, u64) {
+        self.block_range
+    }
+
+    pub fn sampling(&self) -> FeeStatsSampling {
+        self.sampling
+    }
+
+    pub fn batches(&self) -> &Vec<BlocksValidityInput> {
+        &self.batches
+    }
+
+    pub fn percentiles(&self) -> &Vec<u8> {
+        &self.percentiles
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeePercentile {
+    pub percentile: u8,
+    pub base_fee_per_gas: u64,
+}
+
+/// Aggregate base fee statistics over a set of blocks.
+///
+/// `twap`, `mean` and `variance` are fixed-point values scaled by
+/// [`FEE_STATS_SCALE`]; `min`, `max` and the percentiles are exact block fees in wei.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeStats {
+    pub block_count: u64,
+    pub first_block: u64,
+    pub last_block: u64,
+    pub start_timestamp: u64,
+    pub end_timestamp: u64,
+    pub min: u64,
+    pub max: u64,
+    pub twap: u128,
+    pub mean: u128,
+    pub variance: u128,
+    pub percentiles: Vec<FeePercentile>,
+}
+
+impl FeeStats {
+    /// Computes the statistics of `records`, which are ordered by block number
+    /// first and must not repeat a block.
+    ///
+    /// The TWAP weights every fee by the time until the next block in the set;
+    /// when all blocks share a timestamp it falls back to the mean. Percentiles
+    /// use the nearest-rank method. Sets whose scaled values do not fit in a
+    /// `u128`, such as a variance over fees above about 1.8e13 wei, are
+    /// rejected rather than wrapped.
+    pub fn compute(records: &[BlockFeeRecord], percentiles: &[u8]) -> Result<Self, String> {
+        if records.is_empty() {
+            return Err("cannot compute fee statistics of an empty set".to_string());
+        }
+        if let Some(percentile) = percentiles.iter().find(|&&p| p > 100) {
+            return Err(format!("invalid percentile {}", percentile));
+        }
+
+        let mut records = records.to_vec();
+        records.sort_by_key(|record| record.block_number);
+        if let Some(pair) = records
+            .windows(2)
+            .find(|pair| pair[0].block_number == pair[1].block_number)
+        {
+            return Err(format!(
+                "block {} appears more than once",
+                pair[0].block_number
+            ));
+        }
+        let first = &records[0];
+        let last = &records[records.len() - 1];
+        let count = records.len() as u128;
+        let overflow = || "fee statistics overflow u128".to_string();
+
+        let sum: u128 = records.iter().map(|r| r.base_fee_per_gas as u128).sum();
+        let mean = sum.checked_mul(FEE_STATS_SCALE).ok_or_else(overflow)? / count;
+
+        // Each squared deviation is rescaled before summing
+        let mut squared_deviations: u128 = 0;
+        for record in &records {
+            let deviation = (record.base_fee_per_gas as u128 * FEE_STATS_SCALE).abs_diff(mean);
+            let squared = deviation.checked_mul(deviation).ok_or_else(overflow)? / FEE_STATS_SCALE;
+            squared_deviations = squared_deviations
+                .checked_add(squared)
+                .ok_or_else(overflow)?;
+        }
+        let variance = squared_deviations / count;
+
+        let duration = last.timestamp.saturating_sub(first.timestamp) as u128;
+        let twap = if duration == 0 {
+            mean
+        } else {
+            let weighted: u128 = records
+                .windows(2)
+                .map(|pair| {
+                    let weight = pair[1].timestamp.saturating_sub(pair[0].timestamp) as u128;
+                    pair[0].base_fee_per_gas as u128 * weight
+                })
+                .sum();
+            weighted.checked_mul(FEE_STATS_SCALE).ok_or_else(overflow)? / duration
+        };
+
+        let mut fees: Vec<u64> = records.iter().map(|r| r.base_fee_per_gas).collect();
+        fees.sort_unstable();
+        let percentiles = percentiles
+            .iter()
+            .map(|&percentile| {
+                let rank = (percentile as usize * fees.len()).div_ceil(100);
+                FeePercentile {
+                    percentile,
+                    base_fee_per_gas: fees[rank.max(1) - 1],
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            block_count: records.len() as u64,
+            first_block: first.block_number,
+            last_block: last.block_number,
+            start_timestamp: first.timestamp,
+            end_timestamp: last.timestamp,
+            min: fees[0],
+            max: fees[fees.len() - 1],
+            twap,
+            mean,
+            variance,
+            percentiles,
+        })
+    }
+}
+
+/// Version of the [`FeeStatsJournal`] layout.
+pub const FEE_STATS_JOURNAL_VERSION: u32 = 2;
+
+/// MMR and blocks of one batch of a fee statistics proof.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeStatsBatch {
+    pub batch_index: u64,
+    pub mmr_root: String,
+    pub elements_count: usize,
+    pub block_range: (u64, u64),
+}
+
+/// Journal of the fee statistics guest.
+///
+/// Like [`FeeProofJournal`], it holds the MMR root every batch was proven
+/// against, so the statistics can be matched with the roots the Fossil Store
+/// holds for each `batch_index`. `block_range` is the requested range, which
+/// the statistics cover completely under `sampling`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeStatsJournal {
+    pub version: u32,
+    pub chain_id: u64,
+    pub block_range: (u64, u64),
+    pub sampling: FeeStatsSampling,
+    pub batches: Vec<FeeStatsBatch>,
+    pub stats: FeeStats,
+}
+
+impl FeeStatsJournal {
+    /// Computes the statistics of `records`, the blocks of `batches`. Batches
+    /// must be ordered by strictly increasing index without overlapping, and
+    /// records by strictly increasing block number within their batch ranges.
+    /// The records must be exactly the blocks `sampling` selects from
+    /// `block_range`, so that none can be left out of the statistics.
+    pub fn new(
+        chain_id: u64,
+        block_range: (u64, u64),
+        sampling: FeeStatsSampling,
+        batches: Vec<FeeStatsBatch>,
+        records: &[BlockFeeRecord],
+        percentiles: &[u8],
+    ) -> Result<Self, String> {
+        if batches.is_empty() {
+            return Err("no batches to compute fee statistics of".to_string());
+        }
+
+        for pair in batches.windows(2) {
+            if pair[1].batch_index <= pair[0].batch_index
+                || pair[1].block_range.0 <= pair[0].block_range.1
+            {
+                return Err(format!(
+                    "batch {} is out of order after batch {}",
+                    pair[1].batch_index, pair[0].batch_index
+                ));
+            }
+        }
+
+        let mut previous: Option<u64> = None;
+        for record in records {
+            if previous.is_some_and(|previous| record.block_number <= previous) {
+                return Err(format!(
+                    "block {} is out of order or repeated",
+                    record.block_number
+                ));
+            }
+            if !batches.iter().any(|batch| {
+                (batch.block_range.0..=batch.block_range.1).contains(&record.block_number)
+            }) {
+                return Err(format!(
+                    "block {} is outside the proven batches",
+                    record.block_number
+                ));
+            }
+            previous = Some(record.block_number);
+        }
+
+        check_coverage(block_range, sampling, records)?;
+
+        Ok(Self {
+            version: FEE_STATS_JOURNAL_VERSION,
+            chain_id,
+            block_range,
+            sampling,
+            stats: FeeStats::compute(records, percentiles)?,
+            batches,
+        })
+    }
+}
+
+/// Checks that `records`, ordered by block number, are exactly the blocks
+/// `sampling` selects from `block_range`.
+fn check_coverage(
+    (from_block, to_block): (u64, u64),
+    sampling: FeeStatsSampling,
+    records: &[BlockFeeRecord],
+) -> Result<(), String> {
+    if from_block > to_block {
+        return Err(format!(
+            "invalid block range {} to {}",
+            from_block, to_block
+        ));
+    }
+
+    let step = sampling.step()?;
+    let expected_count = (to_block - from_block) / step + 1;
+    for (position, record) in (0..).zip(records) {
+        let expected = (position < expected_count).then(|| from_block + position * step);
+        if expected != Some(record.block_number) {
+            return Err(format!(
+                "block {} is not sampled from the range {} to {}",
+                record.block_number, from_block, to_block
+            ));
+        }
+    }
+    if (records.len() as u64) < expected_count {
+        return Err(format!(
+            "block {} of the range {} to {} is missing",
+            from_block + records.len() as u64 * step,
+            from_block,
+            to_block
+        ));
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -375,11 +1080,392 @@ mod tests {
             elements_count: 10,
         };
 
-        let input = BlocksValidityInput::new(1, Vec::new(), mmr_input, vec![guest_proof]);
+        let input =
+            BlocksValidityInput::new(1, 1024, Vec::new(), mmr_input, vec![guest_proof], true);
 
         assert_eq!(input.chain_id(), 1);
+        assert_eq!(input.batch_size(), 1024);
+        assert!(input.strict());
         assert!(input.headers().is_empty());
         assert_eq!(input.proofs().len(), 1);
         assert_eq!(input.mmr_input().elements_count(), 10);
     }
+
+    #[test]
+    fn test_block_fee_record_from_header() {
+        let header = BlockHeader {
+            number: 19426587,
+            gas_used: 14_000_000,
+            gas_limit: 30_000_000,
+            timestamp: Some("0x65f0a5a3".to_string()),
+            base_fee_per_gas: Some("0x6a2c5ef7a".to_string()),
+            blob_gas_used: Some("0x20000".to_string()),
+            excess_blob_gas: Some("".to_string()),
+            ..Default::default()
+        };
+
+        let record = BlockFeeRecord::from_header(&header).unwrap();
+
+        assert_eq!(record.block_number, 19426587);
+        assert_eq!(record.timestamp, 0x65f0a5a3);
+        assert_eq!(record.base_fee_per_gas, 0x6a2c5ef7a);
+        assert_eq!(record.gas_used, 14_000_000);
+        assert_eq!(record.gas_limit, 30_000_000);
+        assert_eq!(record.blob_gas_used, Some(0x20000));
+        assert_eq!(record.excess_blob_gas, None);
+    }
+
+    #[test]
+    fn test_block_fee_record_invalid_header() {
+        let header = BlockHeader {
+            number: 1,
+            ..Default::default()
+        };
+        assert!(BlockFeeRecord::from_header(&header).is_err());
+
+        let header = BlockHeader {
+            number: 1,
+            timestamp: Some("0xzz".to_string()),
+            ..Default::default()
+        };
+        assert!(BlockFeeRecord::from_header(&header).is_err());
+    }
+
+    #[test]
+    fn test_fee_proof_journal() {
+        let valid = FeeProofJournal::valid(
+            "0xroot".to_string(),
+            7,
+            2,
+            (2048, 2050),
+            vec![fee_record(2048, 0, 1)],
+        );
+        assert_eq!(valid.version, FEE_PROOF_JOURNAL_VERSION);
+        assert!(valid.ok);
+        assert_eq!(valid.elements_count, 7);
+        assert_eq!(valid.fees.len(), 1);
+
+        let invalid = FeeProofJournal::invalid("0xroot".to_string(), 7, 2, (2048, 2050));
+        assert_eq!(invalid.version, FEE_PROOF_JOURNAL_VERSION);
+        assert!(!invalid.ok);
+        assert!(invalid.fees.is_empty());
+    }
+
+    fn fee_record(block_number: u64, timestamp: u64, base_fee_per_gas: u64) -> BlockFeeRecord {
+        BlockFeeRecord {
+            block_number,
+            timestamp,
+            base_fee_per_gas,
+            gas_used: 0,
+            gas_limit: 0,
+            blob_gas_used: None,
+            excess_blob_gas: None,
+        }
+    }
+
+    #[test]
+    fn test_fee_stats() {
+        let records = vec![
+            fee_record(3, 24, 40),
+            fee_record(1, 0, 10),
+            fee_record(2, 12, 20),
+            fee_record(4, 48, 30),
+        ];
+
+        let stats = FeeStats::compute(&records, &[0, 50, 90, 100]).unwrap();
+
+        assert_eq!(stats.block_count, 4);
+        assert_eq!((stats.first_block, stats.last_block), (1, 4));
+        assert_eq!((stats.start_timestamp, stats.end_timestamp), (0, 48));
+        assert_eq!((stats.min, stats.max), (10, 40));
+        assert_eq!(stats.mean, 25 * FEE_STATS_SCALE);
+        assert_eq!(stats.variance, 125 * FEE_STATS_SCALE);
+        // (10 * 12 + 20 * 12 + 40 * 24) / 48
+        assert_eq!(stats.twap, 27_500_000);
+        assert_eq!(
+            stats
+                .percentiles
+                .iter()
+                .map(|p| (p.percentile, p.base_fee_per_gas))
+                .collect::<Vec<_>>(),
+            vec![(0, 10), (50, 20), (90, 40), (100, 40)]
+        );
+    }
+
+    #[test]
+    fn test_fee_stats_single_timestamp() {
+        let records = vec![fee_record(1, 100, 7), fee_record(2, 100, 8)];
+
+        let stats = FeeStats::compute(&records, &[]).unwrap();
+
+        assert_eq!(stats.twap, stats.mean);
+        assert_eq!(stats.mean, 7_500_000);
+        assert!(stats.percentiles.is_empty());
+    }
+
+    #[test]
+    fn test_fee_stats_invalid_input() {
+        assert!(FeeStats::compute(&[], &[50]).is_err());
+        assert!(FeeStats::compute(&[fee_record(1, 0, 1)], &[101]).is_err());
+        assert!(FeeStats::compute(&[fee_record(1, 0, 1), fee_record(1, 0, 1)], &[]).is_err());
+    }
+
+    #[test]
+    fn test_fee_stats_extreme_fees() {
+        // 1e13 wei still fits: each scaled squared deviation stays below u128::MAX
+        let stats = FeeStats::compute(
+            &[fee_record(1, 0, 0), fee_record(2, 12, 10_000_000_000_000)],
+            &[],
+        )
+        .unwrap();
+        assert_eq!(stats.variance, 25_000_000_000_000_000_000_000_000_000_000);
+
+        let extreme = [fee_record(1, 0, 0), fee_record(2, 12, u64::MAX)];
+        assert!(FeeStats::compute(&extreme, &[50]).is_err());
+    }
+
+    fn stats_batch(batch_index: u64, block_range: (u64, u64)) -> FeeStatsBatch {
+        FeeStatsBatch {
+            batch_index,
+            mmr_root: format!("0x{:x}", batch_index),
+            elements_count: 1,
+            block_range,
+        }
+    }
+
+    fn fee_records(blocks: &[u64]) -> Vec<BlockFeeRecord> {
+        blocks
+            .iter()
+            .map(|&block| fee_record(block, block * 12, block * 10))
+            .collect()
+    }
+
+    #[test]
+    fn test_fee_stats_journal() {
+        use FeeStatsSampling::{EveryBlock, EveryNth};
+
+        let batches = vec![stats_batch(0, (1, 2)), stats_batch(1, (3, 5))];
+        let journal_of = |sampling, blocks: &[u64]| {
+            FeeStatsJournal::new(
+                1,
+                (1, 5),
+                sampling,
+                batches.clone(),
+                &fee_records(blocks),
+                &[50],
+            )
+        };
+
+        let journal = journal_of(EveryBlock, &[1, 2, 3, 4, 5]).unwrap();
+        assert_eq!(journal.version, FEE_STATS_JOURNAL_VERSION);
+        assert_eq!(journal.block_range, (1, 5));
+        assert_eq!(journal.sampling, EveryBlock);
+        assert_eq!(journal.batches, batches);
+        assert_eq!(journal.stats.block_count, 5);
+
+        let journal = journal_of(EveryNth { step: 2 }, &[1, 3, 5]).unwrap();
+        assert_eq!(journal.sampling, EveryNth { step: 2 });
+        assert_eq!(journal.stats.block_count, 3);
+
+        // Dropping a block, e.g. one with a high fee, leaves the range uncovered
+        assert!(journal_of(EveryBlock, &[1, 2, 3, 5]).is_err());
+        assert!(journal_of(EveryNth { step: 2 }, &[1, 3]).is_err());
+        // Blocks must be counted from the start of the range
+        assert!(journal_of(EveryNth { step: 2 }, &[2, 4]).is_err());
+        assert!(journal_of(EveryNth { step: 2 }, &[1, 2, 3, 5]).is_err());
+        assert!(journal_of(EveryNth { step: 0 }, &[1]).is_err());
+
+        // A repeated block would skew the statistics
+        assert!(journal_of(EveryBlock, &[1, 1, 2, 3, 4, 5]).is_err());
+
+        // Block 3 is in no batch
+        let gapped = vec![stats_batch(0, (1, 2)), stats_batch(1, (4, 5))];
+        let records = fee_records(&[1, 2, 3, 4, 5]);
+        assert!(FeeStatsJournal::new(1, (1, 5), EveryBlock, gapped, &records, &[]).is_err());
+
+        let overlapping = vec![stats_batch(0, (1, 4)), stats_batch(1, (4, 5))];
+        assert!(FeeStatsJournal::new(1, (1, 5), EveryBlock, overlapping, &records, &[]).is_err());
+    }
+
+    #[test]
+    fn test_aggregated_fee_journal() {
+        let journals = vec![
+            FeeProofJournal::valid(
+                "0xa".to_string(),
+                3,
+                1,
+                (1024, 1030),
+                vec![fee_record(1024, 0, 1), fee_record(1030, 72, 2)],
+            ),
+            FeeProofJournal::valid(
+                "0xb".to_string(),
+                4,
+                2,
+                (2048, 2048),
+                vec![fee_record(2048, 144, 3)],
+            ),
+        ];
+
+        let aggregated = AggregatedFeeJournal::aggregate([7; 8], &journals).unwrap();
+
+        assert_eq!(aggregated.version, AGGREGATED_FEE_JOURNAL_VERSION);
+        assert_eq!(aggregated.fee_image_id, [7; 8]);
+        assert_eq!(aggregated.block_range, (1024, 2048));
+        assert_eq!(
+            aggregated
+                .batches
+                .iter()
+                .map(|b| (b.batch_index, b.mmr_root.as_str(), b.elements_count))
+                .collect::<Vec<_>>(),
+            vec![(1, "0xa", 3), (2, "0xb", 4)]
+        );
+        assert_eq!(
+            aggregated
+                .fees
+                .iter()
+                .map(|f| f.block_number)
+                .collect::<Vec<_>>(),
+            vec![1024, 1030, 2048]
+        );
+    }
+
+    #[test]
+    fn test_aggregated_fee_journal_rejects_invalid_input() {
+        let valid = |batch_index: u64| {
+            FeeProofJournal::valid(
+                "0x0".to_string(),
+                1,
+                batch_index,
+                (batch_index * 1024, batch_index * 1024),
+                vec![],
+            )
+        };
+
+        assert!(AggregatedFeeJournal::aggregate([0; 8], &[]).is_err());
+        assert!(AggregatedFeeJournal::aggregate([0; 8], &[valid(2), valid(1)]).is_err());
+        assert!(AggregatedFeeJournal::aggregate([0; 8], &[valid(1), valid(1)]).is_err());
+        assert!(AggregatedFeeJournal::aggregate(
+            [0; 8],
+            &[
+                valid(1),
+                FeeProofJournal::invalid("0x0".to_string(), 1, 2, (2048, 2048))
+            ]
+        )
+        .is_err());
+
+        let mut old_version = valid(1);
+        old_version.version = FEE_PROOF_JOURNAL_VERSION - 1;
+        assert!(AggregatedFeeJournal::aggregate([0; 8], &[old_version]).is_err());
+    }
+
+    fn append_output(
+        batch_index: u64,
+        blocks: (u64, u64),
+        batch_link: Option<&str>,
+        leaves: (usize, usize),
+        roots: (&str, &str),
+    ) -> GuestOutput {
+        GuestOutput::new(
+            batch_index,
+            blocks.1,
+            format!("hash_{}", blocks.1),
+            roots.1.to_string(),
+            leaves.1,
+        )
+        .with_chain_link(
+            blocks.0,
+            batch_link.map(str::to_string),
+            leaves.0,
+            roots.0.to_string(),
+        )
+    }
+
+    #[test]
+    fn test_mmr_chain_journal() {
+        let outputs = vec![
+            append_output(1, (4, 5), Some("hash_3"), (0, 2), ("0xe", "0xa")),
+            append_output(1, (6, 7), Some("hash_5"), (2, 4), ("0xa", "0xb")),
+            append_output(2, (8, 11), Some("hash_7"), (0, 4), ("0xe", "0xc")),
+        ];
+
+        let journal = MmrChainJournal::chain([3; 8], &outputs).unwrap();
+
+        assert_eq!(journal.version, MMR_CHAIN_JOURNAL_VERSION);
+        assert_eq!(journal.append_image_id, [3; 8]);
+        assert_eq!(journal.first_block, 4);
+        assert_eq!(journal.batch_link.as_deref(), Some("hash_3"));
+        assert_eq!(journal.initial_leaves_count, 0);
+        assert_eq!(journal.initial_root_hash, "0xe");
+        assert_eq!(
+            journal.batches,
+            vec![
+                BatchMmrState {
+                    batch_index: 1,
+                    latest_mmr_block: 7,
+                    latest_mmr_block_hash: "hash_7".to_string(),
+                    root_hash: "0xb".to_string(),
+                    leaves_count: 4,
+                },
+                BatchMmrState {
+                    batch_index: 2,
+                    latest_mmr_block: 11,
+                    latest_mmr_block_hash: "hash_11".to_string(),
+                    root_hash: "0xc".to_string(),
+                    leaves_count: 4,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mmr_chain_journal_rejects_broken_links() {
+        let first = append_output(1, (4, 7), Some("hash_3"), (0, 4), ("0xe", "0xa"));
+
+        assert!(MmrChainJournal::chain([0; 8], &[]).is_err());
+        // Gap between the two ranges
+        assert!(MmrChainJournal::chain(
+            [0; 8],
+            &[
+                first.clone(),
+                append_output(2, (9, 11), Some("hash_7"), (0, 3), ("0xe", "0xb"))
+            ]
+        )
+        .is_err());
+        // Batch link not matching the previous block hash
+        assert!(MmrChainJournal::chain(
+            [0; 8],
+            &[
+                first.clone(),
+                append_output(2, (8, 11), Some("hash_x"), (0, 4), ("0xe", "0xb"))
+            ]
+        )
+        .is_err());
+        // Missing batch link for a non-genesis batch
+        assert!(MmrChainJournal::chain(
+            [0; 8],
+            &[
+                first.clone(),
+                append_output(2, (8, 11), None, (0, 4), ("0xe", "0xb"))
+            ]
+        )
+        .is_err());
+        // Same batch not continuing from the previous root
+        assert!(MmrChainJournal::chain(
+            [0; 8],
+            &[
+                append_output(1, (4, 5), Some("hash_3"), (0, 2), ("0xe", "0xa")),
+                append_output(1, (6, 7), Some("hash_5"), (2, 4), ("0xf", "0xb"))
+            ]
+        )
+        .is_err());
+        // Next batch not starting from an empty MMR
+        assert!(MmrChainJournal::chain(
+            [0; 8],
+            &[
+                first,
+                append_output(2, (8, 11), Some("hash_7"), (4, 8), ("0xa", "0xb"))
+            ]
+        )
+        .is_err());
+    }
 }
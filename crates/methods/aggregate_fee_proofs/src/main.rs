@@ -0,0 +1,20 @@
+// main.rs
+use risc0_zkvm::{guest::env, serde::to_vec};
+use guest_types::{AggregatedFeeJournal, FeeAggregationInput};
+
+fn main() {
+    // Read combined input
+    let input: FeeAggregationInput = env::read();
+
+    // Every journal must come from a receipt of the fee guest, supplied by the
+    // host as an assumption
+    for journal in input.journals() {
+        let journal_words = to_vec(journal).expect("Failed to serialize fee journal");
+        env::verify(input.fee_image_id(), &journal_words).expect("Failed to verify fee receipt");
+    }
+
+    let output = AggregatedFeeJournal::aggregate(input.fee_image_id(), input.journals())
+        .expect("Failed to aggregate fee journals");
+
+    env::commit(&output);
+}
@@ -0,0 +1,21 @@
+// main.rs
+use risc0_zkvm::{guest::env, serde::to_vec};
+use guest_types::{MmrChainInput, MmrChainJournal};
+
+fn main() {
+    // Read chain input
+    let input: MmrChainInput = env::read();
+
+    // Every output must come from a receipt of the mmr-append guest, supplied
+    // by the host as an assumption
+    for output in input.outputs() {
+        let output_words = to_vec(output).expect("Failed to serialize append output");
+        env::verify(input.append_image_id(), &output_words)
+            .expect("Failed to verify append receipt");
+    }
+
+    let journal = MmrChainJournal::chain(input.append_image_id(), input.outputs())
+        .expect("Failed to chain append outputs");
+
+    env::commit(&journal);
+}
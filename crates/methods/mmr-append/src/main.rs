@@ -18,6 +18,9 @@ fn main() {
         input.mmr_input().elements_count(),
         input.mmr_input().leaves_count(),
     );
+    // Root before the append, committed so that consecutive proofs can be chained
+    let initial_leaves_count = mmr.get_leaves_count();
+    let initial_root_hash = mmr.calculate_root_hash(mmr.get_elements_count()).unwrap();
     // let mut append_results = Vec::new();
     // Append block hashes to MMR
     for (_, header) in input.headers().iter().enumerate() {
@@ -75,6 +78,16 @@ fn main() {
         last_block_hash,
         root_hash,
         mmr.get_leaves_count(),
+    )
+    .with_chain_link(
+        first_block_number,
+        // Only checked against the first parent hash outside the genesis batch
+        input
+            .batch_link()
+            .filter(|_| first_batch_index > 0)
+            .map(|link| link.to_string()),
+        initial_leaves_count,
+        initial_root_hash,
     );
     // Commit the output
     env::commit(&output);
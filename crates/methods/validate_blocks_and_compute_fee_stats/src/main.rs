@@ -0,0 +1,78 @@
+// main.rs
+use eth_rlp_verify::are_blocks_valid;
+use risc0_zkvm::guest::env;
+use guest_mmr::core::GuestMMR;
+use guest_types::{BlockFeeRecord, FeeStatsBatch, FeeStatsInput, FeeStatsJournal};
+
+fn main() {
+    // Read combined input
+    let input: FeeStatsInput = env::read();
+
+    let chain_id = input.batches().first().expect("Batches list cannot be empty").chain_id();
+    let mut records: Vec<BlockFeeRecord> = Vec::new();
+    let mut batches: Vec<FeeStatsBatch> = Vec::new();
+
+    for batch in input.batches() {
+        assert_eq!(batch.chain_id(), chain_id, "Chain id mismatch");
+
+        // Verify block headers
+        assert!(
+            are_blocks_valid(batch.headers(), batch.chain_id()),
+            "Invalid block headers"
+        );
+
+        let first_header = batch.headers().first().expect("Headers list cannot be empty");
+        let last_header = batch.headers().last().unwrap();
+        let batch_index = first_header.number as u64 / batch.batch_size();
+
+        // Initialize MMR with the batch state
+        let mmr = GuestMMR::new(
+            batch.mmr_input().initial_peaks(),
+            batch.mmr_input().elements_count(),
+            batch.mmr_input().leaves_count(),
+        );
+        let elements_count = batch.mmr_input().elements_count();
+        let mmr_root = mmr
+            .calculate_root_hash(elements_count)
+            .expect("Failed to calculate MMR root");
+
+        // Every block must be included in the batch MMR
+        assert_eq!(batch.headers().len(), batch.proofs().len(), "Proofs count mismatch");
+        for (header, proof) in batch.headers().iter().zip(batch.proofs()) {
+            assert!(
+                header.number as u64 / batch.batch_size() == batch_index,
+                "Block {} is outside batch {}",
+                header.number,
+                batch_index
+            );
+
+            let included = mmr
+                .verify_proof(proof.clone(), header.block_hash.clone(), None)
+                .expect("Failed to verify MMR proof");
+            assert!(included, "Block {} is not included in the MMR", header.number);
+
+            records.push(BlockFeeRecord::from_header(header).expect("Invalid block header fields"));
+        }
+
+        batches.push(FeeStatsBatch {
+            batch_index,
+            mmr_root,
+            elements_count,
+            block_range: (first_header.number as u64, last_header.number as u64),
+        });
+    }
+
+    // Also rejects blocks out of order, repeated or missing from the sampled
+    // range, which would skew the statistics
+    let journal = FeeStatsJournal::new(
+        chain_id,
+        input.block_range(),
+        input.sampling(),
+        batches,
+        &records,
+        input.percentiles(),
+    )
+    .expect("Failed to compute fee stats");
+
+    env::commit(&journal);
+}
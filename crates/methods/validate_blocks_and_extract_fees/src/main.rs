@@ -2,37 +2,68 @@
 use eth_rlp_verify::are_blocks_valid;
 use risc0_zkvm::guest::env;
 use guest_mmr::core::GuestMMR;
-use guest_types::BlocksValidityInput;
+use guest_types::{BlockFeeRecord, BlocksValidityInput, FeeProofJournal};
 
 fn main() {
     // Read combined input
     let input: BlocksValidityInput = env::read();
 
-    // Verify block headers
-    if !are_blocks_valid(&input.headers(), input.chain_id()) {
-        env::commit(&false);
-    }
-    // Initialize MMR with previous state
+    let first_header = input.headers().first().expect("Headers list cannot be empty");
+    let last_header = input.headers().last().unwrap();
+    let block_range = (first_header.number as u64, last_header.number as u64);
+    let batch_index = block_range.0 / input.batch_size();
+
+    // Initialize MMR with the batch state
     let mmr = GuestMMR::new(
         input.mmr_input().initial_peaks(),
         input.mmr_input().elements_count(),
         input.mmr_input().leaves_count(),
     );
-    // Append block hashes to MMR
-    for (i, header) in input.headers().iter().enumerate() {
-        let block_hash = header.block_hash.clone();
-        let proof = input.proofs()[i].clone();
-        
-        if !mmr.verify_proof(proof, block_hash, None).unwrap() {
-            env::commit(&false);
+    let elements_count = input.mmr_input().elements_count();
+    let mmr_root = mmr
+        .calculate_root_hash(elements_count)
+        .expect("Failed to calculate MMR root");
+
+    let journal = match verify_blocks(&input, &mmr, batch_index) {
+        Ok(fees) => FeeProofJournal::valid(mmr_root, elements_count, batch_index, block_range, fees),
+        Err(reason) => {
+            assert!(!input.strict(), "{}", reason);
+            FeeProofJournal::invalid(mmr_root, elements_count, batch_index, block_range)
         }
+    };
+
+    env::commit(&journal);
+}
+
+fn verify_blocks(
+    input: &BlocksValidityInput,
+    mmr: &GuestMMR,
+    batch_index: u64,
+) -> Result<Vec<BlockFeeRecord>, String> {
+    // Verify block headers
+    if !are_blocks_valid(input.headers(), input.chain_id()) {
+        return Err("Invalid block headers".to_string());
     }
 
-    // Collect base_fee_per_gas values
-    let base_fees: Vec<String> = input.headers()
-        .iter()
-        .map(|header| header.base_fee_per_gas.clone().unwrap_or_default())
-        .collect();
+    if input.headers().len() != input.proofs().len() {
+        return Err("Proofs count mismatch".to_string());
+    }
+
+    let mut fees = Vec::new();
+    for (header, proof) in input.headers().iter().zip(input.proofs()) {
+        if header.number as u64 / input.batch_size() != batch_index {
+            return Err(format!("Block {} is outside batch {}", header.number, batch_index));
+        }
+
+        // Every block must be included in the batch MMR
+        match mmr.verify_proof(proof.clone(), header.block_hash.clone(), None) {
+            Ok(true) => {}
+            Ok(false) => return Err(format!("Block {} is not included in the MMR", header.number)),
+            Err(e) => return Err(format!("Failed to verify MMR proof: {:?}", e)),
+        }
+
+        fees.push(BlockFeeRecord::from_header(header)?);
+    }
 
-    env::commit(&base_fees);
+    Ok(fees)
 }
@@ -180,6 +180,20 @@ pub async fn initialize_mmr(
     Ok((store_manager, mmr, pool))
 }
 
+/// Opens an existing MMR database read-only, without creating the file or saving an MMR ID
+pub async fn open_mmr_read_only(store_path: &str) -> Result<(MMR, SqlitePool), MMRUtilsError> {
+    let url = format!("sqlite://{}?mode=ro", store_path);
+    let pool = SqlitePool::connect(&url).await?;
+    let store = Arc::new(SQLiteStore::new(&url, Some(false), None).await?);
+
+    // Without a saved ID the database holds no MMR, and a fresh ID reads as empty
+    let mmr_id = get_mmr_id(&pool).await?;
+    let hasher = Arc::new(Sha2Hasher::new());
+    let mmr = MMR::new(store, hasher, mmr_id);
+
+    Ok((mmr, pool))
+}
+
 /// Retrieves the MMR ID from the `mmr_metadata` table
 async fn get_mmr_id(pool: &SqlitePool) -> Result<Option<String>, sqlx::Error> {
     let row = sqlx::query("SELECT mmr_id FROM mmr_metadata LIMIT 1")
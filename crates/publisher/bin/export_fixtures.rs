@@ -0,0 +1,13 @@
+use clap::Parser;
+use common::initialize_logger_and_env;
+use publisher::cli::export_fixtures::{run, Args, Config};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    initialize_logger_and_env()?;
+
+    let config = Config::from_env()?;
+    let args = Args::parse();
+
+    run(config, args).await
+}
@@ -1,7 +1,12 @@
+use starknet::core::types::Felt;
 use starknet_handler::{account::StarknetAccount, provider::StarknetProvider};
 
 use crate::{
-    core::AccumulatorBuilder, errors::PublisherError, utils::Stark, validator::ValidatorBuilder,
+    core::AccumulatorBuilder,
+    db::{BlockId, HeaderSource, SamplingMode},
+    errors::PublisherError,
+    utils::{Groth16, Stark},
+    validator::{self, BatchInfo, BatchListing, BlockInclusionProof, ValidatorBuilder},
 };
 
 pub async fn prove_mmr_update(
@@ -11,17 +16,21 @@ pub async fn prove_mmr_update(
     store_address: &String,
     account_private_key: &String,
     account_address: &String,
+    starknet_chain_id: Option<Felt>,
     batch_size: u64,
     start_block: u64,
     end_block: u64,
     skip_proof_verification: bool,
+    header_source: HeaderSource,
 ) -> Result<(), PublisherError> {
     let starknet_provider = StarknetProvider::new(rpc_url)?;
-    let starknet_account = StarknetAccount::new(
+    let starknet_account = StarknetAccount::connect(
         starknet_provider.provider(),
         account_private_key,
         account_address,
-    )?;
+        starknet_chain_id,
+    )
+    .await?;
 
     let mut builder = AccumulatorBuilder::new(
         rpc_url,
@@ -31,6 +40,7 @@ pub async fn prove_mmr_update(
         starknet_account,
         batch_size,
         skip_proof_verification,
+        header_source,
     )
     .await
     .map_err(|e| {
@@ -61,16 +71,25 @@ pub async fn extract_fees(
     start_block: u64,
     end_block: u64,
     skip_proof_verification: Option<bool>,
+    sampling: SamplingMode,
+    header_source: HeaderSource,
 ) -> Result<Vec<Stark>, PublisherError> {
     let skip_proof = skip_proof_verification.unwrap_or(false);
 
-    let validator =
-        ValidatorBuilder::new(rpc_url, l2_store_address, chain_id, batch_size, skip_proof)
-            .await
-            .map_err(|e| {
-                tracing::error!(error = %e, "Failed to create ValidatorBuilder");
-                e
-            })?;
+    let validator = ValidatorBuilder::new(
+        rpc_url,
+        l2_store_address,
+        chain_id,
+        batch_size,
+        skip_proof,
+        header_source,
+    )
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, "Failed to create ValidatorBuilder");
+        e
+    })?
+    .with_sampling(sampling);
 
     let result = validator
         .validate_blocks_and_extract_fees(start_block, end_block)
@@ -84,3 +103,208 @@ pub async fn extract_fees(
 
     Ok(result)
 }
+
+/// Extracts fees like [`extract_fees`], returning a single receipt that
+/// aggregates the proofs of every batch in the range.
+pub async fn extract_fees_aggregated(
+    rpc_url: &String,
+    l2_store_address: &String,
+    chain_id: u64,
+    batch_size: u64,
+    start_block: u64,
+    end_block: u64,
+    skip_proof_verification: Option<bool>,
+    sampling: SamplingMode,
+    header_source: HeaderSource,
+) -> Result<Stark, PublisherError> {
+    let skip_proof = skip_proof_verification.unwrap_or(false);
+
+    let validator = ValidatorBuilder::new(
+        rpc_url,
+        l2_store_address,
+        chain_id,
+        batch_size,
+        skip_proof,
+        header_source,
+    )
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, "Failed to create ValidatorBuilder");
+        e
+    })?
+    .with_sampling(sampling);
+
+    let result = validator
+        .validate_blocks_and_extract_fees_aggregated(start_block, end_block)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to aggregate fee proofs");
+            e
+        })?;
+
+    tracing::info!("Successfully verified blocks validity and aggregated fee proofs");
+
+    Ok(result)
+}
+
+/// Extracts fees like [`extract_fees`] with Groth16 proofs, optionally submitting
+/// each of them to the Fossil verifier on Starknet.
+pub async fn extract_fees_groth16(
+    rpc_url: &String,
+    l2_store_address: &String,
+    chain_id: u64,
+    batch_size: u64,
+    start_block: u64,
+    end_block: u64,
+    sampling: SamplingMode,
+    header_source: HeaderSource,
+    submit_to: Option<(&StarknetAccount, &str)>,
+) -> Result<Vec<Groth16>, PublisherError> {
+    let validator = ValidatorBuilder::new(
+        rpc_url,
+        l2_store_address,
+        chain_id,
+        batch_size,
+        false,
+        header_source,
+    )
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, "Failed to create ValidatorBuilder");
+        e
+    })?
+    .with_sampling(sampling);
+
+    let proofs = validator
+        .validate_blocks_and_extract_fees_groth16(start_block, end_block)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to verify blocks validity and extract fees");
+            e
+        })?;
+
+    if let Some((account, verifier_address)) = submit_to {
+        for proof in &proofs {
+            let tx_hash = account
+                .verify_fee_proof(verifier_address, proof.calldata())
+                .await?;
+            tracing::info!(tx_hash = ?tx_hash, "Submitted fee proof to the verifier");
+        }
+    }
+
+    tracing::info!("Successfully verified blocks validity and extracted fees");
+
+    Ok(proofs)
+}
+
+pub async fn extract_fee_stats(
+    rpc_url: &String,
+    l2_store_address: &String,
+    chain_id: u64,
+    batch_size: u64,
+    start_block: u64,
+    end_block: u64,
+    percentiles: &[u8],
+    skip_proof_verification: Option<bool>,
+    sampling: SamplingMode,
+    header_source: HeaderSource,
+) -> Result<Stark, PublisherError> {
+    let skip_proof = skip_proof_verification.unwrap_or(false);
+
+    let validator = ValidatorBuilder::new(
+        rpc_url,
+        l2_store_address,
+        chain_id,
+        batch_size,
+        skip_proof,
+        header_source,
+    )
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, "Failed to create ValidatorBuilder");
+        e
+    })?
+    .with_sampling(sampling);
+
+    let result = validator
+        .validate_blocks_and_compute_fee_stats(start_block, end_block, percentiles)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to verify blocks validity and compute fee stats");
+            e
+        })?;
+
+    tracing::info!("Successfully verified blocks validity and computed fee stats");
+
+    Ok(result)
+}
+
+/// Returns the MMR inclusion proof of a block, identified by number or hash.
+pub async fn get_block_inclusion_proof(
+    rpc_url: &String,
+    l2_store_address: &String,
+    chain_id: u64,
+    batch_size: u64,
+    block: &BlockId,
+    header_source: HeaderSource,
+) -> Result<BlockInclusionProof, PublisherError> {
+    let validator = ValidatorBuilder::new(
+        rpc_url,
+        l2_store_address,
+        chain_id,
+        batch_size,
+        true,
+        header_source,
+    )
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, "Failed to create ValidatorBuilder");
+        e
+    })?;
+
+    let proof = validator
+        .get_block_inclusion_proof(block)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, block = %block, "Failed to get block inclusion proof");
+            e
+        })?;
+
+    Ok(proof)
+}
+
+/// Lists the batches of the Fossil Store and of the local batch databases,
+/// flagging batches where the two diverge.
+pub async fn list_batches(
+    rpc_url: &String,
+    l2_store_address: &String,
+    batch_size: u64,
+) -> Result<BatchListing, PublisherError> {
+    let provider = StarknetProvider::new(rpc_url)?;
+    let listing = validator::list_batches(&provider, l2_store_address, batch_size)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to list batches");
+            e
+        })?;
+
+    Ok(listing)
+}
+
+/// Returns the Fossil Store and local state of a single batch.
+pub async fn get_batch(
+    rpc_url: &String,
+    l2_store_address: &String,
+    batch_size: u64,
+    batch_index: u64,
+) -> Result<BatchInfo, PublisherError> {
+    let provider = StarknetProvider::new(rpc_url)?;
+    let batch = validator::get_batch(&provider, l2_store_address, batch_size, batch_index)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, batch_index, "Failed to get batch");
+            e
+        })?;
+
+    Ok(batch)
+}
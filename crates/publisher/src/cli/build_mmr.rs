@@ -1,7 +1,9 @@
 use crate::core::AccumulatorBuilder;
+use crate::db::DbConnection;
 use clap::Parser;
-use common::{get_env_var, initialize_logger_and_env};
-use starknet_handler::{account::StarknetAccount, provider::StarknetProvider};
+use common::{get_env_var, get_ethereum_chain_id, initialize_logger_and_env};
+use starknet_handler::account::{starknet_chain_id_from_env, StarknetAccount};
+use starknet_handler::provider::StarknetProvider;
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
@@ -29,6 +31,14 @@ pub struct Args {
     /// Start building from the latest MMR block
     #[arg(short = 'l', long, default_value_t = false)]
     pub from_latest: bool,
+
+    /// Chain the proofs of this many consecutive batches into one on-chain verification
+    #[arg(short = 'c', long)]
+    pub chain_length: Option<usize>,
+
+    /// Verify the batch proofs bundled in multicall transactions instead of one by one
+    #[arg(short = 'm', long, default_value_t = false)]
+    pub multicall: bool,
 }
 
 pub async fn run(args: Args) -> Result<(), Box<dyn std::error::Error>> {
@@ -36,7 +46,7 @@ pub async fn run(args: Args) -> Result<(), Box<dyn std::error::Error>> {
     dotenv::from_path(&args.env_file)?;
     initialize_logger_and_env()?;
 
-    let chain_id = get_env_var("CHAIN_ID")?.parse::<u64>()?;
+    let chain_id = get_ethereum_chain_id()?;
     let rpc_url = get_env_var("STARKNET_RPC_URL")?;
     let verifier_address = get_env_var("FOSSIL_VERIFIER")?;
     let store_address = get_env_var("FOSSIL_STORE")?;
@@ -44,8 +54,14 @@ pub async fn run(args: Args) -> Result<(), Box<dyn std::error::Error>> {
     let account_address = get_env_var("STARKNET_ACCOUNT_ADDRESS")?;
 
     let starknet_provider = StarknetProvider::new(&rpc_url)?;
-    let starknet_account =
-        StarknetAccount::new(starknet_provider.provider(), &private_key, &account_address)?;
+    let starknet_account = StarknetAccount::connect(
+        starknet_provider.provider(),
+        &private_key,
+        &account_address,
+        starknet_chain_id_from_env()?,
+    )
+    .await?;
+    let db_connection = DbConnection::new().await?;
 
     let mut builder = AccumulatorBuilder::new(
         &rpc_url,
@@ -55,12 +71,19 @@ pub async fn run(args: Args) -> Result<(), Box<dyn std::error::Error>> {
         starknet_account,
         args.batch_size,
         args.skip_proof,
+        db_connection.into(),
     )
     .await
     .map_err(|e| {
         tracing::error!(error = %e, "Failed to create AccumulatorBuilder");
         e
     })?;
+    if let Some(chain_length) = args.chain_length {
+        builder = builder.with_proof_chaining(chain_length)?;
+    }
+    if args.multicall {
+        builder = builder.with_multicall_batching();
+    }
 
     // Build MMR from specified start block or finalized block
     let result: Result<(), Box<dyn std::error::Error>> =
@@ -106,6 +129,14 @@ mod tests {
         assert_eq!(args.from_latest, false);
         assert!(args.num_batches.is_none());
         assert!(args.start_block.is_none());
+        assert!(args.chain_length.is_none());
+        assert!(!args.multicall);
+    }
+
+    #[test]
+    fn test_chain_length() {
+        let args = Args::parse_from(&["test", "--chain-length", "8"]);
+        assert_eq!(args.chain_length, Some(8));
     }
 
     #[test]
@@ -132,6 +163,8 @@ mod tests {
             env_file: ".env".to_string(),
             start_block: Some(100),
             from_latest: true,
+            chain_length: None,
+            multicall: false,
         };
 
         // Check the validation directly
@@ -0,0 +1,99 @@
+use crate::db::{DbConnection, HeaderFixture};
+use clap::Parser;
+use common::get_ethereum_chain_id;
+use std::path::PathBuf;
+use tracing::info;
+
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+pub struct Args {
+    /// Start block
+    #[arg(long)]
+    pub start_block: u64,
+
+    /// End block
+    #[arg(long)]
+    pub end_block: u64,
+
+    /// Output JSON file for the fixture pack
+    #[arg(short, long)]
+    pub output: PathBuf,
+}
+
+#[derive(Debug)]
+pub struct Config {
+    pub chain_id: u64,
+}
+
+impl Config {
+    pub fn from_env() -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self {
+            chain_id: get_ethereum_chain_id()?,
+        })
+    }
+}
+
+pub async fn run(config: Config, args: Args) -> Result<(), Box<dyn std::error::Error>> {
+    info!(
+        start_block = args.start_block,
+        end_block = args.end_block,
+        "Exporting block headers to fixture pack..."
+    );
+
+    let db_connection = DbConnection::new().await?;
+    let headers = db_connection
+        .get_block_headers_by_block_range(args.start_block, args.end_block)
+        .await?;
+
+    let expected = args.end_block - args.start_block + 1;
+    if headers.len() as u64 != expected {
+        return Err(format!(
+            "Expected {} headers in range {}..={}, found {}",
+            expected,
+            args.start_block,
+            args.end_block,
+            headers.len()
+        )
+        .into());
+    }
+
+    HeaderFixture::new(config.chain_id, headers).write_to_file(&args.output)?;
+
+    info!("Fixture pack written to {}", args.output.display());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_args() {
+        let args = Args::parse_from([
+            "export_fixtures",
+            "--start-block",
+            "100",
+            "--end-block",
+            "200",
+            "--output",
+            "headers.json",
+        ]);
+
+        assert_eq!(args.start_block, 100);
+        assert_eq!(args.end_block, 200);
+        assert_eq!(args.output, PathBuf::from("headers.json"));
+    }
+
+    #[test]
+    fn test_args_missing_output() {
+        let result = Args::try_parse_from([
+            "export_fixtures",
+            "--start-block",
+            "100",
+            "--end-block",
+            "200",
+        ]);
+        assert!(result.is_err());
+    }
+}
@@ -1,6 +1,10 @@
-use crate::api::operations::extract_fees;
+use crate::api::operations::{extract_fees, extract_fees_groth16};
+use crate::db::{DbConnection, SamplingMode};
 use clap::Parser;
-use common::get_env_var;
+use common::{get_env_var, get_ethereum_chain_id};
+use starknet::core::types::Felt;
+use starknet_handler::account::{starknet_chain_id_from_env, StarknetAccount};
+use starknet_handler::provider::StarknetProvider;
 use tracing::info;
 
 #[derive(Parser, Debug)]
@@ -13,11 +17,27 @@ pub struct Args {
     /// End block
     #[arg(long)]
     pub end_block: u64,
+
+    /// Which blocks to prove: every_block, every_nth:<step>, time_bucket:<seconds>
+    /// or blocks:<n1>,<n2>,...
+    #[arg(long, default_value_t = SamplingMode::default())]
+    pub sampling: SamplingMode,
+
+    /// Wrap the fee proofs in Groth16 so they can be verified on Starknet
+    #[arg(long)]
+    pub groth16: bool,
+
+    /// Submit the Groth16 fee proofs to the verifier at FOSSIL_VERIFIER
+    #[arg(long, requires = "groth16")]
+    pub submit: bool,
 }
 
 #[derive(Debug)]
 pub struct Config {
+    /// Ethereum chain id, checked by header validation
     pub chain_id: u64,
+    /// Starknet chain id transactions are signed for; the provider's when unset
+    pub starknet_chain_id: Option<Felt>,
     pub rpc_url: String,
     pub store_address: String,
     pub private_key: String,
@@ -27,7 +47,8 @@ pub struct Config {
 impl Config {
     pub fn from_env() -> Result<Self, Box<dyn std::error::Error>> {
         Ok(Self {
-            chain_id: get_env_var("CHAIN_ID")?.parse()?,
+            chain_id: get_ethereum_chain_id()?,
+            starknet_chain_id: starknet_chain_id_from_env()?,
             rpc_url: get_env_var("STARKNET_RPC_URL")?,
             store_address: get_env_var("FOSSIL_STORE")?,
             private_key: get_env_var("STARKNET_PRIVATE_KEY")?,
@@ -39,6 +60,42 @@ impl Config {
 pub async fn run(config: Config, args: Args) -> Result<(), Box<dyn std::error::Error>> {
     info!("Starting fee extraction...");
 
+    let db_connection = DbConnection::new().await?;
+
+    if args.groth16 {
+        let verifier_address = if args.submit {
+            Some(get_env_var("FOSSIL_VERIFIER")?)
+        } else {
+            None
+        };
+        let provider = StarknetProvider::new(&config.rpc_url)?;
+        let account = StarknetAccount::connect(
+            provider.provider(),
+            &config.private_key,
+            &config.account_address,
+            config.starknet_chain_id,
+        )
+        .await?;
+
+        extract_fees_groth16(
+            &config.rpc_url,
+            &config.store_address,
+            config.chain_id,
+            1024, // batch_size
+            args.start_block,
+            args.end_block,
+            args.sampling,
+            db_connection.into(),
+            verifier_address
+                .as_deref()
+                .map(|verifier_address| (&account, verifier_address)),
+        )
+        .await?;
+
+        info!("Fee extraction completed");
+        return Ok(());
+    }
+
     extract_fees(
         &config.rpc_url,
         &config.store_address,
@@ -47,6 +104,8 @@ pub async fn run(config: Config, args: Args) -> Result<(), Box<dyn std::error::E
         args.start_block,
         args.end_block,
         None, // skip_proof_verification
+        args.sampling,
+        db_connection.into(),
     )
     .await?;
 
@@ -83,6 +142,46 @@ mod tests {
 
         assert_eq!(args.start_block, 100);
         assert_eq!(args.end_block, 200);
+        assert_eq!(args.sampling, SamplingMode::default());
+    }
+
+    #[test]
+    fn test_args_sampling() {
+        let args = Args::parse_from([
+            "extract_fees",
+            "--start-block",
+            "100",
+            "--end-block",
+            "200",
+            "--sampling",
+            "every_nth:10",
+        ]);
+
+        assert_eq!(args.sampling, SamplingMode::EveryNth { step: 10 });
+    }
+
+    #[test]
+    fn test_args_submit_requires_groth16() {
+        let result = Args::try_parse_from([
+            "extract_fees",
+            "--start-block",
+            "100",
+            "--end-block",
+            "200",
+            "--submit",
+        ]);
+        assert!(result.is_err());
+
+        let args = Args::parse_from([
+            "extract_fees",
+            "--start-block",
+            "100",
+            "--end-block",
+            "200",
+            "--groth16",
+            "--submit",
+        ]);
+        assert!(args.groth16 && args.submit);
     }
 
     #[test]
@@ -1,3 +1,4 @@
 pub mod build_mmr;
+pub mod export_fixtures;
 pub mod extract_fees;
 pub mod update_mmr;
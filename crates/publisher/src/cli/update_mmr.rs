@@ -1,6 +1,9 @@
 use crate::api::operations::prove_mmr_update;
+use crate::db::DbConnection;
 use clap::Parser;
-use common::get_env_var;
+use common::{get_env_var, get_ethereum_chain_id};
+use starknet::core::types::Felt;
+use starknet_handler::account::starknet_chain_id_from_env;
 use tracing::info;
 
 #[derive(Parser, Debug)]
@@ -24,7 +27,10 @@ pub struct Args {
 }
 
 pub struct Config {
+    /// Ethereum chain id, checked by header validation
     pub chain_id: u64,
+    /// Starknet chain id transactions are signed for; the provider's when unset
+    pub starknet_chain_id: Option<Felt>,
     pub rpc_url: String,
     pub verifier_address: String,
     pub store_address: String,
@@ -35,7 +41,8 @@ pub struct Config {
 impl Config {
     pub fn from_env() -> Result<Self, Box<dyn std::error::Error>> {
         Ok(Self {
-            chain_id: get_env_var("CHAIN_ID")?.parse()?,
+            chain_id: get_ethereum_chain_id()?,
+            starknet_chain_id: starknet_chain_id_from_env()?,
             rpc_url: get_env_var("STARKNET_RPC_URL")?,
             verifier_address: get_env_var("FOSSIL_VERIFIER")?,
             store_address: get_env_var("FOSSIL_STORE")?,
@@ -48,6 +55,8 @@ impl Config {
 pub async fn run(config: Config, args: Args) -> Result<(), Box<dyn std::error::Error>> {
     info!("Starting Publisher...");
 
+    let db_connection = DbConnection::new().await?;
+
     prove_mmr_update(
         &config.rpc_url,
         config.chain_id,
@@ -55,10 +64,12 @@ pub async fn run(config: Config, args: Args) -> Result<(), Box<dyn std::error::E
         &config.store_address,
         &config.private_key,
         &config.account_address,
+        config.starknet_chain_id,
         args.batch_size,
         args.start,
         args.end,
         args.skip_proof,
+        db_connection.into(),
     )
     .await?;
 
@@ -88,6 +99,7 @@ mod tests {
         fn from_env_test() -> Result<Self, Box<dyn std::error::Error>> {
             Ok(Self {
                 chain_id: env::var("CHAIN_ID")?.parse()?,
+                starknet_chain_id: None,
                 rpc_url: env::var("STARKNET_RPC_URL")?,
                 verifier_address: env::var("FOSSIL_VERIFIER")?,
                 store_address: env::var("FOSSIL_STORE")?,
@@ -1,11 +1,16 @@
 use crate::core::{BatchProcessor, ProofGenerator};
+use crate::db::HeaderSource;
 use crate::errors::AccumulatorError;
 use crate::utils::BatchResult;
 use ethereum::get_finalized_block_hash;
-use methods::{MMR_APPEND_ELF, MMR_APPEND_ID};
+use guest_types::{GuestOutput, MmrChainInput};
+use methods::{
+    CHAIN_MMR_APPEND_PROOFS_ELF, CHAIN_MMR_APPEND_PROOFS_ID, MMR_APPEND_ELF, MMR_APPEND_ID,
+};
 use starknet_crypto::Felt;
 use starknet_handler::account::StarknetAccount;
 use starknet_handler::provider::StarknetProvider;
+use starknet_handler::StarknetHandlerError;
 use tracing::{debug, error, info, warn};
 
 use super::MMRStateManager;
@@ -17,6 +22,10 @@ pub struct AccumulatorBuilder<'a> {
     batch_processor: BatchProcessor<'a>,
     current_batch: u64,
     total_batches: u64,
+    chain_length: Option<usize>,
+    pending_chain: Vec<BatchResult>,
+    multicall: bool,
+    pending_proofs: Vec<(Vec<Felt>, String)>,
 }
 
 impl<'a> AccumulatorBuilder<'a> {
@@ -28,6 +37,7 @@ impl<'a> AccumulatorBuilder<'a> {
         starknet_account: StarknetAccount,
         batch_size: u64,
         skip_proof_verification: bool,
+        header_source: HeaderSource,
     ) -> Result<Self, AccumulatorError> {
         let proof_generator = ProofGenerator::new(MMR_APPEND_ELF, MMR_APPEND_ID)?;
         let mmr_state_manager = MMRStateManager::new(starknet_account, store_address);
@@ -52,17 +62,52 @@ impl<'a> AccumulatorBuilder<'a> {
                 proof_generator,
                 skip_proof_verification,
                 mmr_state_manager,
+                header_source,
             )?,
             current_batch: 0,
             total_batches: 0,
+            chain_length: None,
+            pending_chain: Vec::new(),
+            multicall: false,
+            pending_proofs: Vec::new(),
         })
     }
 
+    /// Chains the append proofs of up to `chain_length` consecutive batches
+    /// into a single proof, verified on-chain in one transaction.
+    pub fn with_proof_chaining(mut self, chain_length: usize) -> Result<Self, AccumulatorError> {
+        if chain_length == 0 {
+            return Err(AccumulatorError::InvalidInput(
+                "Chain length must be greater than 0",
+            ));
+        }
+
+        self.batch_processor.enable_proof_chaining();
+        self.chain_length = Some(chain_length);
+        Ok(self)
+    }
+
+    /// Holds batch proofs back until they fill a multicall transaction within
+    /// the account's limits, so that they are verified on-chain in as few
+    /// transactions as possible. Held proofs are verified when the build
+    /// ends, including when it fails.
+    pub fn with_multicall_batching(mut self) -> Self {
+        self.multicall = true;
+        self
+    }
+
     /// Build the MMR using a specified number of batches
     pub async fn build_with_num_batches(
         &mut self,
         num_batches: u64,
     ) -> Result<(), AccumulatorError> {
+        let result = self.append_num_batches(num_batches).await;
+        self.finish_build(result).await?;
+        info!("MMR build completed successfully");
+        Ok(())
+    }
+
+    async fn append_num_batches(&mut self, num_batches: u64) -> Result<(), AccumulatorError> {
         if num_batches == 0 {
             return Err(AccumulatorError::InvalidInput(
                 "Number of batches must be greater than 0",
@@ -110,7 +155,7 @@ impl<'a> AccumulatorBuilder<'a> {
                 })?;
 
             if let Some(batch_result) = result {
-                self.handle_batch_result(&batch_result).await?;
+                self.submit_batch_result(&batch_result).await?;
                 self.current_batch += 1;
                 info!(
                     progress = format!("{}/{}", self.current_batch, self.total_batches),
@@ -121,11 +166,15 @@ impl<'a> AccumulatorBuilder<'a> {
             current_end = start_block.saturating_sub(1);
         }
 
-        info!("MMR build completed successfully");
         Ok(())
     }
 
     pub async fn build_from_finalized(&mut self) -> Result<(), AccumulatorError> {
+        let result = self.append_from_finalized().await;
+        self.finish_build(result).await
+    }
+
+    async fn append_from_finalized(&mut self) -> Result<(), AccumulatorError> {
         let (finalized_block_number, _) = get_finalized_block_hash().await?;
         debug!(
             "Building MMR from finalized block {} with batch size {}",
@@ -143,7 +192,7 @@ impl<'a> AccumulatorBuilder<'a> {
                 .await?;
 
             if let Some(result) = batch_result {
-                self.handle_batch_result(&result).await?;
+                self.submit_batch_result(&result).await?;
             }
 
             current_end = start_block.saturating_sub(1);
@@ -157,6 +206,24 @@ impl<'a> AccumulatorBuilder<'a> {
         start_block: u64,
         end_block: u64,
     ) -> Result<(), AccumulatorError> {
+        let result = self.append_new_headers(start_block, end_block).await;
+        let batches = self.finish_build(result).await?;
+
+        if batches == 0 {
+            error!(start_block, end_block, "No batch results generated");
+            Err(AccumulatorError::InvalidStateTransition)
+        } else {
+            debug!(total_batches = batches, "MMR update completed successfully");
+            Ok(())
+        }
+    }
+
+    /// Appends the headers of the range, returning the number of batches updated.
+    async fn append_new_headers(
+        &mut self,
+        start_block: u64,
+        end_block: u64,
+    ) -> Result<usize, AccumulatorError> {
         if end_block < start_block {
             return Err(AccumulatorError::InvalidInput(
                 "End block cannot be less than start block",
@@ -196,7 +263,7 @@ impl<'a> AccumulatorBuilder<'a> {
                     e
                 })?
             {
-                self.handle_batch_result(&result).await?;
+                self.submit_batch_result(&result).await?;
                 let ipfs_hash = result.ipfs_hash();
                 let calldata = result
                     .proof()
@@ -216,27 +283,199 @@ impl<'a> AccumulatorBuilder<'a> {
             current_end = batch_range.start.saturating_sub(1);
         }
 
-        if batch_results.is_empty() {
-            error!(start_block, end_block, "No batch results generated");
-            Err(AccumulatorError::InvalidStateTransition)
-        } else {
-            debug!(
-                total_batches = batch_results.len(),
-                "MMR update completed successfully"
-            );
-            Ok(())
+        Ok(batch_results.len())
+    }
+
+    async fn submit_batch_result(
+        &mut self,
+        batch_result: &BatchResult,
+    ) -> Result<(), AccumulatorError> {
+        let chain_length = match (self.chain_length, batch_result.stark()) {
+            (Some(chain_length), Some(_)) => chain_length,
+            _ => return self.handle_batch_result(batch_result).await,
+        };
+
+        // Batches are processed from the newest down; a chain must cover
+        // contiguous blocks, so a gap closes the current one
+        if let Some(last) = self.pending_chain.last() {
+            if batch_result.end_block() + 1 != last.start_block() {
+                self.flush_proof_chain().await?;
+            }
+        }
+
+        self.pending_chain.push(batch_result.clone());
+        if self.pending_chain.len() >= chain_length {
+            self.flush_proof_chain().await?;
+        }
+        Ok(())
+    }
+
+    /// Verifies what is still pending once a build ends, also when it fails:
+    /// the batches already appended and uploaded must not be left unverified.
+    async fn finish_build<T>(
+        &mut self,
+        result: Result<T, AccumulatorError>,
+    ) -> Result<T, AccumulatorError> {
+        match result {
+            Ok(value) => {
+                self.flush_pending_verifications().await?;
+                Ok(value)
+            }
+            Err(e) => {
+                if let Err(flush_error) = self.flush_pending_verifications().await {
+                    error!(
+                        error = %flush_error,
+                        "Failed to verify pending proofs of a failed build"
+                    );
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Verifies everything still pending on-chain: the current proof chain
+    /// and the proofs held for a multicall.
+    async fn flush_pending_verifications(&mut self) -> Result<(), AccumulatorError> {
+        self.flush_proof_chain().await?;
+        self.flush_pending_proofs().await
+    }
+
+    /// Verifies the held batch proofs on-chain, in the order they were proven.
+    /// Proofs that were not verified stay held when a multicall fails.
+    async fn flush_pending_proofs(&mut self) -> Result<(), AccumulatorError> {
+        if self.pending_proofs.is_empty() {
+            return Ok(());
         }
+
+        let proofs = self.pending_proofs.clone();
+        let proof_count = proofs.len();
+        let starknet_account = self.batch_processor.mmr_state_manager().account();
+
+        info!(proofs = proof_count, "Verifying MMR proofs in multicalls");
+        let result = starknet_account
+            .verify_mmr_proofs(self.verifier_address, proofs)
+            .await;
+        let tx_hashes = match result {
+            Ok(tx_hashes) => {
+                self.pending_proofs.clear();
+                tx_hashes
+            }
+            Err(e) => {
+                if let StarknetHandlerError::ProofsPartiallyVerified {
+                    verified,
+                    tx_hashes,
+                    ..
+                } = &e
+                {
+                    warn!(
+                        verified,
+                        transactions = tx_hashes.len(),
+                        "Keeping the MMR proofs left unverified"
+                    );
+                    self.pending_proofs.drain(..*verified);
+                }
+                error!(error = %e, "Failed to verify MMR proofs");
+                return Err(e.into());
+            }
+        };
+
+        info!(
+            proofs = proof_count,
+            transactions = tx_hashes.len(),
+            "MMR proofs verified successfully"
+        );
+        Ok(())
+    }
+
+    /// Proves the pending batches as one chain and verifies it on-chain.
+    async fn flush_proof_chain(&mut self) -> Result<(), AccumulatorError> {
+        if self.pending_chain.is_empty() {
+            return Ok(());
+        }
+
+        let mut results = std::mem::take(&mut self.pending_chain);
+        results.sort_by_key(|result| result.start_block());
+
+        let mut outputs = Vec::with_capacity(results.len());
+        let mut receipts = Vec::with_capacity(results.len());
+        let mut ipfs_hashes: Vec<(u64, String)> = Vec::new();
+        for result in &results {
+            let receipt = result
+                .stark()
+                .ok_or(AccumulatorError::InvalidInput(
+                    "Chained batch is missing its STARK receipt",
+                ))?
+                .receipt();
+            let output: GuestOutput = self
+                .batch_processor
+                .proof_generator()
+                .decode_journal(&receipt)?;
+
+            // The chain journal keeps the last state of each batch, uploaded last
+            match ipfs_hashes.last_mut() {
+                Some((batch_index, ipfs_hash)) if *batch_index == output.batch_index() => {
+                    *ipfs_hash = result.ipfs_hash();
+                }
+                _ => ipfs_hashes.push((output.batch_index(), result.ipfs_hash())),
+            }
+            outputs.push(output);
+            receipts.push(receipt);
+        }
+
+        info!(
+            start_block = results[0].start_block(),
+            end_block = results[results.len() - 1].end_block(),
+            batches = ipfs_hashes.len(),
+            "Chaining MMR append proofs"
+        );
+
+        let chain_proof_generator =
+            ProofGenerator::new(CHAIN_MMR_APPEND_PROOFS_ELF, CHAIN_MMR_APPEND_PROOFS_ID)?;
+        let proof = chain_proof_generator
+            .generate_groth16_proof_with_assumptions(
+                MmrChainInput::new(MMR_APPEND_ID, outputs),
+                receipts,
+            )
+            .await
+            .map_err(|e| {
+                error!(error = %e, "Failed to generate MMR chain proof");
+                e
+            })?;
+
+        let starknet_account = self.batch_processor.mmr_state_manager().account();
+        starknet_account
+            .verify_mmr_chain_proof(
+                self.verifier_address,
+                proof.calldata(),
+                ipfs_hashes
+                    .into_iter()
+                    .map(|(_, ipfs_hash)| ipfs_hash)
+                    .collect(),
+            )
+            .await
+            .map_err(|e| {
+                error!(error = %e, "Failed to verify MMR chain proof");
+                e
+            })?;
+
+        info!("MMR chain proof verified successfully");
+        Ok(())
     }
 
     async fn handle_batch_result(
-        &self,
+        &mut self,
         batch_result: &BatchResult,
     ) -> Result<(), AccumulatorError> {
         // Skip verification if explicitly disabled or if no proof is available
         if !self.batch_processor.skip_proof_verification() {
             if let Some(proof) = batch_result.proof() {
-                self.verify_proof(proof.calldata(), batch_result.ipfs_hash())
-                    .await?;
+                if self.multicall {
+                    self.hold_proof(proof.calldata(), batch_result.ipfs_hash())
+                        .await?;
+                } else {
+                    self.verify_proof(proof.calldata(), batch_result.ipfs_hash())
+                        .await?;
+                }
             } else {
                 debug!("Skipping proof verification - no proof available");
             }
@@ -246,6 +485,26 @@ impl<'a> AccumulatorBuilder<'a> {
         Ok(())
     }
 
+    /// Holds a proof for a multicall, first verifying the held proofs once
+    /// this one would not fit in the same multicall.
+    async fn hold_proof(
+        &mut self,
+        calldata: Vec<Felt>,
+        ipfs_hash: String,
+    ) -> Result<(), AccumulatorError> {
+        self.pending_proofs.push((calldata, ipfs_hash));
+
+        let starknet_account = self.batch_processor.mmr_state_manager().account();
+        if !starknet_account.fits_in_multicall(self.verifier_address, &self.pending_proofs)? {
+            // The newest proof starts the next multicall, also when the flush fails
+            let newest = self.pending_proofs.pop();
+            let flushed = self.flush_pending_proofs().await;
+            self.pending_proofs.extend(newest);
+            flushed?;
+        }
+        Ok(())
+    }
+
     async fn verify_proof(
         &self,
         calldata: Vec<Felt>,
@@ -285,6 +544,11 @@ impl<'a> AccumulatorBuilder<'a> {
     }
 
     async fn process_blocks_from(&mut self, start_block: u64) -> Result<(), AccumulatorError> {
+        let result = self.append_blocks_from(start_block).await;
+        self.finish_build(result).await
+    }
+
+    async fn append_blocks_from(&mut self, start_block: u64) -> Result<(), AccumulatorError> {
         let (finalized_block_number, _) = get_finalized_block_hash().await?;
         if start_block > finalized_block_number {
             return Err(AccumulatorError::InvalidInput(
@@ -308,7 +572,7 @@ impl<'a> AccumulatorBuilder<'a> {
                 .await?;
 
             if let Some(result) = batch_result {
-                self.handle_batch_result(&result).await?;
+                self.submit_batch_result(&result).await?;
             }
 
             current_end = start.saturating_sub(1);
@@ -321,6 +585,19 @@ impl<'a> AccumulatorBuilder<'a> {
         &mut self,
         start_block: u64,
         num_batches: u64,
+    ) -> Result<(), AccumulatorError> {
+        let result = self
+            .append_blocks_from_with_limit(start_block, num_batches)
+            .await;
+        self.finish_build(result).await?;
+        info!("MMR accumulation completed successfully");
+        Ok(())
+    }
+
+    async fn append_blocks_from_with_limit(
+        &mut self,
+        start_block: u64,
+        num_batches: u64,
     ) -> Result<(), AccumulatorError> {
         if num_batches == 0 {
             return Err(AccumulatorError::InvalidInput(
@@ -368,7 +645,7 @@ impl<'a> AccumulatorBuilder<'a> {
                 })?;
 
             if let Some(batch_result) = result {
-                self.handle_batch_result(&batch_result).await?;
+                self.submit_batch_result(&batch_result).await?;
                 self.current_batch += 1;
                 info!(
                     progress = format!("{}/{}", self.current_batch, self.total_batches),
@@ -379,7 +656,6 @@ impl<'a> AccumulatorBuilder<'a> {
             current_end = start.saturating_sub(1);
         }
 
-        info!("MMR accumulation completed successfully");
         Ok(())
     }
 
@@ -441,6 +717,7 @@ mod tests {
     use super::*;
     use mockall::mock;
     use mockall::predicate::*;
+    use starknet::core::chain_id;
     use starknet::core::types::U256;
     use starknet::providers::jsonrpc::HttpTransport;
     use starknet::providers::JsonRpcClient;
@@ -463,7 +740,7 @@ mod tests {
             let transport = HttpTransport::new(Url::parse("http://localhost:8545").unwrap());
             let provider = Arc::new(JsonRpcClient::new(transport));
 
-            StarknetAccount::new(provider, "0x123", "0x456").unwrap()
+            StarknetAccount::new(provider, "0x123", "0x456", chain_id::SEPOLIA).unwrap()
         }
     }
 
@@ -483,6 +760,7 @@ mod tests {
             account.into(),
             100,
             false,
+            HeaderSource::empty_for_tests(),
         )
         .await;
 
@@ -509,6 +787,7 @@ mod tests {
             MockStarknetAccount::new().into(), // Create new instance instead of cloning
             100,
             false,
+            HeaderSource::empty_for_tests(),
         )
         .await;
         assert!(matches!(result, Err(AccumulatorError::InvalidInput(_))));
@@ -523,6 +802,7 @@ mod tests {
             account.into(),
             0,
             false,
+            HeaderSource::empty_for_tests(),
         )
         .await;
         assert!(matches!(result, Err(AccumulatorError::InvalidInput(_))));
@@ -543,6 +823,7 @@ mod tests {
             account.into(),
             100,
             false,
+            HeaderSource::empty_for_tests(),
         )
         .await
         .unwrap();
@@ -566,6 +847,7 @@ mod tests {
             account.into(),
             100,
             false,
+            HeaderSource::empty_for_tests(),
         )
         .await
         .unwrap();
@@ -581,7 +863,7 @@ mod tests {
         let verifier_addr = "0x123".to_string();
         let store_addr = "0x456".to_string();
 
-        let builder = AccumulatorBuilder::new(
+        let mut builder = AccumulatorBuilder::new(
             &rpc_url,
             1,
             &verifier_addr,
@@ -589,6 +871,7 @@ mod tests {
             account.into(),
             100,
             true,
+            HeaderSource::empty_for_tests(),
         )
         .await
         .unwrap();
@@ -613,4 +896,39 @@ mod tests {
         let result = builder.handle_batch_result(&batch_result).await;
         assert!(result.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_multicall_batching_without_proofs() {
+        let account = MockStarknetAccount::new();
+        let rpc_url = "http://localhost:8545".to_string();
+        let verifier_addr = "0x123".to_string();
+        let store_addr = "0x456".to_string();
+
+        let mut builder = AccumulatorBuilder::new(
+            &rpc_url,
+            1,
+            &verifier_addr,
+            &store_addr,
+            account.into(),
+            100,
+            false,
+            HeaderSource::empty_for_tests(),
+        )
+        .await
+        .unwrap()
+        .with_multicall_batching();
+
+        let batch_result = BatchResult::new(
+            100,
+            200,
+            MmrState::new(100, U256::from(0_u64), U256::from(0_u64), 0, None),
+            None,
+            "test_hash".to_string(),
+        );
+
+        // Nothing is held without a proof, and flushing submits nothing
+        builder.handle_batch_result(&batch_result).await.unwrap();
+        assert!(builder.pending_proofs.is_empty());
+        assert!(builder.flush_pending_verifications().await.is_ok());
+    }
 }
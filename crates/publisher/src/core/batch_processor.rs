@@ -1,5 +1,5 @@
 use crate::core::{MMRStateManager, ProofGenerator};
-use crate::db::DbConnection;
+use crate::db::HeaderSource;
 use crate::errors::AccumulatorError;
 use crate::utils::BatchResult;
 use common::get_or_create_db_path;
@@ -15,7 +15,9 @@ pub struct BatchProcessor<'a> {
     proof_generator: ProofGenerator<CombinedInput>,
     mmr_state_manager: MMRStateManager<'a>,
     skip_proof_verification: bool,
-    ipfs_manager: IpfsManager,
+    ipfs_manager: Option<IpfsManager>,
+    header_source: HeaderSource,
+    chain_proofs: bool,
 }
 
 impl<'a> BatchProcessor<'a> {
@@ -24,6 +26,7 @@ impl<'a> BatchProcessor<'a> {
         proof_generator: ProofGenerator<CombinedInput>,
         skip_proof_verification: bool,
         mmr_state_manager: MMRStateManager<'a>,
+        header_source: HeaderSource,
     ) -> Result<Self, AccumulatorError> {
         if batch_size == 0 {
             return Err(AccumulatorError::InvalidInput(
@@ -31,7 +34,7 @@ impl<'a> BatchProcessor<'a> {
             ));
         }
 
-        let ipfs_manager = IpfsManager::new();
+        let ipfs_manager = Some(IpfsManager::new());
 
         Ok(Self {
             batch_size,
@@ -39,6 +42,8 @@ impl<'a> BatchProcessor<'a> {
             skip_proof_verification,
             mmr_state_manager,
             ipfs_manager,
+            header_source,
+            chain_proofs: false,
         })
     }
 
@@ -50,6 +55,10 @@ impl<'a> BatchProcessor<'a> {
         &self.proof_generator
     }
 
+    pub fn header_source(&self) -> &HeaderSource {
+        &self.header_source
+    }
+
     pub fn batch_size(&self) -> u64 {
         self.batch_size
     }
@@ -58,6 +67,22 @@ impl<'a> BatchProcessor<'a> {
         self.skip_proof_verification
     }
 
+    /// Disables uploading batch files to IPFS, e.g. for offline runs. Batch
+    /// results then carry an empty IPFS hash.
+    pub fn disable_ipfs_upload(&mut self) {
+        self.ipfs_manager = None;
+    }
+
+    /// Proves batches with STARK receipts, to be chained into a single proof,
+    /// instead of one Groth16 proof per batch.
+    pub fn enable_proof_chaining(&mut self) {
+        self.chain_proofs = true;
+    }
+
+    pub fn chain_proofs(&self) -> bool {
+        self.chain_proofs
+    }
+
     pub async fn process_batch(
         &self,
         chain_id: u64,
@@ -113,11 +138,8 @@ impl<'a> BatchProcessor<'a> {
             return Ok(None);
         }
 
-        let db_connection = DbConnection::new().await.map_err(|e| {
-            error!(error = %e, "Failed to create DB connection");
-            e
-        })?;
-        let headers = db_connection
+        let headers = self
+            .header_source
             .get_block_headers_by_block_range(start_block, adjusted_end_block)
             .await
             .map_err(|e| {
@@ -160,7 +182,7 @@ impl<'a> BatchProcessor<'a> {
 
         let batch_link: Option<String> = if batch_index > 0 {
             Some(
-                db_connection
+                self.header_source
                     .get_block_header_by_number(start_block - 1)
                     .await?
                     .ok_or_else(|| {
@@ -172,7 +194,8 @@ impl<'a> BatchProcessor<'a> {
             None
         };
 
-        let next_batch_link = db_connection
+        let next_batch_link = self
+            .header_source
             .get_block_header_by_number(adjusted_end_block + 1)
             .await?
             .map(|header| header.parent_hash)
@@ -188,9 +211,34 @@ impl<'a> BatchProcessor<'a> {
             self.skip_proof_verification,
         );
 
-        let (guest_output, proof) = if self.skip_proof_verification {
+        let (guest_output, proof, stark) = if self.skip_proof_verification {
             info!("Skipping proof generation and verification");
-            (None, None)
+            (None, None, None)
+        } else if self.chain_proofs {
+            let stark = self
+                .proof_generator
+                .generate_stark_proof(combined_input)
+                .await
+                .map_err(|e| {
+                    error!(error = %e, "Failed to generate proof");
+                    e
+                })?;
+
+            let guest_output: GuestOutput = self
+                .proof_generator
+                .decode_journal(&stark.receipt())
+                .map_err(|e| {
+                error!(error = %e, "Failed to decode guest output");
+                e
+            })?;
+
+            debug!(
+                "Guest output - root_hash: {}, leaves_count: {}",
+                guest_output.root_hash(),
+                guest_output.leaves_count()
+            );
+
+            (Some(guest_output), None, Some(stark))
         } else {
             let proof = self
                 .proof_generator
@@ -203,11 +251,13 @@ impl<'a> BatchProcessor<'a> {
 
             debug!("Generated proof with {} elements", proof.calldata().len());
 
-            let guest_output: GuestOutput =
-                self.proof_generator.decode_journal(&proof).map_err(|e| {
-                    error!(error = %e, "Failed to decode guest output");
-                    e
-                })?;
+            let guest_output: GuestOutput = self
+                .proof_generator
+                .decode_journal(&proof.receipt())
+                .map_err(|e| {
+                error!(error = %e, "Failed to decode guest output");
+                e
+            })?;
 
             debug!(
                 "Guest output - root_hash: {}, leaves_count: {}",
@@ -215,7 +265,7 @@ impl<'a> BatchProcessor<'a> {
                 guest_output.leaves_count()
             );
 
-            (Some(guest_output), Some(proof))
+            (Some(guest_output), Some(proof), None)
         };
 
         let new_mmr_state = self
@@ -242,22 +292,29 @@ impl<'a> BatchProcessor<'a> {
             })?);
 
         // Upload current state to IPFS
-        let ipfs_hash = self
-            .ipfs_manager
-            .upload_db(&permanent_path)
-            .await
-            .map_err(|e| {
+        let ipfs_hash = match &self.ipfs_manager {
+            Some(ipfs_manager) => ipfs_manager.upload_db(&permanent_path).await.map_err(|e| {
                 error!(error = %e, "Failed to upload batch file to IPFS");
                 AccumulatorError::StorageError(format!("Failed to upload to IPFS: {}", e))
-            })?;
+            })?,
+            None => {
+                debug!("IPFS upload disabled, keeping batch file local only");
+                String::new()
+            }
+        };
 
-        Ok(Some(BatchResult::new(
+        let batch_result = BatchResult::new(
             start_block,
             adjusted_end_block,
             new_mmr_state,
             proof,
             ipfs_hash.to_string(),
-        )))
+        );
+
+        Ok(Some(match stark {
+            Some(stark) => batch_result.with_stark(stark),
+            None => batch_result,
+        }))
     }
 
     pub fn calculate_batch_bounds(&self, batch_index: u64) -> Result<(u64, u64), AccumulatorError> {
@@ -348,7 +405,7 @@ mod tests {
     use mockall::automock;
     use serde::Serialize;
     use starknet::{
-        core::types::U256,
+        core::{chain_id, types::U256},
         providers::{jsonrpc::HttpTransport, JsonRpcClient, Url},
     };
     use starknet_handler::account::StarknetAccount;
@@ -402,7 +459,10 @@ mod tests {
                 Url::parse("http://localhost:5050").expect("Invalid URL"),
             )));
             let account = StarknetAccount::new(
-                provider, "0x0", "0x0", // private key as &str
+                provider,
+                "0x0",
+                "0x0", // private key as &str
+                chain_id::SEPOLIA,
             )
             .expect("Failed to create StarknetAccount");
 
@@ -417,7 +477,14 @@ mod tests {
         let proof_gen = ProofGenerator::mock();
         let mmr_state_mgr = MMRStateManager::mock();
 
-        BatchProcessor::new(100, proof_gen, false, mmr_state_mgr).unwrap()
+        BatchProcessor::new(
+            100,
+            proof_gen,
+            false,
+            mmr_state_mgr,
+            HeaderSource::empty_for_tests(),
+        )
+        .unwrap()
     }
 
     #[tokio::test]
@@ -477,11 +544,23 @@ mod tests {
         let mmr_state_mgr = MMRStateManager::mock();
 
         // Test valid creation
-        let result = BatchProcessor::new(100, proof_gen, false, mmr_state_mgr);
+        let result = BatchProcessor::new(
+            100,
+            proof_gen,
+            false,
+            mmr_state_mgr,
+            HeaderSource::empty_for_tests(),
+        );
         assert!(result.is_ok());
 
         // Test invalid batch size
-        let result = BatchProcessor::new(0, ProofGenerator::mock(), false, MMRStateManager::mock());
+        let result = BatchProcessor::new(
+            0,
+            ProofGenerator::mock(),
+            false,
+            MMRStateManager::mock(),
+            HeaderSource::empty_for_tests(),
+        );
         assert!(result.is_err());
     }
 
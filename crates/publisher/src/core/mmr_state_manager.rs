@@ -241,6 +241,7 @@ impl<'a> MMRStateManager<'a> {
 mod tests {
     use super::*;
     use mmr_utils::StoreManager;
+    use starknet::core::chain_id;
     use starknet::providers::{jsonrpc::HttpTransport, JsonRpcClient, Url};
     use starknet_handler::account::StarknetAccount;
     use std::sync::Arc;
@@ -254,6 +255,7 @@ mod tests {
             ))),
             "0x1234567890abcdef", // Valid hex address
             "0x1234567890abcdef", // Valid hex private key
+            chain_id::SEPOLIA,
         )
         .expect("Failed to create StarknetAccount");
 
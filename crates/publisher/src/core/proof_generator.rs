@@ -4,8 +4,11 @@ use garaga_rs::{
     },
     definitions::CurveID,
 };
+use guest_types::{BlocksValidityInput, FeeProofJournal, FEE_PROOF_JOURNAL_VERSION};
 use risc0_ethereum_contracts::encode_seal;
-use risc0_zkvm::{compute_image_id, default_prover, ExecutorEnv, ProverOpts, VerifierContext};
+use risc0_zkvm::{
+    compute_image_id, default_prover, ExecutorEnv, ProverOpts, Receipt, VerifierContext,
+};
 use serde::Deserialize;
 use tokio::task;
 use tracing::{debug, error, info};
@@ -51,6 +54,17 @@ where
 
     /// Generate a standard Stark proof for intermediate batches
     pub async fn generate_stark_proof(&self, input: T) -> Result<Stark, ProofGeneratorError> {
+        self.generate_stark_proof_with_assumptions(input, Vec::new())
+            .await
+    }
+
+    /// Generate a Stark proof for a guest that verifies other receipts with
+    /// `env::verify`, passing those receipts as assumptions.
+    pub async fn generate_stark_proof_with_assumptions(
+        &self,
+        input: T,
+        assumptions: Vec<Receipt>,
+    ) -> Result<Stark, ProofGeneratorError> {
         let input_size = std::mem::size_of_val(&input);
         if input_size == 0 {
             return Err(ProofGeneratorError::InvalidInput("Input cannot be empty"));
@@ -66,7 +80,11 @@ where
 
             move || -> Result<Stark, ProofGeneratorError> {
                 debug!("Building executor environment");
-                let env = ExecutorEnv::builder()
+                let mut builder = ExecutorEnv::builder();
+                for assumption in assumptions {
+                    builder.add_assumption(assumption);
+                }
+                let env = builder
                     .write(&input)
                     .map_err(|e| {
                         error!("Failed to write input to executor env: {}", e);
@@ -108,6 +126,17 @@ where
 
     /// Generate a Groth16 proof for the final batch
     pub async fn generate_groth16_proof(&self, input: T) -> Result<Groth16, ProofGeneratorError> {
+        self.generate_groth16_proof_with_assumptions(input, Vec::new())
+            .await
+    }
+
+    /// Generate a Groth16 proof for a guest that verifies other receipts with
+    /// `env::verify`, passing those receipts as assumptions.
+    pub async fn generate_groth16_proof_with_assumptions(
+        &self,
+        input: T,
+        assumptions: Vec<Receipt>,
+    ) -> Result<Groth16, ProofGeneratorError> {
         let input_size = std::mem::size_of_val(&input);
         if input_size == 0 {
             return Err(ProofGeneratorError::InvalidInput("Input cannot be empty"));
@@ -120,7 +149,11 @@ where
 
         let proof = task::spawn_blocking(move || -> Result<Groth16, ProofGeneratorError> {
             debug!("Building executor environment");
-            let env = ExecutorEnv::builder()
+            let mut builder = ExecutorEnv::builder();
+            for assumption in assumptions {
+                builder.add_assumption(assumption);
+            }
+            let env = builder
                 .write(&input)
                 .map_err(|e| {
                     error!("Failed to write input to executor env: {}", e);
@@ -189,19 +222,42 @@ where
 
     pub fn decode_journal<U: for<'a> Deserialize<'a>>(
         &self,
-        proof: &Groth16,
+        receipt: &Receipt,
     ) -> Result<U, ProofGeneratorError> {
-        if proof.receipt().journal.bytes.is_empty() {
+        if receipt.journal.bytes.is_empty() {
             return Err(ProofGeneratorError::InvalidInput(
                 "Proof journal cannot be empty",
             ));
         }
 
-        let receipt = proof.receipt();
         Ok(receipt.journal.decode()?)
     }
 }
 
+impl ProofGenerator<BlocksValidityInput> {
+    /// Decodes a fee extraction journal, rejecting layouts this host does not understand.
+    ///
+    /// A journal with `ok == false` is returned as is: it proves the blocks were invalid.
+    pub fn decode_fee_journal(
+        &self,
+        receipt: &Receipt,
+    ) -> Result<FeeProofJournal, ProofGeneratorError> {
+        let journal: FeeProofJournal = self.decode_journal(receipt)?;
+        check_fee_journal_version(&journal)?;
+        Ok(journal)
+    }
+}
+
+fn check_fee_journal_version(journal: &FeeProofJournal) -> Result<(), ProofGeneratorError> {
+    if journal.version != FEE_PROOF_JOURNAL_VERSION {
+        return Err(ProofGeneratorError::UnsupportedJournalVersion {
+            expected: FEE_PROOF_JOURNAL_VERSION,
+            actual: journal.version,
+        });
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -294,4 +350,16 @@ mod tests {
         // This test would verify that journal decoding works correctly
         // and handles errors appropriately
     }
+
+    #[test]
+    fn test_check_fee_journal_version() {
+        let mut journal = FeeProofJournal::invalid("0x0".to_string(), 1, 0, (1, 2));
+        assert!(check_fee_journal_version(&journal).is_ok());
+
+        journal.version = FEE_PROOF_JOURNAL_VERSION + 1;
+        assert!(matches!(
+            check_fee_journal_version(&journal),
+            Err(ProofGeneratorError::UnsupportedJournalVersion { .. })
+        ));
+    }
 }
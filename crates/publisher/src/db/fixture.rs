@@ -0,0 +1,421 @@
+use super::SamplingMode;
+use crate::errors::DbError;
+use eth_rlp_types::BlockHeader;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use tracing::{debug, info};
+
+/// On-disk format of a header fixture pack.
+///
+/// A pack is a JSON document holding the Ethereum chain id the headers belong to
+/// and the headers themselves, serialized with the same field names as the
+/// `blockheaders` table.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeaderFixture {
+    pub chain_id: u64,
+    pub headers: Vec<BlockHeader>,
+}
+
+impl HeaderFixture {
+    pub fn new(chain_id: u64, headers: Vec<BlockHeader>) -> Self {
+        Self { chain_id, headers }
+    }
+
+    /// Writes the fixture as pretty-printed JSON.
+    pub fn write_to_file(&self, path: &Path) -> Result<(), DbError> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| DbError::Fixture(format!("Failed to serialize fixture: {}", e)))?;
+        fs::write(path, json)
+            .map_err(|e| DbError::Fixture(format!("Failed to write {}: {}", path.display(), e)))?;
+        Ok(())
+    }
+
+    pub fn read_from_file(path: &Path) -> Result<Self, DbError> {
+        let json = fs::read_to_string(path)
+            .map_err(|e| DbError::Fixture(format!("Failed to read {}: {}", path.display(), e)))?;
+        serde_json::from_str(&json)
+            .map_err(|e| DbError::Fixture(format!("Failed to parse {}: {}", path.display(), e)))
+    }
+}
+
+/// In-memory header store backed by one or more fixture files.
+///
+/// Mirrors the queries of [`DbConnection`](super::DbConnection) so it can stand
+/// in for Postgres in tests and offline runs.
+#[derive(Debug)]
+pub struct FixtureHeaderStore {
+    chain_id: u64,
+    headers: BTreeMap<u64, BlockHeader>,
+}
+
+impl FixtureHeaderStore {
+    pub fn new(chain_id: u64, headers: Vec<BlockHeader>) -> Arc<Self> {
+        let headers = headers
+            .into_iter()
+            .map(|header| (header.number as u64, header))
+            .collect();
+
+        Arc::new(Self { chain_id, headers })
+    }
+
+    /// Loads a fixture pack from a single JSON file or from every `.json` file
+    /// in a directory. All files must share the same chain id and together
+    /// hold each block of a contiguous range exactly once.
+    pub fn load(path: &Path) -> Result<Arc<Self>, DbError> {
+        let files = if path.is_dir() {
+            let mut files = fs::read_dir(path)
+                .map_err(|e| DbError::Fixture(format!("Failed to read {}: {}", path.display(), e)))?
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+                .collect::<Vec<_>>();
+            files.sort();
+            files
+        } else {
+            vec![path.to_path_buf()]
+        };
+
+        if files.is_empty() {
+            return Err(DbError::Fixture(format!(
+                "No fixture files found in {}",
+                path.display()
+            )));
+        }
+
+        let mut chain_id = None;
+        let mut headers = Vec::new();
+
+        for file in files {
+            debug!("Loading header fixture {}", file.display());
+            let fixture = HeaderFixture::read_from_file(&file)?;
+
+            match chain_id {
+                Some(id) if id != fixture.chain_id => {
+                    return Err(DbError::Fixture(format!(
+                        "Chain id mismatch in {}: expected {}, found {}",
+                        file.display(),
+                        id,
+                        fixture.chain_id
+                    )));
+                }
+                _ => chain_id = Some(fixture.chain_id),
+            }
+
+            headers.extend(fixture.headers);
+        }
+
+        check_contiguous(&headers)?;
+        let store = Self::new(chain_id.unwrap_or_default(), headers);
+        info!(
+            headers = store.len(),
+            chain_id = store.chain_id,
+            "Loaded header fixture pack"
+        );
+
+        Ok(store)
+    }
+
+    pub fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    pub fn len(&self) -> usize {
+        self.headers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.headers.is_empty()
+    }
+
+    /// Lowest and highest block numbers covered by the pack
+    pub fn block_range(&self) -> Option<(u64, u64)> {
+        let first = self.headers.keys().next()?;
+        let last = self.headers.keys().next_back()?;
+        Some((*first, *last))
+    }
+
+    pub fn get_block_headers_by_block_range(
+        &self,
+        start_block: u64,
+        end_block: u64,
+    ) -> Result<Vec<BlockHeader>, DbError> {
+        if start_block > end_block {
+            return Err(DbError::InvalidBlockRange {
+                start_block,
+                end_block,
+            });
+        }
+
+        Ok(self
+            .headers
+            .range(start_block..=end_block)
+            .map(|(_, header)| header.clone())
+            .collect())
+    }
+
+    pub fn get_block_header_by_number(&self, block_number: u64) -> Option<BlockHeader> {
+        self.headers.get(&block_number).cloned()
+    }
+
+    pub fn get_block_header_by_hash(&self, block_hash: &str) -> Option<BlockHeader> {
+        self.headers
+            .values()
+            .find(|header| header.block_hash.eq_ignore_ascii_case(block_hash))
+            .cloned()
+    }
+
+    /// Returns the first block of every hour in the range, like the Postgres
+    /// `DISTINCT ON` bucket query.
+    pub fn get_hourly_block_headers_in_range(
+        &self,
+        start_block: u64,
+        end_block: u64,
+    ) -> Result<Vec<BlockHeader>, DbError> {
+        self.get_sampled_block_headers(start_block, end_block, &SamplingMode::hourly())
+    }
+
+    pub fn get_sampled_block_headers(
+        &self,
+        start_block: u64,
+        end_block: u64,
+        sampling: &SamplingMode,
+    ) -> Result<Vec<BlockHeader>, DbError> {
+        sampling.validate(start_block, end_block)?;
+
+        match sampling {
+            SamplingMode::EveryBlock => {
+                self.get_block_headers_by_block_range(start_block, end_block)
+            }
+            SamplingMode::EveryNth { step } => Ok(self
+                .get_block_headers_by_block_range(start_block, end_block)?
+                .into_iter()
+                .filter(|header| (header.number as u64 - start_block) % step == 0)
+                .collect()),
+            SamplingMode::TimeBucket { bucket_secs } => {
+                let mut seen_buckets = HashSet::new();
+                let mut headers = Vec::new();
+
+                for header in self.get_block_headers_by_block_range(start_block, end_block)? {
+                    let timestamp = header_timestamp(&header)?;
+                    if seen_buckets.insert(timestamp / bucket_secs) {
+                        headers.push(header);
+                    }
+                }
+
+                Ok(headers)
+            }
+            SamplingMode::Blocks { blocks } => {
+                let requested = SamplingMode::sorted_blocks(blocks);
+                let missing: Vec<u64> = requested
+                    .iter()
+                    .filter(|block| !self.headers.contains_key(block))
+                    .copied()
+                    .collect();
+                if !missing.is_empty() {
+                    return Err(DbError::MissingBlocks(missing));
+                }
+
+                Ok(requested
+                    .iter()
+                    .map(|block| self.headers[block].clone())
+                    .collect())
+            }
+        }
+    }
+}
+
+fn check_contiguous(headers: &[BlockHeader]) -> Result<(), DbError> {
+    let mut numbers: Vec<i64> = headers.iter().map(|header| header.number).collect();
+    numbers.sort_unstable();
+
+    for pair in numbers.windows(2) {
+        if pair[0] == pair[1] {
+            return Err(DbError::Fixture(format!(
+                "Block {} appears more than once",
+                pair[0]
+            )));
+        }
+        if pair[1] != pair[0] + 1 {
+            return Err(DbError::Fixture(format!(
+                "Blocks {} to {} are missing",
+                pair[0] + 1,
+                pair[1] - 1
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn header_timestamp(header: &BlockHeader) -> Result<u64, DbError> {
+    let timestamp = header
+        .timestamp
+        .as_deref()
+        .ok_or_else(|| DbError::Fixture(format!("Block {} has no timestamp", header.number)))?;
+
+    u64::from_str_radix(timestamp.trim_start_matches("0x"), 16).map_err(|e| {
+        DbError::Fixture(format!(
+            "Invalid timestamp {} for block {}: {}",
+            timestamp, header.number, e
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(number: i64, timestamp: u64) -> BlockHeader {
+        BlockHeader {
+            number,
+            block_hash: format!("0x{:064x}", number),
+            timestamp: Some(format!("0x{:x}", timestamp)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_block_range_queries() {
+        let store =
+            FixtureHeaderStore::new(11155111, vec![header(12, 0), header(10, 0), header(11, 0)]);
+
+        assert_eq!(store.len(), 3);
+        assert_eq!(store.block_range(), Some((10, 12)));
+
+        let headers = store.get_block_headers_by_block_range(10, 11).unwrap();
+        assert_eq!(
+            headers.iter().map(|h| h.number).collect::<Vec<_>>(),
+            vec![10, 11]
+        );
+
+        assert!(store.get_block_header_by_number(12).is_some());
+        assert!(store.get_block_header_by_number(13).is_none());
+        assert_eq!(
+            store
+                .get_block_header_by_hash(&format!("0x{:064x}", 11))
+                .map(|h| h.number),
+            Some(11)
+        );
+        assert!(store
+            .get_block_header_by_hash(&format!("0x{:064x}", 13))
+            .is_none());
+        assert!(matches!(
+            store.get_block_headers_by_block_range(12, 10),
+            Err(DbError::InvalidBlockRange { .. })
+        ));
+    }
+
+    #[test]
+    fn test_hourly_headers() {
+        let store = FixtureHeaderStore::new(
+            1,
+            vec![
+                header(1, 3600),
+                header(2, 3612),
+                header(3, 7199),
+                header(4, 7200),
+                header(5, 10900),
+            ],
+        );
+
+        let headers = store.get_hourly_block_headers_in_range(1, 5).unwrap();
+        assert_eq!(
+            headers.iter().map(|h| h.number).collect::<Vec<_>>(),
+            vec![1, 4, 5]
+        );
+    }
+
+    #[test]
+    fn test_sampled_headers() {
+        let store = FixtureHeaderStore::new(
+            1,
+            (1..=10)
+                .map(|number| header(number, number as u64 * 600))
+                .collect(),
+        );
+        let numbers = |sampling: SamplingMode| {
+            store
+                .get_sampled_block_headers(2, 9, &sampling)
+                .unwrap()
+                .iter()
+                .map(|h| h.number)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            numbers(SamplingMode::EveryBlock),
+            (2..=9).collect::<Vec<_>>()
+        );
+        assert_eq!(numbers(SamplingMode::EveryNth { step: 3 }), vec![2, 5, 8]);
+        assert_eq!(
+            numbers(SamplingMode::TimeBucket { bucket_secs: 1800 }),
+            vec![2, 3, 6, 9]
+        );
+        assert_eq!(
+            numbers(SamplingMode::Blocks {
+                blocks: vec![7, 3, 7]
+            }),
+            vec![3, 7]
+        );
+
+        let mut sparse = (1..=10).map(|number| header(number, 0)).collect::<Vec<_>>();
+        sparse.retain(|h| h.number != 4);
+        let store = FixtureHeaderStore::new(1, sparse);
+        assert!(matches!(
+            store.get_sampled_block_headers(
+                1,
+                10,
+                &SamplingMode::Blocks { blocks: vec![3, 4] }
+            ),
+            Err(DbError::MissingBlocks(missing)) if missing == vec![4]
+        ));
+    }
+
+    #[test]
+    fn test_load_from_directory() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+
+        HeaderFixture::new(5, vec![header(1, 0), header(2, 12)])
+            .write_to_file(&dir.join("a.json"))
+            .unwrap();
+        HeaderFixture::new(5, vec![header(3, 24)])
+            .write_to_file(&dir.join("b.json"))
+            .unwrap();
+
+        let store = FixtureHeaderStore::load(dir).unwrap();
+        assert_eq!(store.chain_id(), 5);
+        assert_eq!(store.len(), 3);
+
+        HeaderFixture::new(6, vec![header(4, 36)])
+            .write_to_file(&dir.join("c.json"))
+            .unwrap();
+        assert!(matches!(
+            FixtureHeaderStore::load(dir),
+            Err(DbError::Fixture(_))
+        ));
+    }
+
+    #[test]
+    fn test_load_rejects_gaps_and_duplicates() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("pack.json");
+
+        HeaderFixture::new(5, vec![header(1, 0), header(3, 24)])
+            .write_to_file(&path)
+            .unwrap();
+        assert!(matches!(
+            FixtureHeaderStore::load(&path),
+            Err(DbError::Fixture(message)) if message.contains("missing")
+        ));
+
+        HeaderFixture::new(5, vec![header(1, 0), header(2, 12), header(2, 12)])
+            .write_to_file(&path)
+            .unwrap();
+        assert!(matches!(
+            FixtureHeaderStore::load(&path),
+            Err(DbError::Fixture(message)) if message.contains("more than once")
+        ));
+    }
+}
@@ -0,0 +1,106 @@
+use super::{BlockId, DbConnection, FixtureHeaderStore, SamplingMode};
+use crate::errors::DbError;
+use eth_rlp_types::BlockHeader;
+use std::sync::Arc;
+
+/// Where block headers are read from.
+///
+/// Production runs read from the Postgres indexer; tests and offline runs can
+/// use a [`FixtureHeaderStore`] loaded from a header fixture pack instead.
+#[derive(Debug, Clone)]
+pub enum HeaderSource {
+    Postgres(Arc<DbConnection>),
+    Fixture(Arc<FixtureHeaderStore>),
+}
+
+impl From<Arc<DbConnection>> for HeaderSource {
+    fn from(db_connection: Arc<DbConnection>) -> Self {
+        Self::Postgres(db_connection)
+    }
+}
+
+impl From<Arc<FixtureHeaderStore>> for HeaderSource {
+    fn from(store: Arc<FixtureHeaderStore>) -> Self {
+        Self::Fixture(store)
+    }
+}
+
+impl HeaderSource {
+    /// Empty in-memory header store, for tests that need no headers.
+    #[cfg(any(test, feature = "test-utils"))]
+    pub fn empty_for_tests() -> Self {
+        FixtureHeaderStore::new(1, Vec::new()).into()
+    }
+
+    pub async fn get_block_headers_by_block_range(
+        &self,
+        start_block: u64,
+        end_block: u64,
+    ) -> Result<Vec<BlockHeader>, DbError> {
+        match self {
+            Self::Postgres(db) => {
+                db.get_block_headers_by_block_range(start_block, end_block)
+                    .await
+            }
+            Self::Fixture(store) => store.get_block_headers_by_block_range(start_block, end_block),
+        }
+    }
+
+    pub async fn get_block_header_by_number(
+        &self,
+        block_number: u64,
+    ) -> Result<Option<BlockHeader>, DbError> {
+        match self {
+            Self::Postgres(db) => db.get_block_header_by_number(block_number).await,
+            Self::Fixture(store) => Ok(store.get_block_header_by_number(block_number)),
+        }
+    }
+
+    pub async fn get_block_header_by_hash(
+        &self,
+        block_hash: &str,
+    ) -> Result<Option<BlockHeader>, DbError> {
+        match self {
+            Self::Postgres(db) => db.get_block_header_by_hash(block_hash).await,
+            Self::Fixture(store) => Ok(store.get_block_header_by_hash(block_hash)),
+        }
+    }
+
+    pub async fn get_block_header(&self, block: &BlockId) -> Result<Option<BlockHeader>, DbError> {
+        match block {
+            BlockId::Number(block_number) => self.get_block_header_by_number(*block_number).await,
+            BlockId::Hash(block_hash) => self.get_block_header_by_hash(block_hash).await,
+        }
+    }
+
+    pub async fn get_hourly_block_headers_in_range(
+        &self,
+        start_block: u64,
+        end_block: u64,
+    ) -> Result<Vec<BlockHeader>, DbError> {
+        match self {
+            Self::Postgres(db) => {
+                db.get_hourly_block_headers_in_range(start_block, end_block)
+                    .await
+            }
+            Self::Fixture(store) => store.get_hourly_block_headers_in_range(start_block, end_block),
+        }
+    }
+
+    pub async fn get_sampled_block_headers(
+        &self,
+        start_block: u64,
+        end_block: u64,
+        sampling: &SamplingMode,
+    ) -> Result<Vec<BlockHeader>, DbError> {
+        match self {
+            Self::Postgres(db) => {
+                db.get_sampled_block_headers(start_block, end_block, sampling)
+                    .await
+            }
+            Self::Fixture(store) => {
+                store.get_sampled_block_headers(start_block, end_block, sampling)
+            }
+        }
+    }
+}
@@ -1,3 +1,8 @@
 mod db_access;
+mod fixture;
+mod header_source;
 
+pub use api_types::{BlockId, SamplingMode};
 pub use db_access::*;
+pub use fixture::*;
+pub use header_source::*;
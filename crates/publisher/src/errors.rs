@@ -1,3 +1,4 @@
+use api_types::RequestError;
 use common::UtilsError;
 use mmr::{InStoreTableError, MMRError, StoreError};
 use mmr_utils::MMRUtilsError;
@@ -19,6 +20,8 @@ pub enum PublisherError {
     Validator(#[from] ValidatorError),
     #[error("Invalid Stark proof receipt: receipt format or signature verification failed")]
     ReceiptError,
+    #[error("Database connection failed: {0}")]
+    DbConnection(#[from] DbError),
 }
 
 #[derive(Error, Debug)]
@@ -87,6 +90,12 @@ pub enum ValidatorError {
     StarknetProvider(#[from] starknet_handler::StarknetHandlerError),
     #[error("Invalid MMR root: expected {expected} but found {actual}")]
     InvalidMmrRoot { expected: U256, actual: U256 },
+    #[error("Invalid MMR elements count: expected {expected} but found {actual}")]
+    InvalidElementsCount { expected: u64, actual: u64 },
+    #[error("Fee guest rejected blocks {start_block} to {end_block}")]
+    RejectedBlocks { start_block: u64, end_block: u64 },
+    #[error("Block {0} not found")]
+    BlockNotFound(String),
     #[error("Failed to parse Felt value: {0}")]
     FeltParsing(#[from] FromStrError),
     #[error("Database connection failed: {0}")]
@@ -113,6 +122,8 @@ pub enum ProofGeneratorError {
     Join(#[from] tokio::task::JoinError),
     #[error("Risc0 serde error: {0}")]
     Risc0Serde(#[from] risc0_zkvm::serde::Error),
+    #[error("Unsupported journal version: expected {expected} but found {actual}")]
+    UnsupportedJournalVersion { expected: u32, actual: u32 },
 }
 
 #[derive(Error, Debug)]
@@ -127,4 +138,30 @@ pub enum DbError {
     EnvVar(#[from] UtilsError),
     #[error("Connection error: {0}")]
     Connection(String),
+    #[error("Invalid pool configuration: {0}")]
+    InvalidConfig(&'static str),
+    #[error("Header fixture error: {0}")]
+    Fixture(String),
+    #[error("Invalid sampling mode: {0}")]
+    InvalidSampling(String),
+    #[error("Requested blocks not found: {0:?}")]
+    MissingBlocks(Vec<u64>),
+    #[error("Invalid block identifier: {0}")]
+    InvalidBlockId(String),
+}
+
+impl From<RequestError> for DbError {
+    fn from(error: RequestError) -> Self {
+        match error {
+            RequestError::InvalidBlockRange {
+                start_block,
+                end_block,
+            } => Self::InvalidBlockRange {
+                start_block,
+                end_block,
+            },
+            RequestError::InvalidSampling(reason) => Self::InvalidSampling(reason),
+            RequestError::InvalidBlockId(reason) => Self::InvalidBlockId(reason),
+        }
+    }
 }
@@ -11,5 +11,8 @@ pub mod errors;
 pub mod utils;
 pub mod validator;
 
-pub use api::operations::{extract_fees, prove_mmr_update};
+pub use api::operations::{
+    extract_fee_stats, extract_fees, extract_fees_aggregated, extract_fees_groth16, get_batch,
+    get_block_inclusion_proof, list_batches, prove_mmr_update,
+};
 pub use errors::{PublisherError, ValidatorError};
@@ -1,10 +1,9 @@
-use risc0_zkvm::{Journal, Receipt};
+pub use api_types::Stark;
+use risc0_zkvm::Receipt;
 use serde::{Deserialize, Serialize};
 use starknet_crypto::Felt;
 use starknet_handler::MmrState;
 
-use crate::PublisherError;
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Groth16 {
     receipt: Receipt,
@@ -25,44 +24,13 @@ impl Groth16 {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Stark {
-    receipt: Receipt,
-    image_id: Vec<u8>,
-    method_id: [u32; 8],
-}
-
-impl Stark {
-    pub fn new(receipt: Receipt, image_id: Vec<u8>, method_id: [u32; 8]) -> Self {
-        Self {
-            receipt: receipt.clone(),
-            image_id,
-            method_id,
-        }
-    }
-
-    pub fn receipt(&self) -> Receipt {
-        self.receipt.clone()
-    }
-
-    pub fn journal(&self) -> Journal {
-        self.receipt.journal.clone()
-    }
-
-    pub fn image_id(&self) -> Result<[u8; 32], PublisherError> {
-        self.image_id
-            .clone()
-            .try_into()
-            .map_err(|_| PublisherError::ReceiptError)
-    }
-}
-
 #[derive(Debug, Clone)]
 pub struct BatchResult {
     start_block: u64,
     end_block: u64,
     new_mmr_state: MmrState,
     proof: Option<Groth16>,
+    stark: Option<Stark>,
     ipfs_hash: String,
 }
 
@@ -79,10 +47,18 @@ impl BatchResult {
             end_block,
             new_mmr_state,
             proof,
+            stark: None,
             ipfs_hash,
         }
     }
 
+    /// Attaches the STARK receipt of a batch proven for chaining instead of
+    /// on-chain verification.
+    pub fn with_stark(mut self, stark: Stark) -> Self {
+        self.stark = Some(stark);
+        self
+    }
+
     pub fn start_block(&self) -> u64 {
         self.start_block
     }
@@ -99,6 +75,10 @@ impl BatchResult {
         self.proof.clone()
     }
 
+    pub fn stark(&self) -> Option<&Stark> {
+        self.stark.as_ref()
+    }
+
     pub fn ipfs_hash(&self) -> String {
         self.ipfs_hash.clone()
     }
@@ -0,0 +1,197 @@
+use crate::errors::ValidatorError;
+pub use api_types::{BatchDivergence, BatchInfo, BatchListing, LocalBatch, OnchainBatch};
+use common::get_db_instances_dir;
+use mmr_utils::open_mmr_read_only;
+use starknet_handler::provider::StarknetProvider;
+use starknet_handler::{u256_from_hex, u256_to_hex, MmrSnapshot};
+use std::collections::BTreeSet;
+use tracing::{debug, warn};
+
+/// Lists the batches between the Fossil Store's min and latest MMR blocks,
+/// plus any other batch with a local database, comparing both sides.
+pub async fn list_batches(
+    provider: &StarknetProvider,
+    l2_store_address: &str,
+    batch_size: u64,
+) -> Result<BatchListing, ValidatorError> {
+    let min_mmr_block = provider.get_min_mmr_block(l2_store_address).await?;
+    let latest_mmr_block = provider.get_latest_mmr_block(l2_store_address).await?;
+
+    let mut batch_indexes: BTreeSet<u64> =
+        (min_mmr_block / batch_size..=latest_mmr_block / batch_size).collect();
+    batch_indexes.extend(local_batch_indexes()?);
+
+    let snapshots = provider
+        .get_mmr_states(l2_store_address, batch_indexes)
+        .await?;
+    let mut batches = Vec::with_capacity(snapshots.len());
+    for (batch_index, snapshot) in snapshots {
+        let batch = compare_batch(batch_size, batch_index, snapshot).await?;
+        if batch.is_known() {
+            batches.push(batch);
+        }
+    }
+
+    Ok(BatchListing {
+        batch_size,
+        min_mmr_block,
+        latest_mmr_block,
+        batches,
+    })
+}
+
+/// Compares the Fossil Store state of a batch with its local database.
+pub async fn get_batch(
+    provider: &StarknetProvider,
+    l2_store_address: &str,
+    batch_size: u64,
+    batch_index: u64,
+) -> Result<BatchInfo, ValidatorError> {
+    let snapshot = provider
+        .get_mmr_state(l2_store_address, batch_index)
+        .await?;
+    compare_batch(batch_size, batch_index, snapshot).await
+}
+
+/// Batches the store never saw come back zeroed, batch index included, so
+/// the index is the one requested rather than the snapshot's.
+async fn compare_batch(
+    batch_size: u64,
+    batch_index: u64,
+    snapshot: MmrSnapshot,
+) -> Result<BatchInfo, ValidatorError> {
+    let onchain = onchain_batch(snapshot)?;
+    let local = local_batch(batch_index).await?;
+
+    let batch = BatchInfo::new(batch_index, batch_size, onchain, local);
+    if !batch.divergence.is_empty() {
+        warn!(
+            batch_index,
+            divergence = ?batch.divergence,
+            "Local batch database diverges from the Fossil Store"
+        );
+    }
+    Ok(batch)
+}
+
+fn onchain_batch(snapshot: MmrSnapshot) -> Result<Option<OnchainBatch>, ValidatorError> {
+    // Batches the store never saw are returned zeroed
+    if snapshot.leaves_count() == 0 {
+        return Ok(None);
+    }
+
+    let ipfs_cid = snapshot.ipfs_cid()?;
+    Ok(Some(OnchainBatch {
+        latest_mmr_block: snapshot.latest_mmr_block(),
+        latest_mmr_block_hash: u256_to_hex(snapshot.latest_mmr_block_hash()),
+        leaves_count: snapshot.leaves_count(),
+        root_hash: u256_to_hex(snapshot.root_hash()),
+        ipfs_cid,
+    }))
+}
+
+async fn local_batch(batch_index: u64) -> Result<Option<LocalBatch>, ValidatorError> {
+    let path = get_db_instances_dir()?.join(format!("batch_{}.db", batch_index));
+    // Validation creates empty files for batches it could not download
+    let is_empty = std::fs::metadata(&path)
+        .map(|metadata| metadata.len() == 0)
+        .unwrap_or(true);
+    if is_empty {
+        return Ok(None);
+    }
+
+    let path = path.to_str().ok_or(ValidatorError::InvalidInput(
+        "Batch database path is not UTF-8",
+    ))?;
+    let (mmr, pool) = open_mmr_read_only(path).await?;
+
+    let elements_count = mmr.elements_count.get().await?;
+    let leaves_count = mmr.leaves_count.get().await?;
+    let local = if elements_count == 0 {
+        None
+    } else {
+        let bag = mmr.bag_the_peaks(Some(elements_count)).await?;
+        let root_hash = mmr.calculate_root_hash(&bag, elements_count)?;
+        let root_hash = root_hash.trim_start_matches("0x");
+        Some(LocalBatch {
+            leaves_count: leaves_count as u64,
+            elements_count: elements_count as u64,
+            root_hash: u256_to_hex(u256_from_hex(root_hash)?),
+        })
+    };
+    pool.close().await;
+
+    debug!(batch_index, ?local, "Read local batch database");
+    Ok(local)
+}
+
+/// Indexes of the `batch_N.db` files in the local database directory.
+fn local_batch_indexes() -> Result<Vec<u64>, ValidatorError> {
+    let dir = get_db_instances_dir()?;
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let entries = std::fs::read_dir(&dir).map_err(common::UtilsError::from)?;
+    Ok(entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            entry
+                .file_name()
+                .to_str()?
+                .strip_prefix("batch_")?
+                .strip_suffix(".db")?
+                .parse()
+                .ok()
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn onchain(leaves_count: u64, root_hash: &str) -> OnchainBatch {
+        OnchainBatch {
+            latest_mmr_block: 2047,
+            latest_mmr_block_hash: "0x01".to_string(),
+            leaves_count,
+            root_hash: root_hash.to_string(),
+            ipfs_cid: "Qm".to_string(),
+        }
+    }
+
+    fn local(leaves_count: u64, root_hash: &str) -> LocalBatch {
+        LocalBatch {
+            leaves_count,
+            elements_count: 2 * leaves_count - leaves_count.count_ones() as u64,
+            root_hash: root_hash.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_batch_info_divergence() {
+        let batch = BatchInfo::new(1, 1024, Some(onchain(8, "0xaa")), Some(local(8, "0xaa")));
+        assert_eq!(batch.block_range, (1024, 2047));
+        assert!(batch.divergence.is_empty());
+
+        let batch = BatchInfo::new(1, 1024, Some(onchain(8, "0xaa")), Some(local(4, "0xbb")));
+        assert_eq!(
+            batch.divergence,
+            vec![
+                BatchDivergence::LeavesCountMismatch,
+                BatchDivergence::RootMismatch
+            ]
+        );
+
+        let batch = BatchInfo::new(1, 1024, Some(onchain(8, "0xaa")), None);
+        assert_eq!(batch.divergence, vec![BatchDivergence::MissingLocal]);
+
+        let batch = BatchInfo::new(1, 1024, None, Some(local(8, "0xaa")));
+        assert_eq!(batch.divergence, vec![BatchDivergence::MissingOnchain]);
+
+        let batch = BatchInfo::new(1, 1024, None, None);
+        assert!(!batch.is_known());
+        assert!(batch.divergence.is_empty());
+    }
+}
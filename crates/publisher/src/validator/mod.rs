@@ -1,3 +1,5 @@
+mod batches;
 mod validator;
 
+pub use batches::*;
 pub use validator::*;
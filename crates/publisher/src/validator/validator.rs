@@ -1,27 +1,54 @@
-use crate::db::DbConnection;
-use crate::errors::ValidatorError;
-use crate::{core::ProofGenerator, utils::Stark};
+use crate::db::{BlockId, HeaderSource, SamplingMode};
+use crate::errors::{ProofGeneratorError, ValidatorError};
+use crate::{
+    core::ProofGenerator,
+    utils::{Groth16, Stark},
+};
+pub use api_types::{BlockInclusionProof, ProvingStage};
 use common::get_or_create_db_path;
-use guest_types::{BlocksValidityInput, GuestProof, MMRInput};
+use guest_types::{
+    BlocksValidityInput, FeeAggregationInput, FeeProofJournal, FeeStatsInput, FeeStatsJournal,
+    FeeStatsSampling, GuestProof, MMRInput, FEE_STATS_JOURNAL_VERSION,
+};
 use ipfs_utils::IpfsManager;
-use methods::{VALIDATE_BLOCKS_AND_EXTRACT_FEES_ELF, VALIDATE_BLOCKS_AND_EXTRACT_FEES_ID};
+use methods::{
+    AGGREGATE_FEE_PROOFS_ELF, AGGREGATE_FEE_PROOFS_ID, VALIDATE_BLOCKS_AND_COMPUTE_FEE_STATS_ELF,
+    VALIDATE_BLOCKS_AND_COMPUTE_FEE_STATS_ID, VALIDATE_BLOCKS_AND_EXTRACT_FEES_ELF,
+    VALIDATE_BLOCKS_AND_EXTRACT_FEES_ID,
+};
 use mmr::{PeaksOptions, MMR};
 use mmr_utils::{initialize_mmr, StoreManager};
 use starknet::core::types::U256;
 use starknet_handler::provider::StarknetProvider;
-use starknet_handler::u256_from_hex;
-use std::collections::HashMap;
+use starknet_handler::{u256_from_hex, MmrSnapshot};
+use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
+use std::sync::Arc;
 use store::SqlitePool;
 use tracing::{error, info, warn};
 
+/// Called with the number of proven batches and the total number of batches
+/// as batch proofs complete.
+pub type BatchProgress = Arc<dyn Fn(usize, usize) + Send + Sync>;
+
+/// Called with every [`ProvingStage`] a validator enters.
+pub type StageListener = Arc<dyn Fn(ProvingStage) + Send + Sync>;
+
 pub struct ValidatorBuilder<'a> {
     rpc_url: &'a str,
     l2_store_address: &'a str,
     chain_id: u64,
     proof_generator: ProofGenerator<BlocksValidityInput>,
+    stats_proof_generator: ProofGenerator<FeeStatsInput>,
+    aggregation_proof_generator: ProofGenerator<FeeAggregationInput>,
     batch_size: u64,
     skip_proof: bool,
+    header_source: HeaderSource,
+    local_batches_only: bool,
+    sampling: SamplingMode,
+    strict: bool,
+    progress: Option<BatchProgress>,
+    stages: Option<StageListener>,
 }
 
 impl<'a> ValidatorBuilder<'a> {
@@ -31,6 +58,7 @@ impl<'a> ValidatorBuilder<'a> {
         chain_id: u64,
         batch_size: u64,
         skip_proof: bool,
+        header_source: HeaderSource,
     ) -> Result<Self, ValidatorError> {
         if batch_size == 0 {
             return Err(ValidatorError::InvalidInput(
@@ -42,28 +70,292 @@ impl<'a> ValidatorBuilder<'a> {
             VALIDATE_BLOCKS_AND_EXTRACT_FEES_ELF,
             VALIDATE_BLOCKS_AND_EXTRACT_FEES_ID,
         )?;
+        let stats_proof_generator = ProofGenerator::new(
+            VALIDATE_BLOCKS_AND_COMPUTE_FEE_STATS_ELF,
+            VALIDATE_BLOCKS_AND_COMPUTE_FEE_STATS_ID,
+        )?;
+        let aggregation_proof_generator =
+            ProofGenerator::new(AGGREGATE_FEE_PROOFS_ELF, AGGREGATE_FEE_PROOFS_ID)?;
 
         Ok(Self {
             rpc_url,
             l2_store_address,
             chain_id,
             proof_generator,
+            stats_proof_generator,
+            aggregation_proof_generator,
             batch_size,
             skip_proof,
+            header_source,
+            local_batches_only: false,
+            sampling: SamplingMode::default(),
+            strict: true,
+            progress: None,
+            stages: None,
         })
     }
 
+    /// Reads batch MMRs from the local `db-instances` directory only, without
+    /// querying the Fossil Store or IPFS. On-chain root verification is skipped
+    /// in this mode.
+    pub fn with_local_batches_only(mut self) -> Self {
+        self.local_batches_only = true;
+        self
+    }
+
+    /// Selects which blocks of the requested range are proven. Defaults to the
+    /// first block of every hour.
+    pub fn with_sampling(mut self, sampling: SamplingMode) -> Self {
+        self.sampling = sampling;
+        self
+    }
+
+    /// When disabled, the fee guest commits a journal with `ok == false` for
+    /// invalid blocks instead of aborting, so the rejection itself is proven.
+    /// Enabled by default.
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Reports the progress of batch fee proofs, e.g. to a job tracker.
+    pub fn with_progress(mut self, progress: BatchProgress) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Reports every stage of proof requests, e.g. to stream them to clients.
+    pub fn with_stages(mut self, stages: StageListener) -> Self {
+        self.stages = Some(stages);
+        self
+    }
+
+    fn report_progress(&self, proven: usize, total: usize) {
+        if let Some(progress) = &self.progress {
+            progress(proven, total);
+        }
+    }
+
+    fn report_stage(&self, stage: ProvingStage) {
+        if let Some(stages) = &self.stages {
+            stages(stage);
+        }
+    }
+
     pub async fn validate_blocks_and_extract_fees(
         &self,
         start_block: u64,
         end_block: u64,
     ) -> Result<Vec<Stark>, ValidatorError> {
-        let db_connection = DbConnection::new().await.map_err(|e| {
-            error!(error = %e, "Failed to create DB connection");
-            e
+        let proofs = self.prove_batch_fees(start_block, end_block).await?;
+        Ok(proofs.into_iter().map(|(proof, _)| proof).collect())
+    }
+
+    /// Proves the fees of every batch of the range, keeping the journal decoded
+    /// while checking each receipt next to its proof.
+    async fn prove_batch_fees(
+        &self,
+        start_block: u64,
+        end_block: u64,
+    ) -> Result<Vec<(Stark, FeeProofJournal)>, ValidatorError> {
+        let inputs = self.prepare_batch_inputs(start_block, end_block).await?;
+
+        let total = inputs.len();
+        self.report_progress(0, total);
+
+        let mut proofs = Vec::new();
+        for input in inputs {
+            self.report_stage(ProvingStage::ProvingBatch {
+                batch: proofs.len() + 1,
+                total,
+            });
+            let proof = self.proof_generator.generate_stark_proof(input).await?;
+            let journal = self.check_fee_journal(&proof.receipt())?;
+            proofs.push((proof, journal));
+            self.report_progress(proofs.len(), total);
+        }
+
+        Ok(proofs)
+    }
+
+    /// Same as [`Self::validate_blocks_and_extract_fees`], but wraps every batch
+    /// proof in Groth16 with Garaga calldata so it can be verified on Starknet.
+    pub async fn validate_blocks_and_extract_fees_groth16(
+        &self,
+        start_block: u64,
+        end_block: u64,
+    ) -> Result<Vec<Groth16>, ValidatorError> {
+        let inputs = self.prepare_batch_inputs(start_block, end_block).await?;
+
+        let total = inputs.len();
+        self.report_progress(0, total);
+
+        let mut proofs = Vec::new();
+        for input in inputs {
+            self.report_stage(ProvingStage::ProvingBatch {
+                batch: proofs.len() + 1,
+                total,
+            });
+            let proof = self.proof_generator.generate_groth16_proof(input).await?;
+            self.check_fee_journal(&proof.receipt())?;
+            proofs.push(proof);
+            self.report_progress(proofs.len(), total);
+        }
+
+        Ok(proofs)
+    }
+
+    /// Proves the fees of the range like [`Self::validate_blocks_and_extract_fees`]
+    /// and folds the per-batch receipts into a single receipt whose journal is a
+    /// [`guest_types::AggregatedFeeJournal`].
+    pub async fn validate_blocks_and_extract_fees_aggregated(
+        &self,
+        start_block: u64,
+        end_block: u64,
+    ) -> Result<Stark, ValidatorError> {
+        let batch_proofs = self.prove_batch_fees(start_block, end_block).await?;
+
+        let mut journals = Vec::with_capacity(batch_proofs.len());
+        let mut receipts = Vec::with_capacity(batch_proofs.len());
+        for (proof, journal) in batch_proofs {
+            if !journal.ok {
+                return Err(ValidatorError::RejectedBlocks {
+                    start_block: journal.block_range.0,
+                    end_block: journal.block_range.1,
+                });
+            }
+            journals.push(journal);
+            receipts.push(proof.receipt());
+        }
+
+        info!("Aggregating {} batch fee proofs", receipts.len());
+        self.report_stage(ProvingStage::Aggregating);
+        let input = FeeAggregationInput::new(VALIDATE_BLOCKS_AND_EXTRACT_FEES_ID, journals);
+
+        Ok(self
+            .aggregation_proof_generator
+            .generate_stark_proof_with_assumptions(input, receipts)
+            .await?)
+    }
+
+    fn check_fee_journal(
+        &self,
+        receipt: &risc0_zkvm::Receipt,
+    ) -> Result<FeeProofJournal, ValidatorError> {
+        let journal = self.proof_generator.decode_fee_journal(receipt)?;
+        if !journal.ok {
+            warn!(
+                batch_index = journal.batch_index,
+                "Fee guest rejected blocks {} to {}", journal.block_range.0, journal.block_range.1
+            );
+        }
+        Ok(journal)
+    }
+
+    /// Proves aggregate base fee statistics (TWAP, min/max, variance and the
+    /// requested percentiles) over the sampled blocks of the range, in a single
+    /// receipt whose journal is a [`guest_types::FeeStatsJournal`]. The guest
+    /// checks the sample covers the range, so only every-block and every-nth
+    /// sampling are supported.
+    pub async fn validate_blocks_and_compute_fee_stats(
+        &self,
+        start_block: u64,
+        end_block: u64,
+        percentiles: &[u8],
+    ) -> Result<Stark, ValidatorError> {
+        if percentiles.iter().any(|&percentile| percentile > 100) {
+            return Err(ValidatorError::InvalidInput(
+                "Percentiles must be between 0 and 100",
+            ));
+        }
+
+        let sampling = match self.sampling {
+            SamplingMode::EveryBlock => FeeStatsSampling::EveryBlock,
+            SamplingMode::EveryNth { step } => FeeStatsSampling::EveryNth { step },
+            SamplingMode::TimeBucket { .. } | SamplingMode::Blocks { .. } => {
+                return Err(ValidatorError::InvalidInput(
+                    "Fee statistics can only be proven over every block or every nth block",
+                ));
+            }
+        };
+
+        let inputs = self.prepare_batch_inputs(start_block, end_block).await?;
+
+        let proof = self
+            .stats_proof_generator
+            .generate_stark_proof(FeeStatsInput::new(
+                (start_block, end_block),
+                sampling,
+                inputs,
+                percentiles.to_vec(),
+            ))
+            .await?;
+
+        let journal: FeeStatsJournal = self
+            .stats_proof_generator
+            .decode_journal(&proof.receipt())?;
+        if journal.version != FEE_STATS_JOURNAL_VERSION {
+            return Err(ProofGeneratorError::UnsupportedJournalVersion {
+                expected: FEE_STATS_JOURNAL_VERSION,
+                actual: journal.version,
+            }
+            .into());
+        }
+        Ok(proof)
+    }
+
+    /// Returns the inclusion proof of a block in its batch MMR, read from the
+    /// same batch files as the fee proofs.
+    pub async fn get_block_inclusion_proof(
+        &self,
+        block: &BlockId,
+    ) -> Result<BlockInclusionProof, ValidatorError> {
+        let header = self
+            .header_source
+            .get_block_header(block)
+            .await?
+            .ok_or_else(|| ValidatorError::BlockNotFound(block.to_string()))?;
+
+        let batch_index = header.number as u64 / self.batch_size;
+        let headers = std::slice::from_ref(&header);
+        let mmr_states = self.get_mmr_states(headers).await?;
+        let mmrs = self
+            .initialize_mmrs_for_headers(headers, &mmr_states)
+            .await?;
+        let (store_manager, mmr, pool) = mmrs.get(&batch_index).ok_or_else(|| {
+            error!("MMR not found for batch index: {}", batch_index);
+            ValidatorError::Store(store::StoreError::GetError)
+        })?;
+
+        // A header the indexer knows about may not have been appended yet
+        let element_index = store_manager
+            .get_element_index_for_value(pool, &header.block_hash)
+            .await?
+            .ok_or_else(|| ValidatorError::BlockNotFound(block.to_string()))?;
+
+        let proof = mmr.get_proof(element_index, None).await.map_err(|e| {
+            error!(error = %e, "Failed to get proof for element index: {}", element_index);
+            ValidatorError::Store(store::StoreError::GetError)
         })?;
-        let headers: Vec<eth_rlp_types::BlockHeader> = db_connection
-            .get_hourly_block_headers_in_range(start_block, end_block)
+
+        Ok(BlockInclusionProof {
+            block_number: header.number as u64,
+            batch_index,
+            proof: LocalGuestProof::from(proof).into(),
+        })
+    }
+
+    /// Fetches the sampled headers of the range and builds one guest input per
+    /// batch, ordered by batch index.
+    async fn prepare_batch_inputs(
+        &self,
+        start_block: u64,
+        end_block: u64,
+    ) -> Result<Vec<BlocksValidityInput>, ValidatorError> {
+        self.report_stage(ProvingStage::FetchingHeaders);
+        let headers: Vec<eth_rlp_types::BlockHeader> = self
+            .header_source
+            .get_sampled_block_headers(start_block, end_block, &self.sampling)
             .await
             .map_err(|e| {
                 error!(error = %e, "Failed to fetch block headers");
@@ -72,18 +364,34 @@ impl<'a> ValidatorBuilder<'a> {
 
         self.validate_headers(&headers)?;
 
-        let mmrs = self.initialize_mmrs_for_headers(&headers).await?;
+        let mmr_states = self.get_mmr_states(&headers).await?;
+        let mmrs = self
+            .initialize_mmrs_for_headers(&headers, &mmr_states)
+            .await?;
 
-        if self.skip_proof {
-            tracing::info!("Skipping MMR root verification as skip_proof is enabled");
+        if self.skip_proof || self.local_batches_only {
+            tracing::info!("Skipping MMR root verification against onchain state");
         } else {
             tracing::info!("Verifying MMR roots against onchain state...");
-            self.verify_mmr_roots(&mmrs).await?;
+            self.report_stage(ProvingStage::VerifyingRoots);
+            self.verify_mmr_roots(&mmrs, &mmr_states).await?;
         }
 
         let block_indexes = self.collect_block_indexes(&headers, &mmrs).await?;
-        self.generate_proofs_for_batches(&headers, &mmrs, &block_indexes)
-            .await
+
+        let mut batch_indexes: Vec<u64> = mmrs.keys().copied().collect();
+        batch_indexes.sort_unstable();
+
+        let mut inputs = Vec::new();
+        for batch_index in batch_indexes {
+            let (_, mmr, _) = &mmrs[&batch_index];
+            inputs.push(
+                self.build_batch_input(&headers, mmr, &block_indexes, batch_index)
+                    .await?,
+            );
+        }
+
+        Ok(inputs)
     }
 
     fn validate_headers(
@@ -99,14 +407,11 @@ impl<'a> ValidatorBuilder<'a> {
     async fn verify_mmr_roots(
         &self,
         mmrs: &HashMap<u64, (StoreManager, MMR, SqlitePool)>,
+        mmr_states: &BTreeMap<u64, MmrSnapshot>,
     ) -> Result<(), ValidatorError> {
-        let batch_indexes: Vec<u64> = mmrs.keys().cloned().collect();
-        let onchain_mmr_roots = self.get_onchain_mmr_root(&batch_indexes).await?;
-
-        let onchain_roots_map: HashMap<u64, U256> = batch_indexes
+        let onchain_roots_map: HashMap<u64, U256> = mmr_states
             .iter()
-            .zip(onchain_mmr_roots.iter())
-            .map(|(&index, root)| (index, root.clone()))
+            .map(|(&index, snapshot)| (index, snapshot.root_hash()))
             .collect();
 
         for (batch_index, (_, mmr, _)) in mmrs.iter() {
@@ -147,30 +452,13 @@ impl<'a> ValidatorBuilder<'a> {
         Ok(())
     }
 
-    async fn generate_proofs_for_batches(
-        &self,
-        headers: &[eth_rlp_types::BlockHeader],
-        mmrs: &HashMap<u64, (StoreManager, MMR, SqlitePool)>,
-        block_indexes: &[(usize, u64)],
-    ) -> Result<Vec<Stark>, ValidatorError> {
-        let mut proofs = Vec::new();
-        for (batch_index, (_, mmr, _)) in mmrs {
-            let proof = self
-                .generate_batch_proof(headers, mmr, block_indexes, *batch_index)
-                .await?;
-            proofs.push(proof);
-        }
-
-        Ok(proofs)
-    }
-
-    async fn generate_batch_proof(
+    async fn build_batch_input(
         &self,
         headers: &[eth_rlp_types::BlockHeader],
         mmr: &MMR,
         block_indexes: &[(usize, u64)],
         batch_index: u64,
-    ) -> Result<Stark, ValidatorError> {
+    ) -> Result<BlocksValidityInput, ValidatorError> {
         let batch_block_indexes = self.get_batch_block_indexes(block_indexes, batch_index);
         let batch_headers = self.get_batch_headers(headers, batch_index);
 
@@ -182,13 +470,15 @@ impl<'a> ValidatorBuilder<'a> {
         self.validate_proofs_count(&batch_headers, &guest_proofs, batch_index)?;
 
         let mmr_input = self.prepare_mmr_input(mmr).await?;
-        let blocks_validity_input =
-            BlocksValidityInput::new(self.chain_id, batch_headers, mmr_input, guest_proofs);
 
-        Ok(self
-            .proof_generator
-            .generate_stark_proof(blocks_validity_input)
-            .await?)
+        Ok(BlocksValidityInput::new(
+            self.chain_id,
+            self.batch_size,
+            batch_headers,
+            mmr_input,
+            guest_proofs,
+            self.strict,
+        ))
     }
 
     async fn get_batch_proofs(
@@ -226,53 +516,67 @@ impl<'a> ValidatorBuilder<'a> {
         Ok(())
     }
 
-    async fn get_onchain_mmr_root(
+    /// Reads the onchain state of every batch the headers fall in, once, so
+    /// the IPFS download and the root check see the same snapshots.
+    async fn get_mmr_states(
         &self,
-        batch_indexs: &Vec<u64>,
-    ) -> Result<Vec<starknet::core::types::U256>, ValidatorError> {
-        let provider = StarknetProvider::new(&self.rpc_url)?;
-
-        let mut mmr_roots = Vec::new();
-
-        for batch_index in batch_indexs {
-            let mmr_state = provider
-                .get_mmr_state(&self.l2_store_address, *batch_index)
-                .await?;
-            mmr_roots.push(mmr_state.root_hash());
+        headers: &[eth_rlp_types::BlockHeader],
+    ) -> Result<BTreeMap<u64, MmrSnapshot>, ValidatorError> {
+        if self.local_batches_only {
+            return Ok(BTreeMap::new());
         }
 
-        Ok(mmr_roots)
+        let provider = StarknetProvider::new(&self.rpc_url)?;
+        Ok(provider
+            .get_mmr_states(
+                &self.l2_store_address,
+                headers
+                    .iter()
+                    .map(|header| header.number as u64 / self.batch_size),
+            )
+            .await?)
     }
 
     async fn initialize_mmrs_for_headers(
         &self,
         headers: &[eth_rlp_types::BlockHeader],
+        mmr_states: &BTreeMap<u64, MmrSnapshot>,
     ) -> Result<HashMap<u64, (StoreManager, MMR, SqlitePool)>, ValidatorError> {
         let mut mmrs = HashMap::new();
-        let provider = StarknetProvider::new(&self.rpc_url)?;
         let ipfs_manager = IpfsManager::new();
 
         for header in headers {
             let batch_index = header.number as u64 / self.batch_size;
 
             if !mmrs.contains_key(&batch_index) {
-                let mmr_state = provider
-                    .get_mmr_state(&self.l2_store_address, batch_index)
-                    .await?;
-
                 let batch_file_name = get_or_create_db_path(&format!("batch_{}.db", batch_index))
                     .map_err(|e| {
                     error!(error = %e, "Failed to get or create DB path");
                     ValidatorError::Store(store::StoreError::GetError)
                 })?;
 
-                let ipfs_hash = mmr_state.ipfs_hash();
-                let ipfs_hash_str = String::try_from(ipfs_hash)
-                    .map_err(|_| ValidatorError::Store(store::StoreError::GetError))?;
-                match ipfs_manager
-                    .fetch_db(&ipfs_hash_str, Path::new(&batch_file_name))
-                    .await
-                {
+                if self.local_batches_only {
+                    info!("Using local DB for batch {}", batch_index);
+                    let mmr_components = initialize_mmr(&batch_file_name).await.map_err(|e| {
+                        error!(error = %e, "Failed to initialize MMR");
+                        ValidatorError::Store(store::StoreError::GetError)
+                    })?;
+                    mmrs.insert(batch_index, mmr_components);
+                    continue;
+                }
+
+                // A batch without a valid CID is read from the local file, like a failed download
+                let fetched = match mmr_states[&batch_index].ipfs_cid() {
+                    Ok(ipfs_hash_str) => {
+                        self.report_stage(ProvingStage::DownloadingBatch { batch_index });
+                        ipfs_manager
+                            .fetch_db(&ipfs_hash_str, Path::new(&batch_file_name))
+                            .await
+                            .map_err(|e| e.to_string())
+                    }
+                    Err(e) => Err(e.to_string()),
+                };
+                match fetched {
                     Ok(_) => {
                         info!(
                             "Successfully downloaded DB from IPFS for batch {}",
@@ -378,6 +682,54 @@ impl<'a> ValidatorBuilder<'a> {
     }
 }
 
+/// Checks that a fee journal was proven against the MMR the Fossil Store holds
+/// for its batch, by comparing the committed root and elements count with
+/// `get_mmr_state(batch_index)`.
+pub async fn verify_fee_journal_root(
+    provider: &StarknetProvider,
+    l2_store_address: &str,
+    journal: &FeeProofJournal,
+) -> Result<(), ValidatorError> {
+    verify_batch_root(
+        provider,
+        l2_store_address,
+        journal.batch_index,
+        &journal.mmr_root,
+        journal.elements_count,
+    )
+    .await
+}
+
+/// Checks a batch MMR root and elements count committed by a guest against the Fossil Store.
+pub async fn verify_batch_root(
+    provider: &StarknetProvider,
+    l2_store_address: &str,
+    batch_index: u64,
+    mmr_root: &str,
+    elements_count: usize,
+) -> Result<(), ValidatorError> {
+    let mmr_state = provider
+        .get_mmr_state(l2_store_address, batch_index)
+        .await?;
+
+    let committed_root = u256_from_hex(mmr_root)?;
+    if committed_root != mmr_state.root_hash() {
+        return Err(ValidatorError::InvalidMmrRoot {
+            expected: mmr_state.root_hash(),
+            actual: committed_root,
+        });
+    }
+
+    if elements_count as u64 != mmr_state.elements_count() {
+        return Err(ValidatorError::InvalidElementsCount {
+            expected: mmr_state.elements_count(),
+            actual: elements_count as u64,
+        });
+    }
+
+    Ok(())
+}
+
 // Add this wrapper struct
 pub struct LocalGuestProof {
     pub element_index: usize,
@@ -448,7 +800,15 @@ mod tests {
 
     #[tokio::test]
     async fn test_validator_builder_new() {
-        let result = ValidatorBuilder::new("http://localhost:8545", "0x123", 1, 100, false).await;
+        let result = ValidatorBuilder::new(
+            "http://localhost:8545",
+            "0x123",
+            1,
+            100,
+            false,
+            HeaderSource::empty_for_tests(),
+        )
+        .await;
 
         assert!(result.is_ok());
         let builder = result.unwrap();
@@ -459,16 +819,31 @@ mod tests {
 
     #[tokio::test]
     async fn test_validator_builder_new_invalid_batch_size() {
-        let result = ValidatorBuilder::new("http://localhost:8545", "0x123", 1, 0, false).await;
+        let result = ValidatorBuilder::new(
+            "http://localhost:8545",
+            "0x123",
+            1,
+            0,
+            false,
+            HeaderSource::empty_for_tests(),
+        )
+        .await;
 
         assert!(matches!(result, Err(ValidatorError::InvalidInput(_))));
     }
 
     #[tokio::test]
     async fn test_validate_headers_empty() {
-        let builder = ValidatorBuilder::new("http://localhost:8545", "0x123", 1, 100, false)
-            .await
-            .unwrap();
+        let builder = ValidatorBuilder::new(
+            "http://localhost:8545",
+            "0x123",
+            1,
+            100,
+            false,
+            HeaderSource::empty_for_tests(),
+        )
+        .await
+        .unwrap();
 
         let headers = vec![];
         let result = builder.validate_headers(&headers);
@@ -478,9 +853,16 @@ mod tests {
 
     #[tokio::test]
     async fn test_validate_headers_valid() {
-        let builder = ValidatorBuilder::new("http://localhost:8545", "0x123", 1, 100, false)
-            .await
-            .unwrap();
+        let builder = ValidatorBuilder::new(
+            "http://localhost:8545",
+            "0x123",
+            1,
+            100,
+            false,
+            HeaderSource::empty_for_tests(),
+        )
+        .await
+        .unwrap();
 
         let header = eth_rlp_types::BlockHeader {
             number: 1,
@@ -495,9 +877,16 @@ mod tests {
 
     #[tokio::test]
     async fn test_get_batch_block_indexes() {
-        let builder = ValidatorBuilder::new("http://localhost:8545", "0x123", 1, 100, false)
-            .await
-            .unwrap();
+        let builder = ValidatorBuilder::new(
+            "http://localhost:8545",
+            "0x123",
+            1,
+            100,
+            false,
+            HeaderSource::empty_for_tests(),
+        )
+        .await
+        .unwrap();
 
         let block_indexes = vec![(1, 0), (2, 0), (3, 1), (4, 1)];
         let batch_0_indexes = builder.get_batch_block_indexes(&block_indexes, 0);
@@ -509,9 +898,16 @@ mod tests {
 
     #[tokio::test]
     async fn test_get_batch_headers() {
-        let builder = ValidatorBuilder::new("http://localhost:8545", "0x123", 1, 100, false)
-            .await
-            .unwrap();
+        let builder = ValidatorBuilder::new(
+            "http://localhost:8545",
+            "0x123",
+            1,
+            100,
+            false,
+            HeaderSource::empty_for_tests(),
+        )
+        .await
+        .unwrap();
 
         let headers = vec![
             eth_rlp_types::BlockHeader {
@@ -538,9 +934,16 @@ mod tests {
 
     #[tokio::test]
     async fn test_validate_proofs_count() {
-        let builder = ValidatorBuilder::new("http://localhost:8545", "0x123", 1, 100, false)
-            .await
-            .unwrap();
+        let builder = ValidatorBuilder::new(
+            "http://localhost:8545",
+            "0x123",
+            1,
+            100,
+            false,
+            HeaderSource::empty_for_tests(),
+        )
+        .await
+        .unwrap();
 
         let headers = vec![
             eth_rlp_types::BlockHeader::default(),
@@ -0,0 +1,129 @@
+//! End-to-end build-mmr -> extract-fees run against a header fixture pack.
+//!
+//! Runs without Postgres, Starknet, Ethereum RPC or IPFS: headers come from the
+//! JSON files in `tests/fixtures` (or `HEADER_FIXTURES_PATH`), batches are built
+//! in a temporary directory and the fee guest is executed in RISC Zero dev mode.
+//!
+//! Fixture packs are produced from the indexer database with
+//! `cargo run -p publisher --bin export-fixtures -- --start-block <n> --end-block <m> -o <file>`.
+use eth_rlp_types::BlockHeader;
+use guest_types::{BlockFeeRecord, FeeProofJournal, FEE_PROOF_JOURNAL_VERSION};
+use methods::{MMR_APPEND_ELF, MMR_APPEND_ID, VALIDATE_BLOCKS_AND_EXTRACT_FEES_ID};
+use publisher::core::{BatchProcessor, MMRStateManager, ProofGenerator};
+use publisher::db::FixtureHeaderStore;
+use publisher::validator::{ProvingStage, ValidatorBuilder};
+use starknet::core::chain_id;
+use starknet_handler::{account::StarknetAccount, provider::StarknetProvider};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+const BATCH_SIZE: u64 = 1024;
+// Never contacted: on-chain verification and IPFS are disabled below
+const UNUSED_RPC_URL: &str = "http://localhost:5050";
+
+fn fixtures_path() -> PathBuf {
+    std::env::var("HEADER_FIXTURES_PATH")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures"))
+}
+
+fn expected_hourly_fees(headers: &[BlockHeader]) -> Vec<BlockFeeRecord> {
+    let mut seen_hours = std::collections::HashSet::new();
+    headers
+        .iter()
+        .filter(|header| {
+            let timestamp = header.timestamp.as_deref().unwrap();
+            let timestamp = u64::from_str_radix(timestamp.trim_start_matches("0x"), 16).unwrap();
+            seen_hours.insert(timestamp / 3600)
+        })
+        .map(|header| BlockFeeRecord::from_header(header).unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_build_and_extract_fees_offline() {
+    std::env::set_var("RISC0_DEV_MODE", "1");
+    // Batch databases are created from scratch, away from the real db-instances
+    let db_instances = tempfile::tempdir().unwrap();
+    std::env::set_var("DB_INSTANCES_DIR", db_instances.path());
+
+    let store = FixtureHeaderStore::load(&fixtures_path()).unwrap();
+    let (first_block, last_block) = store.block_range().unwrap();
+    // The block before the range is needed as the batch link
+    let start_block = first_block + 1;
+
+    let provider = StarknetProvider::new(UNUSED_RPC_URL).unwrap();
+    let account =
+        StarknetAccount::new(provider.provider(), "0x1", "0x1", chain_id::SEPOLIA).unwrap();
+    let mut batch_processor = BatchProcessor::new(
+        BATCH_SIZE,
+        ProofGenerator::new(MMR_APPEND_ELF, MMR_APPEND_ID).unwrap(),
+        true,
+        MMRStateManager::new(account, "0x0"),
+        store.clone().into(),
+    )
+    .unwrap();
+    batch_processor.disable_ipfs_upload();
+
+    let mut current_end = last_block;
+    while current_end >= start_block {
+        let range = batch_processor
+            .calculate_batch_range(current_end, start_block)
+            .unwrap();
+        let result = batch_processor
+            .process_batch(store.chain_id(), range.start, range.end)
+            .await
+            .unwrap()
+            .expect("batch should not be complete yet");
+        assert_eq!(result.end_block(), range.end);
+
+        current_end = range.start.saturating_sub(1);
+    }
+
+    let stages = Arc::new(Mutex::new(Vec::new()));
+    let recorded = stages.clone();
+    let validator = ValidatorBuilder::new(
+        UNUSED_RPC_URL,
+        "0x0",
+        store.chain_id(),
+        BATCH_SIZE,
+        false,
+        store.clone().into(),
+    )
+    .await
+    .unwrap()
+    .with_local_batches_only()
+    .with_stages(Arc::new(move |stage| recorded.lock().unwrap().push(stage)));
+
+    let proofs = validator
+        .validate_blocks_and_extract_fees(start_block, last_block)
+        .await
+        .unwrap();
+
+    // Local batches are neither downloaded nor checked against the store
+    let stages = stages.lock().unwrap().clone();
+    let total = proofs.len();
+    let mut expected_stages = vec![ProvingStage::FetchingHeaders];
+    expected_stages.extend((1..=total).map(|batch| ProvingStage::ProvingBatch { batch, total }));
+    assert_eq!(stages, expected_stages);
+
+    let mut fees = Vec::new();
+    for proof in proofs {
+        proof
+            .receipt()
+            .verify(VALIDATE_BLOCKS_AND_EXTRACT_FEES_ID)
+            .unwrap();
+        let journal: FeeProofJournal = proof.journal().decode().unwrap();
+        assert_eq!(journal.version, FEE_PROOF_JOURNAL_VERSION);
+        assert!(journal.ok);
+        fees.extend(journal.fees);
+    }
+
+    let headers = store
+        .get_block_headers_by_block_range(start_block, last_block)
+        .unwrap();
+    let expected = expected_hourly_fees(&headers);
+
+    // Batch proofs are returned in batch order
+    assert_eq!(fees, expected);
+}
@@ -1,28 +1,291 @@
 use starknet::macros::selector;
 use starknet::{
-    accounts::{Account, ExecutionEncoding, SingleOwnerAccount},
-    core::{chain_id, codec::Encode, types::ByteArray},
-    providers::{jsonrpc::HttpTransport, JsonRpcClient},
+    accounts::{Account, AccountError, ConnectedAccount, ExecutionEncoding, SingleOwnerAccount},
+    core::{
+        codec::Encode,
+        types::{
+            ByteArray, Call, ExecutionResult, FeeEstimate, StarknetError, TransactionFinalityStatus,
+        },
+        utils::cairo_short_string_to_felt,
+    },
+    providers::{jsonrpc::HttpTransport, JsonRpcClient, Provider, ProviderError},
     signers::{LocalWallet, SigningKey},
 };
 use starknet_crypto::Felt;
-use std::{sync::Arc, time::Duration};
+use std::str::FromStr;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::Mutex;
 use tracing::{debug, info, instrument, warn};
 
-use common::felt;
+use common::{felt, get_env_var, get_var, UtilsError};
 
 use crate::StarknetHandlerError;
 
+/// Parses a Starknet chain id given either as a short string, e.g. `SN_MAIN`,
+/// `SN_SEPOLIA` or `KATANA`, or as a `0x`-prefixed felt.
+pub fn parse_chain_id(chain_id: &str) -> Result<Felt, StarknetHandlerError> {
+    let chain_id = chain_id.trim();
+    if chain_id.starts_with("0x") {
+        return Ok(felt(chain_id)?);
+    }
+    cairo_short_string_to_felt(chain_id)
+        .map_err(|e| StarknetHandlerError::ParseChainId(format!("{}: {}", chain_id, e)))
+}
+
+/// Starknet chain id configured in `STARKNET_CHAIN_ID`, if any. Unrelated to
+/// the Ethereum `ETHEREUM_CHAIN_ID` headers are validated against.
+pub fn starknet_chain_id_from_env() -> Result<Option<Felt>, StarknetHandlerError> {
+    get_env_var("STARKNET_CHAIN_ID")
+        .ok()
+        .map(|chain_id| parse_chain_id(&chain_id))
+        .transpose()
+}
+
+/// How transactions are priced, batched, and how long to wait for their
+/// acceptance.
+///
+/// Gas amounts and prices are estimated before every submission and scaled
+/// by the multipliers. The `max_*` bounds cap what the account may commit
+/// to: a transaction whose scaled estimate exceeds one is not submitted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransactionSettings {
+    pub gas_multiplier: f64,
+    pub gas_price_multiplier: f64,
+    pub max_l1_gas: Option<u64>,
+    pub max_l1_gas_price: Option<u128>,
+    pub max_l2_gas: Option<u64>,
+    pub max_l2_gas_price: Option<u128>,
+    pub max_l1_data_gas: Option<u64>,
+    pub max_l1_data_gas_price: Option<u128>,
+    /// Most calls bundled in one multicall, bounding the steps it executes
+    pub max_calls_per_transaction: usize,
+    /// Most felts of `__execute__` calldata in one multicall
+    pub max_calldata_len: usize,
+    /// How long to wait for a submitted transaction to be `ACCEPTED_ON_L2`
+    pub acceptance_timeout: Duration,
+    pub poll_interval: Duration,
+}
+
+impl Default for TransactionSettings {
+    fn default() -> Self {
+        Self {
+            gas_multiplier: 1.5,
+            gas_price_multiplier: 1.5,
+            max_l1_gas: None,
+            max_l1_gas_price: None,
+            max_l2_gas: None,
+            max_l2_gas_price: None,
+            max_l1_data_gas: None,
+            max_l1_data_gas_price: None,
+            max_calls_per_transaction: 4,
+            max_calldata_len: 4000,
+            acceptance_timeout: Duration::from_secs(300),
+            poll_interval: Duration::from_secs(2),
+        }
+    }
+}
+
+impl TransactionSettings {
+    /// Reads the settings from `STARKNET_GAS_MULTIPLIER`,
+    /// `STARKNET_GAS_PRICE_MULTIPLIER`, `STARKNET_MAX_L1_GAS`,
+    /// `STARKNET_MAX_L1_GAS_PRICE`, `STARKNET_MAX_L2_GAS`,
+    /// `STARKNET_MAX_L2_GAS_PRICE`, `STARKNET_MAX_L1_DATA_GAS`,
+    /// `STARKNET_MAX_L1_DATA_GAS_PRICE`, `STARKNET_MAX_CALLS_PER_TX`,
+    /// `STARKNET_MAX_CALLDATA_LEN` and `STARKNET_TX_TIMEOUT_SECS`, keeping the
+    /// defaults of unset variables. Multipliers must be finite and at least 1.
+    pub fn from_env() -> Result<Self, StarknetHandlerError> {
+        let defaults = Self::default();
+        Ok(Self {
+            gas_multiplier: multiplier_var("STARKNET_GAS_MULTIPLIER", defaults.gas_multiplier)?,
+            gas_price_multiplier: multiplier_var(
+                "STARKNET_GAS_PRICE_MULTIPLIER",
+                defaults.gas_price_multiplier,
+            )?,
+            max_l1_gas: optional_var("STARKNET_MAX_L1_GAS")?,
+            max_l1_gas_price: optional_var("STARKNET_MAX_L1_GAS_PRICE")?,
+            max_l2_gas: optional_var("STARKNET_MAX_L2_GAS")?,
+            max_l2_gas_price: optional_var("STARKNET_MAX_L2_GAS_PRICE")?,
+            max_l1_data_gas: optional_var("STARKNET_MAX_L1_DATA_GAS")?,
+            max_l1_data_gas_price: optional_var("STARKNET_MAX_L1_DATA_GAS_PRICE")?,
+            max_calls_per_transaction: optional_var("STARKNET_MAX_CALLS_PER_TX")?
+                .unwrap_or(defaults.max_calls_per_transaction),
+            max_calldata_len: optional_var("STARKNET_MAX_CALLDATA_LEN")?
+                .unwrap_or(defaults.max_calldata_len),
+            acceptance_timeout: optional_var("STARKNET_TX_TIMEOUT_SECS")?
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.acceptance_timeout),
+            poll_interval: defaults.poll_interval,
+        })
+    }
+
+    /// Resource bounds for an estimated transaction, scaled and checked
+    /// against the configured maximums.
+    fn gas_bounds(&self, estimate: &FeeEstimate) -> Result<GasBounds, StarknetHandlerError> {
+        let bounds = GasBounds {
+            l1_gas: scale("L1 gas", estimate.l1_gas_consumed, self.gas_multiplier)?,
+            l1_gas_price: scale(
+                "L1 gas price",
+                estimate.l1_gas_price,
+                self.gas_price_multiplier,
+            )?,
+            l2_gas: scale("L2 gas", estimate.l2_gas_consumed, self.gas_multiplier)?,
+            l2_gas_price: scale(
+                "L2 gas price",
+                estimate.l2_gas_price,
+                self.gas_price_multiplier,
+            )?,
+            l1_data_gas: scale(
+                "L1 data gas",
+                estimate.l1_data_gas_consumed,
+                self.gas_multiplier,
+            )?,
+            l1_data_gas_price: scale(
+                "L1 data gas price",
+                estimate.l1_data_gas_price,
+                self.gas_price_multiplier,
+            )?,
+        };
+
+        check_bound("L1 gas", bounds.l1_gas, self.max_l1_gas)?;
+        check_bound("L1 gas price", bounds.l1_gas_price, self.max_l1_gas_price)?;
+        check_bound("L2 gas", bounds.l2_gas, self.max_l2_gas)?;
+        check_bound("L2 gas price", bounds.l2_gas_price, self.max_l2_gas_price)?;
+        check_bound("L1 data gas", bounds.l1_data_gas, self.max_l1_data_gas)?;
+        check_bound(
+            "L1 data gas price",
+            bounds.l1_data_gas_price,
+            self.max_l1_data_gas_price,
+        )?;
+        Ok(bounds)
+    }
+
+    /// Splits `calls` into multicalls within the call count and calldata
+    /// limits, keeping their order. A call over the calldata limit on its own
+    /// is still submitted alone.
+    fn multicalls(&self, calls: Vec<Call>) -> Vec<Vec<Call>> {
+        let max_calls = self.max_calls_per_transaction.max(1);
+        let mut multicalls: Vec<Vec<Call>> = Vec::new();
+        let mut calldata_len = 0;
+
+        for call in calls {
+            let len = execute_calldata_len(&call);
+            match multicalls.last_mut() {
+                Some(multicall)
+                    if multicall.len() < max_calls
+                        && calldata_len + len <= self.max_calldata_len =>
+                {
+                    calldata_len += len;
+                    multicall.push(call);
+                }
+                _ => {
+                    // The leading felt holds the number of calls
+                    calldata_len = 1 + len;
+                    multicalls.push(vec![call]);
+                }
+            }
+        }
+        multicalls
+    }
+}
+
+/// Felts a call adds to the `__execute__` calldata: its address, selector,
+/// calldata length and calldata.
+fn execute_calldata_len(call: &Call) -> usize {
+    3 + call.calldata.len()
+}
+
+/// Resource bounds a transaction is signed with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct GasBounds {
+    l1_gas: u64,
+    l1_gas_price: u128,
+    l2_gas: u64,
+    l2_gas_price: u128,
+    l1_data_gas: u64,
+    l1_data_gas_price: u128,
+}
+
+/// Scales an estimate by a multiplier, failing when the result does not fit
+/// the resource bound instead of signing with the unscaled estimate.
+fn scale<T>(name: &str, value: T, multiplier: f64) -> Result<T, StarknetHandlerError>
+where
+    T: TryFrom<u128> + Into<u128> + Copy,
+{
+    let value = value.into();
+    let scaled = (value as f64 * multiplier).ceil();
+    let overflow = || {
+        StarknetHandlerError::FeeBoundExceeded(format!(
+            "{} of {} scaled by {} overflows",
+            name, value, multiplier
+        ))
+    };
+    // `as` saturates, so products past `u128::MAX` are caught before the cast
+    if scaled >= u128::MAX as f64 {
+        return Err(overflow());
+    }
+    T::try_from(scaled as u128).map_err(|_| overflow())
+}
+
+fn check_bound<T>(name: &str, value: T, max: Option<T>) -> Result<(), StarknetHandlerError>
+where
+    T: PartialOrd + std::fmt::Display,
+{
+    match max {
+        Some(max) if value > max => Err(StarknetHandlerError::FeeBoundExceeded(format!(
+            "{} of {} exceeds the maximum of {}",
+            name, value, max
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Reads a gas multiplier, rejecting values that are not finite or that would
+/// sign transactions with less than the estimate.
+fn multiplier_var(name: &str, default: f64) -> Result<f64, UtilsError> {
+    check_multiplier(name, optional_var(name)?.unwrap_or(default))
+}
+
+fn check_multiplier(name: &str, multiplier: f64) -> Result<f64, UtilsError> {
+    if multiplier.is_finite() && multiplier >= 1.0 {
+        Ok(multiplier)
+    } else {
+        Err(UtilsError::ParseError(format!(
+            "{}: {} is not a finite multiplier of at least 1",
+            name, multiplier
+        )))
+    }
+}
+
+pub(crate) fn optional_var<T: FromStr>(name: &str) -> Result<Option<T>, UtilsError>
+where
+    T::Err: std::fmt::Display,
+{
+    match get_env_var(name) {
+        Ok(_) => get_var(name).map(Some),
+        Err(_) => Ok(None),
+    }
+}
+
 pub struct StarknetAccount {
     account: SingleOwnerAccount<Arc<JsonRpcClient<HttpTransport>>, LocalWallet>,
+    settings: TransactionSettings,
+    /// Nonce of the next transaction, fetched from the chain when unknown.
+    /// Held for the whole submission so that concurrent calls get
+    /// consecutive nonces.
+    nonce: Mutex<Option<Felt>>,
 }
 
 impl StarknetAccount {
+    /// Creates an account signing transactions for the Starknet chain `chain_id`.
     #[instrument(skip(provider, account_private_key), fields(address = %account_address), level = "debug")]
     pub fn new(
         provider: Arc<JsonRpcClient<HttpTransport>>,
         account_private_key: &str,
         account_address: &str,
+        chain_id: Felt,
     ) -> Result<Self, StarknetHandlerError> {
         debug!("Creating new Starknet account");
 
@@ -33,21 +296,52 @@ impl StarknetAccount {
         let address = felt(account_address)?;
 
         debug!(
-            chain_id = ?chain_id::SEPOLIA,
+            chain_id = %chain_id,
             encoding = ?ExecutionEncoding::New,
             "Initializing SingleOwnerAccount"
         );
 
-        let account = SingleOwnerAccount::new(
-            provider,
-            signer,
-            address,
-            chain_id::SEPOLIA,
-            ExecutionEncoding::New,
-        );
+        let account =
+            SingleOwnerAccount::new(provider, signer, address, chain_id, ExecutionEncoding::New);
 
         debug!("Starknet account successfully created");
-        Ok(Self { account })
+        Ok(Self {
+            account,
+            settings: TransactionSettings::default(),
+            nonce: Mutex::new(None),
+        })
+    }
+
+    pub fn with_transaction_settings(mut self, settings: TransactionSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Creates an account for `chain_id` when given, otherwise for the chain
+    /// the provider is connected to, with its [`TransactionSettings`] read
+    /// from the environment.
+    pub async fn connect(
+        provider: Arc<JsonRpcClient<HttpTransport>>,
+        account_private_key: &str,
+        account_address: &str,
+        chain_id: Option<Felt>,
+    ) -> Result<Self, StarknetHandlerError> {
+        let chain_id = match chain_id {
+            Some(chain_id) => chain_id,
+            None => {
+                let chain_id = provider.chain_id().await?;
+                info!(chain_id = %chain_id, "Using the chain id of the Starknet provider");
+                chain_id
+            }
+        };
+        Ok(
+            Self::new(provider, account_private_key, account_address, chain_id)?
+                .with_transaction_settings(TransactionSettings::from_env()?),
+        )
+    }
+
+    pub fn chain_id(&self) -> Felt {
+        self.account.chain_id()
     }
 
     #[instrument(skip(self), level = "debug")]
@@ -57,66 +351,419 @@ impl StarknetAccount {
         proof: Vec<Felt>,
         ipfs_hash: String,
     ) -> Result<Felt, StarknetHandlerError> {
-        const MAX_RETRIES: u32 = 3;
-        const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+        debug!(
+            verifier_address = %verifier_address,
+            proof_length = proof.len(),
+            "Verifying MMR proof"
+        );
+        let call = mmr_proof_call(verifier_address, proof, &ipfs_hash)?;
+        let tx_hash = self.execute_with_retry(vec![call], "MMR proof").await?;
+        info!(tx_hash = ?tx_hash, "MMR proof onchain verification successful.");
 
-        let mut calldata = vec![];
-        let mut hash_calldata = vec![];
+        Ok(tx_hash)
+    }
 
+    /// Submits several MMR proofs, each with the IPFS hash of its batch,
+    /// bundled into as few multicalls as the [`TransactionSettings`] limits
+    /// allow. The proofs are verified in the given order; the hashes of the
+    /// multicalls are returned in submission order.
+    ///
+    /// When a multicall fails after earlier ones landed, the error is
+    /// [`StarknetHandlerError::ProofsPartiallyVerified`], holding how many of
+    /// the leading proofs were verified and in which transactions.
+    #[instrument(skip(self, proofs), level = "debug")]
+    pub async fn verify_mmr_proofs(
+        &self,
+        verifier_address: &str,
+        proofs: Vec<(Vec<Felt>, String)>,
+    ) -> Result<Vec<Felt>, StarknetHandlerError> {
+        let proof_count = proofs.len();
+        let calls = proofs
+            .into_iter()
+            .map(|(proof, ipfs_hash)| mmr_proof_call(verifier_address, proof, &ipfs_hash))
+            .collect::<Result<Vec<_>, _>>()?;
+        let multicalls = self.settings.multicalls(calls);
+
+        debug!(
+            verifier_address = %verifier_address,
+            proofs = proof_count,
+            transactions = multicalls.len(),
+            "Verifying MMR proofs"
+        );
+        let mut tx_hashes = Vec::with_capacity(multicalls.len());
+        let mut verified = 0;
+        for multicall in multicalls {
+            let calls = multicall.len();
+            match self.execute_with_retry(multicall, "MMR proofs").await {
+                Ok(tx_hash) => {
+                    info!(tx_hash = ?tx_hash, calls, "MMR proofs onchain verification successful.");
+                    tx_hashes.push(tx_hash);
+                    verified += calls;
+                }
+                Err(e) if tx_hashes.is_empty() => return Err(e),
+                Err(e) => {
+                    return Err(StarknetHandlerError::ProofsPartiallyVerified {
+                        verified,
+                        tx_hashes,
+                        source: Box::new(e),
+                    })
+                }
+            }
+        }
+
+        Ok(tx_hashes)
+    }
+
+    /// Whether [`Self::verify_mmr_proofs`] would submit `proofs` in a single
+    /// multicall, within the [`TransactionSettings`] limits.
+    pub fn fits_in_multicall(
+        &self,
+        verifier_address: &str,
+        proofs: &[(Vec<Felt>, String)],
+    ) -> Result<bool, StarknetHandlerError> {
+        let calls = proofs
+            .iter()
+            .map(|(proof, ipfs_hash)| mmr_proof_call(verifier_address, proof.clone(), ipfs_hash))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(self.settings.multicalls(calls).len() <= 1)
+    }
+
+    /// Submits a Groth16 proof chaining several MMR append proofs, with the IPFS hash of
+    /// every batch it updates, in batch order.
+    #[instrument(skip(self, proof), level = "debug")]
+    pub async fn verify_mmr_chain_proof(
+        &self,
+        verifier_address: &str,
+        proof: Vec<Felt>,
+        ipfs_hashes: Vec<String>,
+    ) -> Result<Felt, StarknetHandlerError> {
+        let mut calldata = vec![];
         proof.encode(&mut calldata)?;
+        ipfs_hashes
+            .iter()
+            .map(|ipfs_hash| ByteArray::from(ipfs_hash.as_str()))
+            .collect::<Vec<_>>()
+            .encode(&mut calldata)?;
+
+        let call = Call {
+            selector: selector!("verify_mmr_chain_proof"),
+            calldata,
+            to: felt(verifier_address)?,
+        };
+
+        debug!(
+            verifier_address = %verifier_address,
+            proof_length = proof.len(),
+            batches = ipfs_hashes.len(),
+            "Verifying MMR chain proof"
+        );
+        let tx_hash = self
+            .execute_with_retry(vec![call], "MMR chain proof")
+            .await?;
+        info!(tx_hash = ?tx_hash, "MMR chain proof onchain verification successful.");
 
-        ByteArray::from(ipfs_hash.as_str()).encode(&mut hash_calldata)?;
-        calldata.extend(hash_calldata);
+        Ok(tx_hash)
+    }
 
-        let selector = selector!("verify_mmr_proof");
-        let call = starknet::core::types::Call {
-            selector,
+    /// Submits a Groth16 fee proof to the Fossil verifier, which checks it against the
+    /// MMR root of its batch and stores the proven base fees.
+    #[instrument(skip(self, proof), level = "debug")]
+    pub async fn verify_fee_proof(
+        &self,
+        verifier_address: &str,
+        proof: Vec<Felt>,
+    ) -> Result<Felt, StarknetHandlerError> {
+        let mut calldata = vec![];
+        proof.encode(&mut calldata)?;
+
+        let call = Call {
+            selector: selector!("verify_fee_proof"),
             calldata,
             to: felt(verifier_address)?,
         };
 
+        debug!(
+            verifier_address = %verifier_address,
+            proof_length = proof.len(),
+            "Verifying fee proof"
+        );
+        let tx_hash = self.execute_with_retry(vec![call], "Fee proof").await?;
+        info!(tx_hash = ?tx_hash, "Fee proof onchain verification successful.");
+
+        Ok(tx_hash)
+    }
+
+    /// Submits `calls` in one transaction and waits until it is accepted on L2.
+    ///
+    /// Every attempt reuses the same nonce, so at most one of them can be
+    /// included; an attempt that reached the node before failing is waited
+    /// for rather than submitted again. A nonce rejected as invalid is only
+    /// resynchronized with the chain once none of the attempts is known to
+    /// the node, since a pending attempt may be the one using it.
+    async fn execute_with_retry(
+        &self,
+        calls: Vec<Call>,
+        description: &str,
+    ) -> Result<Felt, StarknetHandlerError> {
+        const MAX_RETRIES: u32 = 3;
+        const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+        let mut next_nonce = self.nonce.lock().await;
+        let mut nonce = match *next_nonce {
+            Some(nonce) => nonce,
+            None => self.account.get_nonce().await?,
+        };
+        let mut bounds = None;
+        let mut submitted = Vec::new();
+        let mut pending = None;
+
         let mut attempt = 0;
         loop {
-            debug!(
-                verifier_address = %verifier_address,
-                proof_length = proof.len(),
-                attempt = attempt + 1,
-                "Verifying MMR proof"
+            let result = match pending.take() {
+                Some(tx_hash) => {
+                    debug!(
+                        attempt = attempt + 1,
+                        tx_hash = %tx_hash,
+                        "Waiting for pending {}",
+                        description
+                    );
+                    self.wait_for_acceptance(tx_hash).await.map(|()| tx_hash)
+                }
+                None => {
+                    debug!(attempt = attempt + 1, nonce = %nonce, "Submitting {}", description);
+                    match self.submit(&calls, nonce, &mut bounds).await {
+                        Ok(tx_hash) => {
+                            submitted.push(tx_hash);
+                            self.wait_for_acceptance(tx_hash).await.map(|()| tx_hash)
+                        }
+                        Err(e) => Err(e),
+                    }
+                }
+            };
+
+            let e = match result {
+                Ok(tx_hash) => {
+                    *next_nonce = Some(nonce + Felt::ONE);
+                    return Ok(tx_hash);
+                }
+                // A reverted transaction is included and uses up its nonce
+                Err(e @ StarknetHandlerError::Reverted { .. }) => {
+                    *next_nonce = Some(nonce + Felt::ONE);
+                    return Err(e);
+                }
+                Err(e) => e,
+            };
+
+            // An earlier attempt may have been accepted in the meantime
+            if let Some(tx_hash) = self.accepted(&submitted).await {
+                *next_nonce = Some(nonce + Felt::ONE);
+                return Ok(tx_hash);
+            }
+
+            if attempt >= MAX_RETRIES {
+                warn!("Max retries reached for {} verification", description);
+                // Resynchronize with the chain on the next submission
+                *next_nonce = None;
+                return Err(e);
+            }
+
+            if is_nonce_error(&e) {
+                pending = self.known_to_node(&submitted).await;
+                match pending {
+                    Some(tx_hash) => {
+                        warn!(
+                            tx_hash = %tx_hash,
+                            "Nonce used by a pending attempt, waiting for it"
+                        );
+                    }
+                    None => {
+                        nonce = self.account.get_nonce().await?;
+                        bounds = None;
+                        warn!(nonce = %nonce, "Nonce out of sync, resynchronized with the chain");
+                    }
+                }
+            }
+
+            let backoff = INITIAL_BACKOFF * 2u32.pow(attempt);
+            warn!(
+                error = ?e,
+                retry_in = ?backoff,
+                "{} verification failed, retrying...",
+                description
             );
 
-            match self.account.execute_v3(vec![call.clone()]).send().await {
-                Ok(tx) => {
-                    info!(
-                        tx_hash = ?tx.transaction_hash,
-                        "MMR proof onchain verification successful."
-                    );
-                    return Ok(tx.transaction_hash);
+            tokio::time::sleep(backoff).await;
+            attempt += 1;
+        }
+    }
+
+    /// Signs and sends `calls` with `nonce`, estimating the gas bounds on the
+    /// first attempt and keeping them for the next ones so that an unchanged
+    /// transaction keeps its hash.
+    async fn submit(
+        &self,
+        calls: &[Call],
+        nonce: Felt,
+        bounds: &mut Option<GasBounds>,
+    ) -> Result<Felt, StarknetHandlerError> {
+        let gas = match *bounds {
+            Some(gas) => gas,
+            None => {
+                let estimate = self
+                    .account
+                    .execute_v3(calls.to_vec())
+                    .nonce(nonce)
+                    .estimate_fee()
+                    .await?;
+                let gas = self.settings.gas_bounds(&estimate)?;
+                debug!(?estimate, ?gas, "Estimated transaction fee");
+                *bounds = Some(gas);
+                gas
+            }
+        };
+
+        let execution = self
+            .account
+            .execute_v3(calls.to_vec())
+            .nonce(nonce)
+            .l1_gas(gas.l1_gas)
+            .l1_gas_price(gas.l1_gas_price)
+            .l2_gas(gas.l2_gas)
+            .l2_gas_price(gas.l2_gas_price)
+            .l1_data_gas(gas.l1_data_gas)
+            .l1_data_gas_price(gas.l1_data_gas_price);
+        let prepared = execution
+            .prepared()
+            .map_err(|e| StarknetHandlerError::TransactionError(format!("{:?}", e)))?;
+        let tx_hash = prepared.transaction_hash(false);
+
+        match prepared.send().await {
+            Ok(result) => Ok(result.transaction_hash),
+            Err(e) => {
+                // The node may have received the transaction before failing to answer
+                if self
+                    .account
+                    .provider()
+                    .get_transaction_status(tx_hash)
+                    .await
+                    .is_ok()
+                {
+                    warn!(tx_hash = %tx_hash, error = ?e, "Transaction received despite the error");
+                    return Ok(tx_hash);
                 }
-                Err(e) => {
-                    if attempt >= MAX_RETRIES {
-                        warn!("Max retries reached for MMR proof verification");
-                        return Err(e.into());
+                Err(e.into())
+            }
+        }
+    }
+
+    /// Waits until the transaction is accepted on L2, failing with its revert
+    /// reason if it reverted.
+    pub async fn wait_for_acceptance(&self, tx_hash: Felt) -> Result<(), StarknetHandlerError> {
+        let deadline = Instant::now() + self.settings.acceptance_timeout;
+        loop {
+            match self
+                .account
+                .provider()
+                .get_transaction_receipt(tx_hash)
+                .await
+            {
+                Ok(receipt) => {
+                    if let ExecutionResult::Reverted { reason } = receipt.receipt.execution_result()
+                    {
+                        return Err(StarknetHandlerError::Reverted {
+                            tx_hash,
+                            reason: reason.clone(),
+                        });
+                    }
+                    if matches!(
+                        receipt.receipt.finality_status(),
+                        TransactionFinalityStatus::AcceptedOnL2
+                            | TransactionFinalityStatus::AcceptedOnL1
+                    ) {
+                        debug!(tx_hash = %tx_hash, "Transaction accepted");
+                        return Ok(());
                     }
+                }
+                // Not included in a block yet
+                Err(ProviderError::StarknetError(StarknetError::TransactionHashNotFound)) => {}
+                Err(e) => return Err(e.into()),
+            }
 
-                    let backoff = INITIAL_BACKOFF * 2u32.pow(attempt);
-                    warn!(
-                        error = ?e,
-                        retry_in = ?backoff,
-                        "MMR proof verification failed, retrying..."
-                    );
+            if Instant::now() >= deadline {
+                return Err(StarknetHandlerError::AcceptanceTimeout(tx_hash));
+            }
+            tokio::time::sleep(self.settings.poll_interval).await;
+        }
+    }
 
-                    tokio::time::sleep(backoff).await;
-                    attempt += 1;
+    /// First of the transactions the node knows about, pending or included.
+    async fn known_to_node(&self, tx_hashes: &[Felt]) -> Option<Felt> {
+        for tx_hash in tx_hashes {
+            if self
+                .account
+                .provider()
+                .get_transaction_status(*tx_hash)
+                .await
+                .is_ok()
+            {
+                return Some(*tx_hash);
+            }
+        }
+        None
+    }
+
+    /// First of the transactions that was accepted without reverting.
+    async fn accepted(&self, tx_hashes: &[Felt]) -> Option<Felt> {
+        for tx_hash in tx_hashes {
+            if let Ok(receipt) = self
+                .account
+                .provider()
+                .get_transaction_receipt(*tx_hash)
+                .await
+            {
+                if matches!(
+                    receipt.receipt.execution_result(),
+                    ExecutionResult::Succeeded
+                ) {
+                    return Some(*tx_hash);
                 }
             }
         }
+        None
     }
 }
 
+fn mmr_proof_call(
+    verifier_address: &str,
+    proof: Vec<Felt>,
+    ipfs_hash: &str,
+) -> Result<Call, StarknetHandlerError> {
+    let mut calldata = vec![];
+    proof.encode(&mut calldata)?;
+    ByteArray::from(ipfs_hash).encode(&mut calldata)?;
+
+    Ok(Call {
+        selector: selector!("verify_mmr_proof"),
+        calldata,
+        to: felt(verifier_address)?,
+    })
+}
+
+fn is_nonce_error(error: &StarknetHandlerError) -> bool {
+    matches!(
+        error,
+        StarknetHandlerError::Account(AccountError::Provider(ProviderError::StarknetError(
+            StarknetError::InvalidTransactionNonce { .. }
+        ))) | StarknetHandlerError::Provider(ProviderError::StarknetError(
+            StarknetError::InvalidTransactionNonce { .. }
+        ))
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::str::FromStr;
+    use starknet::core::chain_id;
 
     // Helper function to create a test provider
     fn create_test_provider() -> Arc<JsonRpcClient<HttpTransport>> {
@@ -131,17 +778,55 @@ mod tests {
         let private_key = "0x1234567890abcdef";
         let address = "0x987654321fedcba";
 
-        let result = StarknetAccount::new(provider, private_key, address);
+        let result = StarknetAccount::new(provider, private_key, address, chain_id::SEPOLIA);
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_new_account_uses_chain_id() {
+        let account = StarknetAccount::new(
+            create_test_provider(),
+            "0x1234567890abcdef",
+            "0x987654321fedcba",
+            chain_id::MAINNET,
+        )
+        .unwrap();
+        assert_eq!(account.chain_id(), chain_id::MAINNET);
+    }
+
+    #[tokio::test]
+    async fn test_connect_with_configured_chain_id() {
+        // No request is made to the provider when the chain id is given
+        let account = StarknetAccount::connect(
+            create_test_provider(),
+            "0x1234567890abcdef",
+            "0x987654321fedcba",
+            Some(chain_id::SEPOLIA),
+        )
+        .await
+        .unwrap();
+        assert_eq!(account.chain_id(), chain_id::SEPOLIA);
+    }
+
+    #[test]
+    fn test_parse_chain_id() {
+        assert_eq!(parse_chain_id("SN_MAIN").unwrap(), chain_id::MAINNET);
+        assert_eq!(parse_chain_id("SN_SEPOLIA").unwrap(), chain_id::SEPOLIA);
+        assert_eq!(
+            parse_chain_id("0x534e5f5345504f4c4941").unwrap(),
+            chain_id::SEPOLIA
+        );
+        assert!(parse_chain_id("0xnothex").is_err());
+        assert!(parse_chain_id("A_CHAIN_ID_LONGER_THAN_THIRTY_ONE_CHARS").is_err());
+    }
+
     #[test]
     fn test_new_account_invalid_private_key() {
         let provider = create_test_provider();
         let private_key = "invalid_key";
         let address = "0x987654321fedcba";
 
-        let result = StarknetAccount::new(provider, private_key, address);
+        let result = StarknetAccount::new(provider, private_key, address, chain_id::SEPOLIA);
         assert!(result.is_err());
     }
 
@@ -151,29 +836,34 @@ mod tests {
         let private_key = "0x1234567890abcdef";
         let address = "invalid_address";
 
-        let result = StarknetAccount::new(provider, private_key, address);
+        let result = StarknetAccount::new(provider, private_key, address, chain_id::SEPOLIA);
         assert!(result.is_err());
     }
 
     #[test]
     fn test_new_account_empty_private_key() {
         let provider = create_test_provider();
-        let result = StarknetAccount::new(provider, "", "0x987654321fedcba");
+        let result = StarknetAccount::new(provider, "", "0x987654321fedcba", chain_id::SEPOLIA);
         assert!(result.is_err());
     }
 
     #[test]
     fn test_new_account_empty_address() {
         let provider = create_test_provider();
-        let result = StarknetAccount::new(provider, "0x1234567890abcdef", "");
+        let result = StarknetAccount::new(provider, "0x1234567890abcdef", "", chain_id::SEPOLIA);
         assert!(result.is_err());
     }
 
     #[tokio::test]
     async fn test_verify_mmr_proof_success() {
         let provider = create_test_provider();
-        let account =
-            StarknetAccount::new(provider, "0x1234567890abcdef", "0x987654321fedcba").unwrap();
+        let account = StarknetAccount::new(
+            provider,
+            "0x1234567890abcdef",
+            "0x987654321fedcba",
+            chain_id::SEPOLIA,
+        )
+        .unwrap();
 
         let verifier_address = "0x123456789";
         let proof = vec![Felt::from_str("0x1").unwrap()];
@@ -190,8 +880,13 @@ mod tests {
     #[tokio::test]
     async fn test_verify_mmr_proof_empty_proof() {
         let provider = create_test_provider();
-        let account =
-            StarknetAccount::new(provider, "0x1234567890abcdef", "0x987654321fedcba").unwrap();
+        let account = StarknetAccount::new(
+            provider,
+            "0x1234567890abcdef",
+            "0x987654321fedcba",
+            chain_id::SEPOLIA,
+        )
+        .unwrap();
 
         let result = account
             .verify_mmr_proof("0x123456789", vec![], "QmTest123".to_string())
@@ -202,8 +897,13 @@ mod tests {
     #[tokio::test]
     async fn test_verify_mmr_proof_empty_ipfs_hash() {
         let provider = create_test_provider();
-        let account =
-            StarknetAccount::new(provider, "0x1234567890abcdef", "0x987654321fedcba").unwrap();
+        let account = StarknetAccount::new(
+            provider,
+            "0x1234567890abcdef",
+            "0x987654321fedcba",
+            chain_id::SEPOLIA,
+        )
+        .unwrap();
 
         let proof = vec![Felt::from_str("0x1").unwrap()];
         let result = account
@@ -211,4 +911,228 @@ mod tests {
             .await;
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn test_verify_fee_proof_fails_without_node() {
+        let provider = create_test_provider();
+        let account = StarknetAccount::new(
+            provider,
+            "0x1234567890abcdef",
+            "0x987654321fedcba",
+            chain_id::SEPOLIA,
+        )
+        .unwrap();
+
+        let proof = vec![Felt::from_str("0x1").unwrap()];
+        let result = account.verify_fee_proof("0x123456789", proof).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_verify_fee_proof_invalid_verifier_address() {
+        let provider = create_test_provider();
+        let account = StarknetAccount::new(
+            provider,
+            "0x1234567890abcdef",
+            "0x987654321fedcba",
+            chain_id::SEPOLIA,
+        )
+        .unwrap();
+
+        let result = account.verify_fee_proof("invalid_address", vec![]).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_verify_mmr_chain_proof_invalid_verifier_address() {
+        let provider = create_test_provider();
+        let account = StarknetAccount::new(
+            provider,
+            "0x1234567890abcdef",
+            "0x987654321fedcba",
+            chain_id::SEPOLIA,
+        )
+        .unwrap();
+
+        let result = account
+            .verify_mmr_chain_proof("invalid_address", vec![], vec!["Qm".to_string()])
+            .await;
+        assert!(result.is_err());
+    }
+
+    fn fee_estimate() -> FeeEstimate {
+        FeeEstimate {
+            l1_gas_consumed: 0,
+            l1_gas_price: 1_000,
+            l2_gas_consumed: 1_000_001,
+            l2_gas_price: 10,
+            l1_data_gas_consumed: 128,
+            l1_data_gas_price: 3,
+            overall_fee: 10_010_394,
+        }
+    }
+
+    #[test]
+    fn test_gas_bounds_scale_estimate() {
+        let bounds = TransactionSettings::default()
+            .gas_bounds(&fee_estimate())
+            .unwrap();
+        assert_eq!(
+            bounds,
+            GasBounds {
+                l1_gas: 0,
+                l1_gas_price: 1_500,
+                l2_gas: 1_500_002,
+                l2_gas_price: 15,
+                l1_data_gas: 192,
+                l1_data_gas_price: 5,
+            }
+        );
+    }
+
+    #[test]
+    fn test_gas_bounds_reject_estimate_over_maximum() {
+        let settings = TransactionSettings {
+            max_l2_gas: Some(1_500_001),
+            ..Default::default()
+        };
+        assert!(matches!(
+            settings.gas_bounds(&fee_estimate()),
+            Err(StarknetHandlerError::FeeBoundExceeded(_))
+        ));
+
+        // 128 L1 data gas at a price of 3, scaled by 1.5
+        for settings in [
+            TransactionSettings {
+                max_l1_data_gas: Some(191),
+                ..Default::default()
+            },
+            TransactionSettings {
+                max_l1_data_gas_price: Some(4),
+                ..Default::default()
+            },
+        ] {
+            assert!(matches!(
+                settings.gas_bounds(&fee_estimate()),
+                Err(StarknetHandlerError::FeeBoundExceeded(_))
+            ));
+        }
+
+        let settings = TransactionSettings {
+            max_l1_gas_price: Some(1_500),
+            max_l2_gas: Some(1_500_002),
+            max_l1_data_gas: Some(192),
+            max_l1_data_gas_price: Some(5),
+            ..Default::default()
+        };
+        assert!(settings.gas_bounds(&fee_estimate()).is_ok());
+    }
+
+    #[test]
+    fn test_check_multiplier() {
+        assert_eq!(check_multiplier("M", 1.0).unwrap(), 1.0);
+        assert_eq!(check_multiplier("M", 2.5).unwrap(), 2.5);
+        for multiplier in [0.99, 0.0, -1.0, f64::NAN, f64::INFINITY] {
+            assert!(check_multiplier("M", multiplier).is_err(), "{}", multiplier);
+        }
+    }
+
+    #[test]
+    fn test_gas_bounds_reject_overflowing_scale() {
+        let estimate = FeeEstimate {
+            l2_gas_consumed: u64::MAX,
+            ..fee_estimate()
+        };
+        assert!(matches!(
+            TransactionSettings::default().gas_bounds(&estimate),
+            Err(StarknetHandlerError::FeeBoundExceeded(_))
+        ));
+
+        let estimate = FeeEstimate {
+            l1_gas_price: u128::MAX,
+            ..fee_estimate()
+        };
+        assert!(matches!(
+            TransactionSettings::default().gas_bounds(&estimate),
+            Err(StarknetHandlerError::FeeBoundExceeded(_))
+        ));
+    }
+
+    fn call(calldata_len: usize) -> Call {
+        Call {
+            to: Felt::ONE,
+            selector: Felt::TWO,
+            calldata: vec![Felt::ZERO; calldata_len],
+        }
+    }
+
+    #[test]
+    fn test_multicalls_respect_call_limit() {
+        let settings = TransactionSettings {
+            max_calls_per_transaction: 2,
+            ..Default::default()
+        };
+        let lens: Vec<Vec<usize>> = settings
+            .multicalls((1..=5).map(call).collect())
+            .iter()
+            .map(|multicall| multicall.iter().map(|call| call.calldata.len()).collect())
+            .collect();
+        assert_eq!(lens, vec![vec![1, 2], vec![3, 4], vec![5]]);
+    }
+
+    #[test]
+    fn test_multicalls_respect_calldata_limit() {
+        // 1 + (3 + 40) + (3 + 50) = 97 felts fit, a third call does not
+        let settings = TransactionSettings {
+            max_calldata_len: 100,
+            ..Default::default()
+        };
+        let multicalls = settings.multicalls(vec![call(40), call(50), call(10), call(200)]);
+        let sizes: Vec<usize> = multicalls.iter().map(Vec::len).collect();
+        assert_eq!(sizes, vec![2, 1, 1]);
+        assert_eq!(multicalls[2][0].calldata.len(), 200);
+    }
+
+    #[test]
+    fn test_fits_in_multicall() {
+        let account = StarknetAccount::new(
+            create_test_provider(),
+            "0x1234567890abcdef",
+            "0x987654321fedcba",
+            chain_id::SEPOLIA,
+        )
+        .unwrap()
+        .with_transaction_settings(TransactionSettings {
+            max_calls_per_transaction: 2,
+            ..Default::default()
+        });
+        let proofs: Vec<(Vec<Felt>, String)> = (0..3)
+            .map(|i| (vec![Felt::from(i)], format!("QmTest{}", i)))
+            .collect();
+
+        assert!(account.fits_in_multicall("0x123456789", &[]).unwrap());
+        assert!(account
+            .fits_in_multicall("0x123456789", &proofs[..2])
+            .unwrap());
+        assert!(!account.fits_in_multicall("0x123456789", &proofs).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_verify_mmr_proofs_invalid_verifier_address() {
+        let account = StarknetAccount::new(
+            create_test_provider(),
+            "0x1234567890abcdef",
+            "0x987654321fedcba",
+            chain_id::SEPOLIA,
+        )
+        .unwrap();
+
+        let result = account
+            .verify_mmr_proofs(
+                "invalid_address",
+                vec![(vec![Felt::ONE], "QmTest123".to_string())],
+            )
+            .await;
+        assert!(result.is_err());
+    }
 }
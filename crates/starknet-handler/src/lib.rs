@@ -8,6 +8,7 @@ use starknet::accounts::AccountError;
 use starknet::core::codec::{Decode, Encode};
 use starknet::core::types::{ByteArray, U256};
 use starknet::signers::local_wallet::SignError as LocalWalletSignError;
+use starknet_crypto::Felt;
 use thiserror::Error;
 use tracing::{debug, instrument};
 
@@ -33,6 +34,22 @@ pub enum StarknetHandlerError {
     Provider(#[from] starknet::providers::ProviderError),
     #[error("Felt conversion error: {0}")]
     FeltConversion(#[from] starknet::core::types::FromStrError),
+    #[error("Invalid chain id: {0}")]
+    ParseChainId(String),
+    #[error("Transaction {tx_hash:#x} reverted: {reason}")]
+    Reverted { tx_hash: Felt, reason: String },
+    #[error("Transaction {0:#x} not accepted on L2 in time")]
+    AcceptanceTimeout(Felt),
+    #[error("Fee bound exceeded: {0}")]
+    FeeBoundExceeded(String),
+    #[error("Verified {verified} MMR proofs before failing: {source}")]
+    ProofsPartiallyVerified {
+        verified: usize,
+        tx_hashes: Vec<Felt>,
+        source: Box<StarknetHandlerError>,
+    },
+    #[error("IPFS hash of batch {0} is not a valid CID")]
+    InvalidIpfsHash(u64),
 }
 
 #[derive(Clone, Debug, Encode, Decode)]
@@ -46,13 +63,58 @@ pub struct MmrSnapshot {
 }
 
 impl MmrSnapshot {
+    pub fn batch_index(&self) -> u64 {
+        self.batch_index
+    }
+
+    pub fn latest_mmr_block(&self) -> u64 {
+        self.latest_mmr_block
+    }
+
+    pub fn latest_mmr_block_hash(&self) -> U256 {
+        self.latest_mmr_block_hash
+    }
+
     pub fn root_hash(&self) -> U256 {
         self.root_hash
     }
 
+    pub fn leaves_count(&self) -> u64 {
+        self.leaves_count
+    }
+
+    /// Number of nodes in an MMR with `leaves_count` leaves: every merge adds
+    /// one parent, and a leaf count with `k` set bits leaves `k` peaks unmerged.
+    pub fn elements_count(&self) -> u64 {
+        2 * self.leaves_count - self.leaves_count.count_ones() as u64
+    }
+
     pub fn ipfs_hash(&self) -> ByteArray {
         self.ipfs_hash.clone()
     }
+
+    /// CID of the batch database on IPFS, decoded from the stored `ByteArray`:
+    /// a base58 CIDv0 (`Qm...`) or a base32 CIDv1 (`b...`).
+    pub fn ipfs_cid(&self) -> Result<String, StarknetHandlerError> {
+        String::try_from(self.ipfs_hash.clone())
+            .ok()
+            .filter(|cid| is_cid(cid))
+            .ok_or(StarknetHandlerError::InvalidIpfsHash(self.batch_index))
+    }
+}
+
+fn is_cid(cid: &str) -> bool {
+    const BASE58_ALPHABET: &str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+    if cid.len() == 46 && cid.starts_with("Qm") {
+        return cid.chars().all(|c| BASE58_ALPHABET.contains(c));
+    }
+    match cid.strip_prefix('b') {
+        Some(base32) => {
+            !base32.is_empty() && base32.chars().all(|c| matches!(c, 'a'..='z' | '2'..='7'))
+        }
+        None => false,
+    }
 }
 
 #[derive(Clone, Debug, Encode, Decode)]
@@ -108,6 +170,11 @@ impl MmrState {
     }
 }
 
+/// Formats a `U256` as a 0x-prefixed, 64-digit hex string, the inverse of [`u256_from_hex`].
+pub fn u256_to_hex(value: U256) -> String {
+    format!("0x{:032x}{:032x}", value.high(), value.low())
+}
+
 #[instrument(level = "debug")]
 pub fn u256_from_hex(hex: &str) -> Result<U256, StarknetHandlerError> {
     let hex_clean = hex.strip_prefix("0x").unwrap_or(hex);
@@ -137,6 +204,24 @@ pub fn u256_from_hex(hex: &str) -> Result<U256, StarknetHandlerError> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_mmr_snapshot_elements_count() {
+        let snapshot = |leaves_count| MmrSnapshot {
+            batch_index: 0,
+            latest_mmr_block: 0,
+            latest_mmr_block_hash: U256::from(0u64),
+            root_hash: U256::from(0u64),
+            leaves_count,
+            ipfs_hash: ByteArray::from(""),
+        };
+
+        let counts: Vec<u64> = (0..=5)
+            .map(|leaves_count| snapshot(leaves_count).elements_count())
+            .collect();
+        assert_eq!(counts, vec![0, 1, 3, 4, 7, 8]);
+        assert_eq!(snapshot(1024).elements_count(), 2047);
+    }
+
     #[test]
     fn test_u256_from_hex() {
         // Test valid hex string
@@ -176,6 +261,17 @@ mod tests {
         assert_eq!(result.to_string(), "1");
     }
 
+    #[test]
+    fn test_u256_to_hex() {
+        for hex in [
+            "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+            "0x0000000000000000000000000000000000000000000000000000000000001234",
+            "0x0000000000000000000000000000000000000000000000000000000000000000",
+        ] {
+            assert_eq!(u256_to_hex(u256_from_hex(hex).unwrap()), hex);
+        }
+    }
+
     #[test]
     fn test_mmr_state() {
         let block = 100u64;
@@ -211,7 +307,7 @@ mod tests {
             )
             .unwrap(),
             leaves_count: 50,
-            ipfs_hash: ByteArray::from("0x1234"),
+            ipfs_hash: ByteArray::from("QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG"),
         };
 
         assert_eq!(
@@ -219,7 +315,37 @@ mod tests {
             u256_from_hex("0000000000000000000000000000000000000000000000000000000000009876")
                 .unwrap()
         );
-        assert_eq!(snapshot.ipfs_hash(), ByteArray::from("0x1234"));
+        assert_eq!(
+            snapshot.ipfs_cid().unwrap(),
+            "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG"
+        );
+    }
+
+    #[test]
+    fn test_mmr_snapshot_ipfs_cid() {
+        let snapshot_with = |ipfs_hash: &str| MmrSnapshot {
+            batch_index: 7,
+            latest_mmr_block: 0,
+            latest_mmr_block_hash: U256::from(0u64),
+            root_hash: U256::from(0u64),
+            leaves_count: 0,
+            ipfs_hash: ByteArray::from(ipfs_hash),
+        };
+
+        let cid_v1 = "bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi";
+        assert_eq!(snapshot_with(cid_v1).ipfs_cid().unwrap(), cid_v1);
+
+        for invalid in [
+            "",
+            "0x1234",
+            "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbd0",
+            "b",
+        ] {
+            assert!(matches!(
+                snapshot_with(invalid).ipfs_cid(),
+                Err(StarknetHandlerError::InvalidIpfsHash(7))
+            ));
+        }
     }
 
     #[test]
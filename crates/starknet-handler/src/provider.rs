@@ -1,8 +1,10 @@
+use futures_util::{stream, StreamExt, TryStreamExt};
 use starknet::providers::Provider;
+use std::collections::{BTreeMap, BTreeSet};
 use std::sync::Arc;
 use tracing::{debug, info, instrument, warn};
 
-use crate::{MmrSnapshot, StarknetHandlerError};
+use crate::{account::optional_var, MmrSnapshot, StarknetHandlerError};
 use starknet::macros::selector;
 use starknet::{
     core::{
@@ -16,9 +18,15 @@ use starknet_crypto::Felt;
 pub struct StarknetProvider {
     provider: Arc<JsonRpcClient<HttpTransport>>,
     rpc_url: String,
+    max_concurrent_calls: usize,
 }
 
+/// Calls in flight at once when reading many batches.
+const DEFAULT_MAX_CONCURRENT_CALLS: usize = 8;
+
 impl StarknetProvider {
+    /// Connects to `rpc_url`, bounding concurrent reads by the optional
+    /// `STARKNET_MAX_CONCURRENT_CALLS`.
     #[instrument(level = "debug", fields(rpc_url = %rpc_url))]
     pub fn new(rpc_url: &str) -> Result<Self, StarknetHandlerError> {
         debug!("Initializing StarknetProvider");
@@ -26,12 +34,22 @@ impl StarknetProvider {
         let parsed_url = Url::parse(rpc_url)?;
         debug!("Parsed RPC URL successfully");
 
+        let max_concurrent_calls =
+            optional_var("STARKNET_MAX_CONCURRENT_CALLS")?.unwrap_or(DEFAULT_MAX_CONCURRENT_CALLS);
+
         Ok(Self {
             provider: Arc::new(JsonRpcClient::new(HttpTransport::new(parsed_url))),
             rpc_url: rpc_url.to_string(),
+            max_concurrent_calls: max_concurrent_calls.max(1),
         })
     }
 
+    /// Bounds the calls in flight at once when reading many batches.
+    pub fn with_max_concurrent_calls(mut self, max_concurrent_calls: usize) -> Self {
+        self.max_concurrent_calls = max_concurrent_calls.max(1);
+        self
+    }
+
     pub fn rpc_url(&self) -> &str {
         &self.rpc_url
     }
@@ -122,6 +140,31 @@ impl StarknetProvider {
         Ok(mmr_state)
     }
 
+    /// Fetches the MMR state of every given batch, at most
+    /// `max_concurrent_calls` at a time, keyed by batch index. Repeated
+    /// indexes are fetched once.
+    #[instrument(skip(self, batch_indexes), level = "debug")]
+    pub async fn get_mmr_states(
+        &self,
+        l2_store_address: &str,
+        batch_indexes: impl IntoIterator<Item = u64>,
+    ) -> Result<BTreeMap<u64, MmrSnapshot>, StarknetHandlerError> {
+        let batch_indexes: BTreeSet<u64> = batch_indexes.into_iter().collect();
+        debug!(batches = batch_indexes.len(), "Fetching MMR states");
+
+        let snapshots = stream::iter(batch_indexes)
+            .map(|batch_index| async move {
+                let snapshot = self.get_mmr_state(l2_store_address, batch_index).await?;
+                Ok::<_, StarknetHandlerError>((batch_index, snapshot))
+            })
+            .buffer_unordered(self.max_concurrent_calls)
+            .try_collect::<BTreeMap<_, _>>()
+            .await?;
+        info!(batches = snapshots.len(), "Retrieved MMR states");
+
+        Ok(snapshots)
+    }
+
     #[instrument(skip(self), level = "debug")]
     pub async fn get_latest_relayed_block(
         &self,
@@ -176,6 +219,86 @@ mod tests {
         assert!(provider.is_err());
     }
 
+    #[tokio::test]
+    async fn test_get_mmr_states_without_batches() {
+        // No call is made for an empty set of batches
+        let provider = StarknetProvider::new("http://localhost:5050")
+            .unwrap()
+            .with_max_concurrent_calls(0);
+        let states = provider.get_mmr_states("0x123", Vec::new()).await.unwrap();
+        assert!(states.is_empty());
+    }
+
+    /// Serves `get_mmr_state` for batches 0 to 3, answering later batches first.
+    async fn serve_mmr_states() -> String {
+        use axum::{routing::post, Json, Router};
+        use serde_json::{json, Value};
+        use starknet::core::{
+            codec::Encode,
+            types::{ByteArray, U256},
+        };
+
+        let app = Router::new().route(
+            "/",
+            post(|Json(request): Json<Value>| async move {
+                let params = &request["params"];
+                let call = if params.is_array() {
+                    &params[0]
+                } else {
+                    &params["request"]
+                };
+                let batch_index = u64::from_str_radix(
+                    call["calldata"][0]
+                        .as_str()
+                        .unwrap()
+                        .trim_start_matches("0x"),
+                    16,
+                )
+                .unwrap();
+                let delay = 20 * 3u64.saturating_sub(batch_index);
+                tokio::time::sleep(std::time::Duration::from_millis(delay)).await;
+
+                let snapshot = MmrSnapshot {
+                    batch_index,
+                    latest_mmr_block: batch_index * 1024,
+                    latest_mmr_block_hash: U256::from(batch_index),
+                    root_hash: U256::from(batch_index),
+                    leaves_count: 1024,
+                    ipfs_hash: ByteArray::from("QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG"),
+                };
+                let mut felts = Vec::new();
+                snapshot.encode(&mut felts).unwrap();
+                let result: Vec<String> = felts.iter().map(|felt| format!("{:#x}", felt)).collect();
+                Json(json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }))
+            }),
+        );
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{}", address)
+    }
+
+    #[tokio::test]
+    async fn test_get_mmr_states_keys_results_by_batch() {
+        let rpc_url = serve_mmr_states().await;
+        let provider = StarknetProvider::new(&rpc_url)
+            .unwrap()
+            .with_max_concurrent_calls(4);
+
+        // Batch 0 answers last, so responses arrive out of request order
+        let states = provider
+            .get_mmr_states("0x123", [3, 0, 2, 1, 2])
+            .await
+            .unwrap();
+
+        assert_eq!(states.keys().copied().collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+        for (batch_index, snapshot) in &states {
+            assert_eq!(snapshot.batch_index(), *batch_index);
+            assert_eq!(snapshot.latest_mmr_block(), batch_index * 1024);
+        }
+    }
+
     #[test]
     fn test_provider_getters() {
         let rpc_url = "http://localhost:5050";
@@ -1,12 +1,32 @@
-use clap::Parser;
-use common::initialize_logger_and_env;
-use methods::VALIDATE_BLOCKS_AND_EXTRACT_FEES_ID;
-use publisher::utils::Stark;
-use reqwest::Client;
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use common::{get_env_var, initialize_logger_and_env};
+use guest_types::BlockFeeRecord;
+use publisher::validator::{verify_batch_root, verify_fee_journal_root};
+use starknet_handler::provider::StarknetProvider;
+use state_proof_client::{
+    fees_to_csv, AggregatedFeeProof, FeeProof, ProofBundle, ProofRequest, StateProofClient,
+};
+use std::ffi::OsString;
+use std::time::Duration;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
-struct Args {
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Fetch and verify the fee proofs of a block range from the state proof API;
+    /// the default when no command is given
+    Fetch(FetchArgs),
+    /// Verify a proof bundle saved by `fetch --bundle`, offline, and output its fees
+    VerifyBundle(VerifyBundleArgs),
+}
+
+#[derive(clap::Args, Debug)]
+struct FetchArgs {
     /// Starting block number
     #[arg(long)]
     from_block: u64,
@@ -19,76 +39,277 @@ struct Args {
     #[arg(long)]
     skip_proof_verification: Option<bool>,
 
+    /// Block sampling mode, e.g. every_block, every_nth:10, time_bucket:3600, blocks:1,2,3
+    #[arg(long)]
+    sampling: Option<String>,
+
+    /// Request a single proof aggregating every batch of the range
+    #[arg(long)]
+    aggregate: bool,
+
+    /// Skip checking the committed MMR roots against the Fossil Store.
+    /// Otherwise STARKNET_RPC_URL and FOSSIL_STORE must be set.
+    #[arg(long)]
+    skip_onchain_root_check: bool,
+
     /// API endpoint URL
     #[arg(long, default_value = "http://127.0.0.1:3000")]
     api_url: String,
+
+    /// API key, for servers started with --api-keys-file
+    #[arg(long, env = "STATE_PROOF_API_KEY")]
+    api_key: Option<String>,
+
+    /// Give up on a request attempt after this many seconds
+    #[arg(long)]
+    timeout_secs: Option<u64>,
+
+    /// Save the verified proofs as a proof bundle file
+    #[arg(long)]
+    bundle: Option<String>,
+}
+
+#[derive(clap::Args, Debug)]
+struct VerifyBundleArgs {
+    /// Proof bundle file
+    bundle: String,
+
+    /// Skip checking the bundled MMR roots against the Fossil Store.
+    /// Otherwise STARKNET_RPC_URL and FOSSIL_STORE must be set.
+    #[arg(long)]
+    skip_onchain_root_check: bool,
+
+    /// Format of the fees
+    #[arg(long, value_enum, default_value_t = FeesFormat::Json)]
+    format: FeesFormat,
+
+    /// File to write the fees to, instead of stdout
+    #[arg(long)]
+    output: Option<String>,
 }
 
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum FeesFormat {
+    Json,
+    Csv,
+}
+
+type OnchainStore = Option<(StarknetProvider, String)>;
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     initialize_logger_and_env()?;
-    let args = Args::parse();
 
-    let client = Client::new();
-    let url = format!(
-        "{}/verify-blocks?from_block={}&to_block={}",
-        args.api_url, args.from_block, args.to_block
-    );
+    match Cli::parse_from(with_default_command(std::env::args_os().collect())).command {
+        Command::Fetch(args) => fetch(args).await,
+        Command::VerifyBundle(args) => verify_bundle(args).await,
+    }
+}
+
+/// Inserts `fetch` when the arguments start with no command, so that
+/// `fetch-fees-proof --from-block 1 --to-block 10` keeps working.
+fn with_default_command(mut args: Vec<OsString>) -> Vec<OsString> {
+    let first = args.get(1).and_then(|arg| arg.to_str()).unwrap_or_default();
+    let is_command = Cli::command()
+        .get_subcommands()
+        .any(|command| command.get_name() == first)
+        || matches!(first, "help" | "-h" | "--help" | "-V" | "--version");
+    if !is_command {
+        args.insert(1, OsString::from("fetch"));
+    }
+    args
+}
+
+fn onchain_store(
+    skip_onchain_root_check: bool,
+) -> Result<OnchainStore, Box<dyn std::error::Error>> {
+    if skip_onchain_root_check {
+        return Ok(None);
+    }
+    let provider = StarknetProvider::new(&get_env_var("STARKNET_RPC_URL")?)?;
+    Ok(Some((provider, get_env_var("FOSSIL_STORE")?)))
+}
+
+async fn fetch(args: FetchArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let onchain = onchain_store(args.skip_onchain_root_check)?;
+
+    let mut client = StateProofClient::new(&args.api_url)?;
+    if let Some(api_key) = &args.api_key {
+        client = client.with_api_key(api_key);
+    }
+    if let Some(secs) = args.timeout_secs {
+        client = client.with_timeout(Duration::from_secs(secs));
+    }
+
+    let mut request = ProofRequest::new(args.from_block, args.to_block);
+    if let Some(skip) = args.skip_proof_verification {
+        request = request.with_skip_proof_verification(skip);
+    }
+    if let Some(sampling) = &args.sampling {
+        request = request.with_sampling(sampling.parse()?);
+    }
 
-    // Add skip_proof_verification to URL if provided
-    let url = if let Some(skip) = args.skip_proof_verification {
-        format!("{}&skip_proof_verification={}", url, skip)
+    let sampling = request.sampling.as_ref().map(ToString::to_string);
+    let (records, bundle) = if args.aggregate {
+        let proof = client.verify_blocks_aggregated(&request).await?;
+        let bundle = ProofBundle::from_aggregated_fee_proof(&proof, sampling)?;
+        (records_from_aggregated_proof(proof, &onchain).await, bundle)
     } else {
-        url
+        let proofs = client.verify_blocks(&request).await?;
+        let bundle = ProofBundle::from_fee_proofs(&proofs, sampling)?;
+        (records_from_batch_proofs(proofs, &onchain).await, bundle)
     };
+    let records = records.inspect_err(|e| tracing::error!("Failed to process response: {}", e))?;
+
+    tracing::info!(
+        "Stark proofs for block fees in range {} to {} verified successfully",
+        args.from_block,
+        args.to_block
+    );
+
+    for record in &records {
+        tracing::info!(
+            block_number = record.block_number,
+            timestamp = record.timestamp,
+            base_fee_per_gas = record.base_fee_per_gas,
+            gas_used = record.gas_used,
+            gas_limit = record.gas_limit,
+            blob_gas_used = ?record.blob_gas_used,
+            excess_blob_gas = ?record.excess_blob_gas,
+            "Decoded block fee record"
+        );
+    }
 
-    let response = client.get(&url).send().await?;
+    let all_fees: Vec<u64> = records
+        .iter()
+        .map(|record| record.base_fee_per_gas)
+        .collect();
+    tracing::info!("All consolidated fees: {:?}", all_fees);
 
-    if !response.status().is_success() {
-        let error_text = response.text().await?;
-        tracing::error!("API request failed: {}", error_text);
-        std::process::exit(1);
+    if let Some(path) = &args.bundle {
+        bundle.save(path)?;
+        tracing::info!("Saved proof bundle to {}", path);
     }
 
-    let bytes = response.bytes().await?;
-    tracing::info!("Received {} bytes from API", bytes.len());
+    Ok(())
+}
+
+async fn verify_bundle(args: VerifyBundleArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let onchain = onchain_store(args.skip_onchain_root_check)?;
+
+    let bundle = ProofBundle::load(&args.bundle)?;
+    let verified = bundle.verify()?;
+    tracing::info!(
+        kind = ?bundle.kind,
+        image_id = %bundle.image_id,
+        "Receipts of the bundle for blocks {} to {} verified successfully",
+        verified.block_range.0,
+        verified.block_range.1
+    );
 
-    match bincode::deserialize::<Vec<Stark>>(&bytes) {
-        Ok(stark_vec) => {
+    if let Some((provider, store_address)) = &onchain {
+        for batch in &verified.batches {
+            verify_batch_root(
+                provider,
+                store_address,
+                batch.batch_index,
+                &batch.mmr_root,
+                batch.elements_count,
+            )
+            .await?;
             tracing::info!(
-                "Successfully retrieved and deserialized {} proofs:",
-                stark_vec.len()
+                batch_index = batch.batch_index,
+                "MMR root matches the Fossil Store"
             );
-            let mut all_fees: Vec<u64> = Vec::new();
-            for (_i, stark) in stark_vec.iter().enumerate() {
-                let decoded_journal = stark.receipt().journal.decode::<Vec<String>>()?;
-                let fees: Vec<u64> = decoded_journal
-                    .iter()
-                    .map(|hex| u64::from_str_radix(&hex[2..], 16))
-                    .collect::<Result<_, _>>()?;
-                all_fees.extend(fees.iter());
-                tracing::info!("Decoded fees: {:?}", fees);
-                stark
-                    .receipt()
-                    .verify(VALIDATE_BLOCKS_AND_EXTRACT_FEES_ID)?;
-                tracing::info!(
-                    "Stark proof for block fees in range {} to {} verified successfully",
-                    args.from_block,
-                    args.to_block
-                );
-            }
-            tracing::info!("All consolidated fees: {:?}", all_fees);
         }
-        Err(e) => {
-            tracing::error!("Failed to deserialize response: {}", e);
-            tracing::error!(
-                "First 100 bytes of response: {:?}",
-                &bytes.get(..100.min(bytes.len()))
+    }
+
+    let fees = match args.format {
+        FeesFormat::Json => serde_json::to_string_pretty(&verified.fees)?,
+        FeesFormat::Csv => fees_to_csv(&verified.fees),
+    };
+    match &args.output {
+        Some(path) => {
+            std::fs::write(path, fees)?;
+            tracing::info!(
+                "Wrote the fees of {} blocks to {}",
+                verified.fees.len(),
+                path
             );
-            tracing::error!("Detailed error: {:?}", e);
-            return Err(e.into());
         }
+        None => println!("{}", fees),
     }
 
     Ok(())
 }
+
+async fn records_from_batch_proofs(
+    proofs: Vec<FeeProof>,
+    onchain: &OnchainStore,
+) -> Result<Vec<BlockFeeRecord>, Box<dyn std::error::Error>> {
+    tracing::info!(
+        "Successfully retrieved and verified {} proofs",
+        proofs.len()
+    );
+
+    let mut all_records: Vec<BlockFeeRecord> = Vec::new();
+    for FeeProof { journal, .. } in proofs {
+        if !journal.ok {
+            return Err(format!(
+                "Proof for batch {} attests blocks {} to {} are invalid",
+                journal.batch_index, journal.block_range.0, journal.block_range.1
+            )
+            .into());
+        }
+        if let Some((provider, store_address)) = onchain {
+            verify_fee_journal_root(provider, store_address, &journal).await?;
+            tracing::info!(
+                batch_index = journal.batch_index,
+                "MMR root matches the Fossil Store"
+            );
+        }
+        tracing::info!(
+            batch_index = journal.batch_index,
+            mmr_root = %journal.mmr_root,
+            elements_count = journal.elements_count,
+            "Decoded fees for blocks {} to {}",
+            journal.block_range.0,
+            journal.block_range.1
+        );
+        all_records.extend(journal.fees);
+    }
+
+    all_records.sort_by_key(|record| record.block_number);
+    Ok(all_records)
+}
+
+async fn records_from_aggregated_proof(
+    proof: AggregatedFeeProof,
+    onchain: &OnchainStore,
+) -> Result<Vec<BlockFeeRecord>, Box<dyn std::error::Error>> {
+    let journal = proof.journal;
+    for batch in &journal.batches {
+        if let Some((provider, store_address)) = onchain {
+            verify_batch_root(
+                provider,
+                store_address,
+                batch.batch_index,
+                &batch.mmr_root,
+                batch.elements_count,
+            )
+            .await?;
+            tracing::info!(
+                batch_index = batch.batch_index,
+                "MMR root matches the Fossil Store"
+            );
+        }
+    }
+    tracing::info!(
+        "Decoded aggregated fees for blocks {} to {} across {} batches",
+        journal.block_range.0,
+        journal.block_range.1,
+        journal.batches.len()
+    );
+
+    Ok(journal.fees)
+}
@@ -1,21 +1,50 @@
 use std::sync::Arc;
 
 use axum::{
-    body::Body,
-    extract::{Query, State},
-    http::{header, HeaderMap, StatusCode},
+    extract::{rejection::QueryRejection, Path, Query, State},
+    http::{HeaderMap, StatusCode},
     response::{IntoResponse, Response},
+    Extension, Json,
+};
+use methods::{AGGREGATE_FEE_PROOFS_ID, VALIDATE_BLOCKS_AND_EXTRACT_FEES_ID};
+use publisher::{
+    db::{BlockId, DbConnection, SamplingMode},
+    errors::DbError,
+    extract_fees, extract_fees_aggregated, get_block_inclusion_proof,
 };
-use publisher::extract_fees;
 use serde::{Deserialize, Serialize};
 use tracing::{error, info};
+use utoipa::IntoParams;
+
+use crate::auth::ClientQuota;
+use crate::cache::{cached_proof, ProofCache};
+use crate::errors::{
+    coded_error_response, db_error_response, error_response, publisher_error_response,
+    ErrorResponse,
+};
+use crate::format::{
+    aggregated_fee_proof_response, fee_proofs_response, ResponseFormat, StarkView,
+};
+use crate::jobs::JobQueue;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct BlockRangeParams {
     from_block: u64,
     to_block: u64,
     /// Optional override for skip_proof_verification from CLI
     skip_proof_verification: Option<bool>,
+    /// Block sampling mode, e.g. `every_nth:10`; defaults to one block per hour
+    sampling: Option<String>,
+}
+
+impl BlockRangeParams {
+    fn sampling(&self) -> Result<SamplingMode, DbError> {
+        match self.sampling.as_deref() {
+            Some(sampling) => sampling.parse().map_err(DbError::from),
+            None => Ok(SamplingMode::default()),
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -25,80 +54,373 @@ pub struct AppState {
     pub chain_id: u64,
     pub skip_proof_verification: bool,
     pub batch_size: u64,
+    pub db_connection: Arc<DbConnection>,
+    pub jobs: Arc<JobQueue>,
+    pub proof_cache: Option<Arc<ProofCache>>,
+    /// Largest block range any client may request at once
+    pub max_blocks_per_request: Option<u64>,
 }
 
-#[derive(Serialize)]
-struct ErrorResponse {
-    error: String,
+fn bincode_bytes<T: Serialize>(value: &T) -> Result<Vec<u8>, Response> {
+    bincode::serialize(value).map_err(|e| {
+        error!("Failed to serialize response: {}", e);
+        error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to serialize response: {}", e),
+        )
+    })
 }
 
-pub async fn verify_blocks(
-    State(state): State<Arc<AppState>>,
-    Query(params): Query<BlockRangeParams>,
-) -> impl IntoResponse {
-    // Log request details
+/// Reports malformed query strings and bodies in the same JSON shape as
+/// every other error.
+pub(crate) fn rejection_response(status: StatusCode, error: String) -> Response {
+    error!("Rejected request: {}", error);
+    coded_error_response(status, "invalid_request", error)
+}
+
+/// Rejects reversed ranges and ranges larger than the server-wide limit or the
+/// client's quota, before any header is fetched or proof generated.
+pub(crate) fn check_range_size(
+    state: &AppState,
+    quota: Option<&ClientQuota>,
+    from_block: u64,
+    to_block: u64,
+) -> Result<(), Response> {
+    if from_block > to_block {
+        return Err(db_error_response(&DbError::InvalidBlockRange {
+            start_block: from_block,
+            end_block: to_block,
+        }));
+    }
+
+    let limit = [
+        state.max_blocks_per_request,
+        quota.and_then(|quota| quota.max_blocks_per_request),
+    ]
+    .into_iter()
+    .flatten()
+    .min();
+
+    let blocks = (to_block - from_block).saturating_add(1);
+    match limit {
+        Some(limit) if blocks > limit => {
+            error!(
+                "Rejected range of {} blocks, the limit is {}",
+                blocks, limit
+            );
+            Err(coded_error_response(
+                StatusCode::BAD_REQUEST,
+                "range_too_large",
+                format!(
+                    "Block range {} to {} spans {} blocks, at most {} are allowed per request",
+                    from_block, to_block, blocks, limit
+                ),
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+pub(crate) fn negotiate(headers: &HeaderMap) -> Result<ResponseFormat, Response> {
+    ResponseFormat::negotiate(headers).map_err(|e| {
+        error!("Unsupported response format: {}", e);
+        error_response(StatusCode::NOT_ACCEPTABLE, e)
+    })
+}
+
+fn log_request(params: &BlockRangeParams) {
     info!(
         "Processing block range request: from_block={}, to_block={}, total_blocks={}",
         params.from_block,
         params.to_block,
-        params.to_block.saturating_sub(params.from_block) + 1,
+        params
+            .to_block
+            .saturating_sub(params.from_block)
+            .saturating_add(1),
     );
+}
+
+/// Returns the fee proofs of the range, one per batch, as a bincode-encoded
+/// `Vec<Stark>` or, depending on `Accept`, a JSON or CBOR array of `StarkView`.
+#[utoipa::path(
+    get,
+    path = "/verify-blocks",
+    tag = "proofs",
+    params(BlockRangeParams),
+    responses(
+        (status = 200, description = "Fee proofs, one per batch", content(
+            (Vec<u8> = "application/octet-stream"),
+            (Vec<StarkView> = "application/json"),
+            (Vec<StarkView> = "application/cbor"),
+        )),
+        (status = 400, description = "Invalid range, sampling mode or range over quota", body = ErrorResponse),
+        (status = 406, description = "Unsupported `Accept` header", body = ErrorResponse),
+        (status = 500, description = "Proving failed", body = ErrorResponse),
+    )
+)]
+pub async fn verify_blocks(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    quota: Option<Extension<ClientQuota>>,
+    params: Result<Query<BlockRangeParams>, QueryRejection>,
+) -> impl IntoResponse {
+    let params = match params {
+        Ok(Query(params)) => params,
+        Err(rejection) => return rejection_response(rejection.status(), rejection.body_text()),
+    };
+    log_request(&params);
+
+    if let Err(response) =
+        check_range_size(&state, quota.as_deref(), params.from_block, params.to_block)
+    {
+        return response;
+    }
+
+    let format = match negotiate(&headers) {
+        Ok(format) => format,
+        Err(response) => return response,
+    };
 
     // Use query parameter if provided, otherwise use CLI default
     let skip_proof = params
         .skip_proof_verification
         .unwrap_or(state.skip_proof_verification);
 
-    let result = match extract_fees(
+    let sampling = match params.sampling() {
+        Ok(sampling) => sampling,
+        Err(e) => {
+            error!("Invalid sampling mode: {}", e);
+            return db_error_response(&e);
+        }
+    };
+
+    let proof = cached_proof(
+        &state,
+        params.from_block,
+        params.to_block,
+        &sampling,
+        VALIDATE_BLOCKS_AND_EXTRACT_FEES_ID,
+        skip_proof,
+        async {
+            let result = extract_fees(
+                &state.rpc_url,
+                &state.l2_store_address,
+                state.chain_id,
+                state.batch_size,
+                params.from_block,
+                params.to_block,
+                Some(skip_proof),
+                sampling.clone(),
+                state.db_connection.clone().into(),
+            )
+            .await
+            .map_err(|e| {
+                error!("Error verifying blocks: {}", e);
+                publisher_error_response(&e)
+            })?;
+            bincode_bytes(&result)
+        },
+    )
+    .await;
+
+    match proof {
+        Ok(bytes) => fee_proofs_response(format, bytes),
+        Err(response) => response,
+    }
+}
+
+/// Returns a single `Stark` aggregating the fee proofs of every batch in the
+/// range, encoded like the proofs of `verify_blocks`.
+#[utoipa::path(
+    get,
+    path = "/verify-blocks/aggregated",
+    tag = "proofs",
+    params(BlockRangeParams),
+    responses(
+        (status = 200, description = "Aggregated fee proof", content(
+            (Vec<u8> = "application/octet-stream"),
+            (StarkView = "application/json"),
+            (StarkView = "application/cbor"),
+        )),
+        (status = 400, description = "Invalid range, sampling mode or range over quota", body = ErrorResponse),
+        (status = 406, description = "Unsupported `Accept` header", body = ErrorResponse),
+        (status = 500, description = "Proving failed", body = ErrorResponse),
+    )
+)]
+pub async fn verify_blocks_aggregated(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    quota: Option<Extension<ClientQuota>>,
+    params: Result<Query<BlockRangeParams>, QueryRejection>,
+) -> impl IntoResponse {
+    let params = match params {
+        Ok(Query(params)) => params,
+        Err(rejection) => return rejection_response(rejection.status(), rejection.body_text()),
+    };
+    log_request(&params);
+
+    if let Err(response) =
+        check_range_size(&state, quota.as_deref(), params.from_block, params.to_block)
+    {
+        return response;
+    }
+
+    let format = match negotiate(&headers) {
+        Ok(format) => format,
+        Err(response) => return response,
+    };
+
+    let skip_proof = params
+        .skip_proof_verification
+        .unwrap_or(state.skip_proof_verification);
+
+    let sampling = match params.sampling() {
+        Ok(sampling) => sampling,
+        Err(e) => {
+            error!("Invalid sampling mode: {}", e);
+            return db_error_response(&e);
+        }
+    };
+
+    let proof = cached_proof(
+        &state,
+        params.from_block,
+        params.to_block,
+        &sampling,
+        AGGREGATE_FEE_PROOFS_ID,
+        skip_proof,
+        async {
+            let result = extract_fees_aggregated(
+                &state.rpc_url,
+                &state.l2_store_address,
+                state.chain_id,
+                state.batch_size,
+                params.from_block,
+                params.to_block,
+                Some(skip_proof),
+                sampling.clone(),
+                state.db_connection.clone().into(),
+            )
+            .await
+            .map_err(|e| {
+                error!("Error aggregating fee proofs: {}", e);
+                publisher_error_response(&e)
+            })?;
+            bincode_bytes(&result)
+        },
+    )
+    .await;
+
+    match proof {
+        Ok(bytes) => aggregated_fee_proof_response(format, bytes),
+        Err(response) => response,
+    }
+}
+
+/// Returns the JSON MMR inclusion proof of a block, given its number or hash,
+/// with the index of the batch whose on-chain root it verifies against.
+#[utoipa::path(
+    get,
+    path = "/block-proof/{block}",
+    tag = "proofs",
+    params(("block" = String, Path, description = "Block number, or 0x-prefixed block hash")),
+    responses(
+        (status = 200, description = "MMR inclusion proof of the block", content_type = "application/json"),
+        (status = 400, description = "Invalid block identifier", body = ErrorResponse),
+        (status = 404, description = "Block not in any batch MMR", body = ErrorResponse),
+        (status = 500, description = "Building the proof failed", body = ErrorResponse),
+    )
+)]
+pub async fn block_proof(
+    State(state): State<Arc<AppState>>,
+    Path(block): Path<String>,
+) -> impl IntoResponse {
+    let block: BlockId = match block.parse() {
+        Ok(block) => block,
+        Err(e) => {
+            error!("Invalid block identifier: {}", e);
+            return db_error_response(&DbError::from(e));
+        }
+    };
+
+    info!("Processing block inclusion proof request: block={}", block);
+
+    match get_block_inclusion_proof(
         &state.rpc_url,
         &state.l2_store_address,
         state.chain_id,
         state.batch_size,
-        params.from_block,
-        params.to_block,
-        Some(skip_proof),
+        &block,
+        state.db_connection.clone().into(),
     )
     .await
     {
-        Ok(result) => match bincode::serialize(&result) {
-            Ok(bytes) => {
-                let mut headers = HeaderMap::new();
-                headers.insert(
-                    header::CONTENT_TYPE,
-                    "application/octet-stream".parse().unwrap(),
-                );
-                Response::builder()
-                    .status(StatusCode::OK)
-                    .header(header::CONTENT_TYPE, "application/octet-stream")
-                    .body(Body::from(bytes))
-                    .unwrap()
-            }
-            Err(e) => {
-                error!("Failed to serialize response: {}", e);
-                let error_json = serde_json::to_vec(&ErrorResponse {
-                    error: format!("Failed to serialize response: {}", e),
-                })
-                .unwrap_or_default();
-                Response::builder()
-                    .status(StatusCode::INTERNAL_SERVER_ERROR)
-                    .header(header::CONTENT_TYPE, "application/json")
-                    .body(error_json.into())
-                    .unwrap()
-            }
-        },
+        Ok(proof) => Json(proof).into_response(),
         Err(e) => {
-            error!("Error verifying blocks: {}", e);
-            let error_json = serde_json::to_vec(&ErrorResponse {
-                error: e.to_string(),
-            })
-            .unwrap_or_default();
-            Response::builder()
-                .status(StatusCode::INTERNAL_SERVER_ERROR)
-                .header(header::CONTENT_TYPE, "application/json")
-                .body(error_json.into())
-                .unwrap()
+            error!("Error building block inclusion proof: {}", e);
+            publisher_error_response(&e)
         }
-    };
+    }
+}
+
+/// Lists every batch known to the Fossil Store or held in a local batch
+/// database, with the state of each side and where they diverge.
+#[utoipa::path(
+    get,
+    path = "/batches",
+    tag = "batches",
+    responses(
+        (status = 200, description = "Batches of the Fossil Store and the local databases", content_type = "application/json"),
+        (status = 500, description = "Reading the batches failed", body = ErrorResponse),
+    )
+)]
+pub async fn list_batches(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    info!("Processing batch listing request");
+
+    match publisher::list_batches(&state.rpc_url, &state.l2_store_address, state.batch_size).await {
+        Ok(listing) => Json(listing).into_response(),
+        Err(e) => {
+            error!("Error listing batches: {}", e);
+            publisher_error_response(&e)
+        }
+    }
+}
 
-    result
+/// Returns the Fossil Store and local state of a single batch.
+#[utoipa::path(
+    get,
+    path = "/batches/{index}",
+    tag = "batches",
+    params(("index" = u64, Path, description = "Batch index")),
+    responses(
+        (status = 200, description = "Fossil Store and local state of the batch", content_type = "application/json"),
+        (status = 404, description = "Batch neither on-chain nor local", body = ErrorResponse),
+        (status = 500, description = "Reading the batch failed", body = ErrorResponse),
+    )
+)]
+pub async fn get_batch(
+    State(state): State<Arc<AppState>>,
+    Path(batch_index): Path<u64>,
+) -> impl IntoResponse {
+    info!("Processing batch request: batch_index={}", batch_index);
+
+    match publisher::get_batch(
+        &state.rpc_url,
+        &state.l2_store_address,
+        state.batch_size,
+        batch_index,
+    )
+    .await
+    {
+        Ok(batch) if batch.is_known() => Json(batch).into_response(),
+        Ok(_) => coded_error_response(
+            StatusCode::NOT_FOUND,
+            "batch_not_found",
+            format!("Batch {} is neither on-chain nor local", batch_index),
+        ),
+        Err(e) => {
+            error!("Error getting batch {}: {}", batch_index, e);
+            publisher_error_response(&e)
+        }
+    }
 }
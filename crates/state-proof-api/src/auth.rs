@@ -0,0 +1,156 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use serde::Deserialize;
+use tracing::{info, warn};
+
+use crate::errors::coded_error_response;
+
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(60);
+pub(crate) const API_KEY_HEADER: &str = "x-api-key";
+
+/// One client allowed to call the API, as listed in the API keys file.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ApiKeyConfig {
+    /// Client name, used in logs
+    pub name: String,
+    /// Secret sent as `Authorization: Bearer <key>` or `X-API-Key: <key>`
+    pub key: String,
+    /// Largest block range this client may request at once
+    pub max_blocks_per_request: Option<u64>,
+    /// Requests allowed in any sliding one-minute window
+    pub requests_per_minute: Option<u32>,
+}
+
+/// Contents of the API keys file:
+///
+/// ```json
+/// { "keys": [{ "name": "dashboard", "key": "...", "max_blocks_per_request": 8192, "requests_per_minute": 30 }] }
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct AuthConfig {
+    pub keys: Vec<ApiKeyConfig>,
+}
+
+impl AuthConfig {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read API keys file {}: {}", path, e))?;
+        serde_json::from_str(&contents)
+            .map_err(|e| format!("Failed to parse API keys file {}: {}", path, e))
+    }
+}
+
+/// Quota of the authenticated client, added to the request extensions for
+/// handlers to enforce.
+#[derive(Debug, Clone)]
+pub struct ClientQuota {
+    pub name: String,
+    pub max_blocks_per_request: Option<u64>,
+}
+
+/// Known API keys with the recent requests of each, for rate limiting.
+pub struct ApiKeys {
+    keys: HashMap<String, ApiKeyConfig>,
+    requests: Mutex<HashMap<String, VecDeque<Instant>>>,
+}
+
+impl ApiKeys {
+    pub fn new(config: AuthConfig) -> Result<Self, String> {
+        let mut keys = HashMap::new();
+        for key in config.keys {
+            if key.key.is_empty() {
+                return Err(format!("API key of client {} is empty", key.name));
+            }
+            if let Some(existing) = keys.insert(key.key.clone(), key) {
+                return Err(format!("Duplicate API key for client {}", existing.name));
+            }
+        }
+        Ok(Self {
+            keys,
+            requests: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Records a request of the key, returning how long to wait before
+    /// retrying when its per-minute quota is used up.
+    fn check_rate(&self, key: &ApiKeyConfig, now: Instant) -> Result<(), Duration> {
+        let Some(limit) = key.requests_per_minute else {
+            return Ok(());
+        };
+
+        let mut requests = self.requests.lock().unwrap();
+        let recent = requests.entry(key.key.clone()).or_default();
+        while recent
+            .front()
+            .is_some_and(|at| now.duration_since(*at) >= RATE_LIMIT_WINDOW)
+        {
+            recent.pop_front();
+        }
+
+        if recent.len() >= limit as usize {
+            let oldest = recent.front().copied().unwrap_or(now);
+            return Err(RATE_LIMIT_WINDOW.saturating_sub(now.duration_since(oldest)));
+        }
+        recent.push_back(now);
+        Ok(())
+    }
+}
+
+fn request_key(headers: &HeaderMap) -> Option<&str> {
+    if let Some(value) = headers.get(header::AUTHORIZATION) {
+        return value.to_str().ok()?.strip_prefix("Bearer ").map(str::trim);
+    }
+    headers.get(API_KEY_HEADER)?.to_str().ok().map(str::trim)
+}
+
+/// Rejects requests without a known API key and requests over the key's
+/// rate limit, and passes the key's [`ClientQuota`] on to the handlers.
+pub async fn require_api_key(
+    State(keys): State<Arc<ApiKeys>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let Some(key) = request_key(request.headers()).and_then(|key| keys.keys.get(key)) else {
+        warn!(
+            "Rejected request to {} without a valid API key",
+            request.uri()
+        );
+        return coded_error_response(
+            StatusCode::UNAUTHORIZED,
+            "unauthorized",
+            "Missing or unknown API key".to_string(),
+        );
+    };
+
+    if let Err(retry_after) = keys.check_rate(key, Instant::now()) {
+        info!(client = %key.name, "Rate limit exceeded");
+        let mut response = coded_error_response(
+            StatusCode::TOO_MANY_REQUESTS,
+            "rate_limited",
+            format!(
+                "Client {} exceeded {} requests per minute",
+                key.name,
+                key.requests_per_minute.unwrap_or_default()
+            ),
+        );
+        let retry_after = retry_after.as_secs().max(1);
+        response
+            .headers_mut()
+            .insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+        return response;
+    }
+
+    request.extensions_mut().insert(ClientQuota {
+        name: key.name.clone(),
+        max_blocks_per_request: key.max_blocks_per_request,
+    });
+    next.run(request).await
+}
@@ -0,0 +1,255 @@
+use std::future::Future;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use publisher::db::SamplingMode;
+use risc0_zkvm::sha::Digest;
+use sqlx::{sqlite::SqlitePoolOptions, Row, SqlitePool};
+use starknet_handler::{provider::StarknetProvider, StarknetHandlerError};
+use tracing::{debug, info, warn};
+
+use crate::api::AppState;
+
+/// Identifies a cached proof.
+///
+/// `range_key` covers the block range, sampling mode and image id of the
+/// method that produced the proof. The MMR roots of the batches the range
+/// spans are stored alongside, so that a root updated on-chain invalidates
+/// the entry instead of serving a proof of the old MMR.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProofCacheKey {
+    range_key: String,
+    mmr_roots: String,
+}
+
+impl ProofCacheKey {
+    pub fn new(
+        from_block: u64,
+        to_block: u64,
+        sampling: &SamplingMode,
+        image_id: [u32; 8],
+        mmr_roots: &[(u64, String)],
+    ) -> Self {
+        let range_key = format!(
+            "{}:{}:{}:{}",
+            from_block,
+            to_block,
+            sampling,
+            Digest::from(image_id)
+        );
+        let mmr_roots = mmr_roots
+            .iter()
+            .map(|(batch_index, root)| format!("{}={}", batch_index, root))
+            .collect::<Vec<_>>()
+            .join(",");
+
+        Self {
+            range_key,
+            mmr_roots,
+        }
+    }
+}
+
+/// SQLite-backed cache of encoded proofs, evicting the least recently used
+/// entries once their total size exceeds `max_bytes`.
+pub struct ProofCache {
+    pool: SqlitePool,
+    max_bytes: u64,
+}
+
+impl ProofCache {
+    /// Opens the cache at a file path, or at any `sqlite:` URL.
+    pub async fn open(path: &str, max_bytes: u64) -> Result<Self, sqlx::Error> {
+        let url = if path.starts_with("sqlite:") {
+            path.to_string()
+        } else {
+            format!("sqlite://{}?mode=rwc", path)
+        };
+
+        // A single connection keeps in-memory databases shared and serializes
+        // the read-modify-write of the eviction pass
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect(&url)
+            .await?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS proof_cache (
+                range_key TEXT PRIMARY KEY,
+                mmr_roots TEXT NOT NULL,
+                proof BLOB NOT NULL,
+                size INTEGER NOT NULL,
+                created_at INTEGER NOT NULL,
+                last_accessed INTEGER NOT NULL
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await?;
+
+        Ok(Self { pool, max_bytes })
+    }
+
+    /// Returns the cached proof for the key. An entry for the same range whose
+    /// MMR roots differ is stale and is removed.
+    pub async fn get(&self, key: &ProofCacheKey) -> Result<Option<Vec<u8>>, sqlx::Error> {
+        let row = sqlx::query("SELECT mmr_roots, proof FROM proof_cache WHERE range_key = ?")
+            .bind(&key.range_key)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        let mmr_roots: String = row.get("mmr_roots");
+        if mmr_roots != key.mmr_roots {
+            info!(
+                range_key = %key.range_key,
+                "Invalidating cached proof, batch MMR roots changed"
+            );
+            self.remove(&key.range_key).await?;
+            return Ok(None);
+        }
+
+        sqlx::query("UPDATE proof_cache SET last_accessed = ? WHERE range_key = ?")
+            .bind(unix_millis())
+            .bind(&key.range_key)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(Some(row.get("proof")))
+    }
+
+    pub async fn put(&self, key: &ProofCacheKey, proof: &[u8]) -> Result<(), sqlx::Error> {
+        let now = unix_millis();
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO proof_cache
+                (range_key, mmr_roots, proof, size, created_at, last_accessed)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&key.range_key)
+        .bind(&key.mmr_roots)
+        .bind(proof)
+        .bind(proof.len() as i64)
+        .bind(now)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        self.evict().await
+    }
+
+    /// Number of cached proofs and their total size in bytes.
+    pub async fn stats(&self) -> Result<(u64, u64), sqlx::Error> {
+        let row = sqlx::query(
+            "SELECT COUNT(*) AS entries, COALESCE(SUM(size), 0) AS bytes FROM proof_cache",
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        let entries: i64 = row.get("entries");
+        let bytes: i64 = row.get("bytes");
+        Ok((entries as u64, bytes as u64))
+    }
+
+    async fn remove(&self, range_key: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM proof_cache WHERE range_key = ?")
+            .bind(range_key)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn evict(&self) -> Result<(), sqlx::Error> {
+        let rows =
+            sqlx::query("SELECT range_key, size FROM proof_cache ORDER BY last_accessed DESC")
+                .fetch_all(&self.pool)
+                .await?;
+
+        let mut total: u64 = 0;
+        for row in rows {
+            let size: i64 = row.get("size");
+            total += size as u64;
+            if total > self.max_bytes {
+                let range_key: String = row.get("range_key");
+                debug!(range_key = %range_key, "Evicting cached proof");
+                self.remove(&range_key).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn unix_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or_default()
+}
+
+/// On-chain MMR roots of every batch the block range spans.
+async fn batch_mmr_roots(
+    state: &AppState,
+    from_block: u64,
+    to_block: u64,
+) -> Result<Vec<(u64, String)>, StarknetHandlerError> {
+    let provider = StarknetProvider::new(&state.rpc_url)?;
+    let snapshots = provider
+        .get_mmr_states(
+            &state.l2_store_address,
+            from_block / state.batch_size..=to_block / state.batch_size,
+        )
+        .await?;
+
+    Ok(snapshots
+        .into_iter()
+        .map(|(batch_index, snapshot)| (batch_index, snapshot.root_hash().to_string()))
+        .collect())
+}
+
+/// Serves an encoded proof from the cache when one was generated for the same
+/// range against the current on-chain roots, otherwise runs `prove` and caches
+/// its result. Proofs generated without on-chain root verification are never
+/// cached, and cache failures only fall back to proving.
+pub(crate) async fn cached_proof<E, Fut>(
+    state: &AppState,
+    from_block: u64,
+    to_block: u64,
+    sampling: &SamplingMode,
+    image_id: [u32; 8],
+    skip_proof: bool,
+    prove: Fut,
+) -> Result<Vec<u8>, E>
+where
+    Fut: Future<Output = Result<Vec<u8>, E>>,
+{
+    let cache = match &state.proof_cache {
+        Some(cache) if !skip_proof => cache,
+        _ => return prove.await,
+    };
+
+    let key = match batch_mmr_roots(state, from_block, to_block).await {
+        Ok(mmr_roots) => ProofCacheKey::new(from_block, to_block, sampling, image_id, &mmr_roots),
+        Err(e) => {
+            warn!(error = %e, "Failed to fetch batch MMR roots, bypassing proof cache");
+            return prove.await;
+        }
+    };
+
+    match cache.get(&key).await {
+        Ok(Some(proof)) => {
+            info!(from_block, to_block, "Serving cached proof");
+            return Ok(proof);
+        }
+        Ok(None) => debug!(from_block, to_block, "Proof cache miss"),
+        Err(e) => warn!(error = %e, "Failed to read proof cache"),
+    }
+
+    let proof = prove.await?;
+    if let Err(e) = cache.put(&key, &proof).await {
+        warn!(error = %e, "Failed to store proof in cache");
+    }
+    Ok(proof)
+}
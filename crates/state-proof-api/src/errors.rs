@@ -0,0 +1,100 @@
+pub use api_types::ErrorResponse;
+use axum::{
+    http::{header, StatusCode},
+    response::Response,
+};
+use publisher::errors::{DbError, PublisherError, ValidatorError};
+
+pub(crate) fn error_response(status: StatusCode, error: String) -> Response {
+    coded_error_response(status, status_code(status), error)
+}
+
+/// Maps a publisher error to the status and code of its variant, so that
+/// clients can tell missing data and invalid requests from prover failures.
+pub(crate) fn publisher_error_response(error: &PublisherError) -> Response {
+    let (status, code) = publisher_error_code(error);
+    coded_error_response(status, code, error.to_string())
+}
+
+pub(crate) fn db_error_response(error: &DbError) -> Response {
+    let (status, code) = db_error_code(error);
+    coded_error_response(status, code, error.to_string())
+}
+
+pub(crate) fn coded_error_response(status: StatusCode, code: &str, error: String) -> Response {
+    let error_json = serde_json::to_vec(&ErrorResponse {
+        code: code.to_string(),
+        error,
+    })
+    .unwrap_or_default();
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(error_json.into())
+        .unwrap()
+}
+
+fn status_code(status: StatusCode) -> &'static str {
+    match status {
+        StatusCode::BAD_REQUEST => "bad_request",
+        StatusCode::NOT_FOUND => "not_found",
+        StatusCode::CONFLICT => "conflict",
+        StatusCode::NOT_ACCEPTABLE => "not_acceptable",
+        _ => "internal_error",
+    }
+}
+
+pub fn publisher_error_code(error: &PublisherError) -> (StatusCode, &'static str) {
+    match error {
+        PublisherError::VerificationError => {
+            (StatusCode::INTERNAL_SERVER_ERROR, "verification_failed")
+        }
+        PublisherError::Accumulator(_) => (StatusCode::INTERNAL_SERVER_ERROR, "accumulator_error"),
+        PublisherError::StarknetHandler(_) => (StatusCode::INTERNAL_SERVER_ERROR, "starknet_error"),
+        PublisherError::MMRUtils(_) => (StatusCode::INTERNAL_SERVER_ERROR, "mmr_error"),
+        PublisherError::Validator(error) => validator_error_code(error),
+        PublisherError::ReceiptError => (StatusCode::INTERNAL_SERVER_ERROR, "invalid_receipt"),
+        PublisherError::DbConnection(error) => db_error_code(error),
+    }
+}
+
+fn validator_error_code(error: &ValidatorError) -> (StatusCode, &'static str) {
+    match error {
+        ValidatorError::BlockNotFound(_) => (StatusCode::NOT_FOUND, "block_not_found"),
+        ValidatorError::InvalidInput(_) => (StatusCode::BAD_REQUEST, "invalid_input"),
+        ValidatorError::RejectedBlocks { .. } => {
+            (StatusCode::UNPROCESSABLE_ENTITY, "rejected_blocks")
+        }
+        ValidatorError::InvalidMmrRoot { .. } | ValidatorError::InvalidElementsCount { .. } => {
+            (StatusCode::CONFLICT, "mmr_root_mismatch")
+        }
+        ValidatorError::InvalidProofsCount { .. } => {
+            (StatusCode::INTERNAL_SERVER_ERROR, "invalid_proofs_count")
+        }
+        ValidatorError::ProofGenerator(_) => (StatusCode::INTERNAL_SERVER_ERROR, "proving_failed"),
+        ValidatorError::StarknetProvider(_) => {
+            (StatusCode::INTERNAL_SERVER_ERROR, "starknet_error")
+        }
+        ValidatorError::DbConnection(error) => db_error_code(error),
+        ValidatorError::Sqlx(_) => (StatusCode::INTERNAL_SERVER_ERROR, "database_error"),
+        ValidatorError::Utils(_)
+        | ValidatorError::MMRUtils(_)
+        | ValidatorError::Store(_)
+        | ValidatorError::MMRError(_)
+        | ValidatorError::FeltParsing(_) => (StatusCode::INTERNAL_SERVER_ERROR, "mmr_error"),
+    }
+}
+
+fn db_error_code(error: &DbError) -> (StatusCode, &'static str) {
+    match error {
+        DbError::InvalidBlockRange { .. } => (StatusCode::BAD_REQUEST, "invalid_block_range"),
+        DbError::InvalidSampling(_) => (StatusCode::BAD_REQUEST, "invalid_sampling"),
+        DbError::InvalidBlockId(_) => (StatusCode::BAD_REQUEST, "invalid_block_id"),
+        DbError::MissingBlocks(_) => (StatusCode::NOT_FOUND, "missing_blocks"),
+        DbError::Connection(_) => (StatusCode::SERVICE_UNAVAILABLE, "database_unavailable"),
+        DbError::Database(_)
+        | DbError::EnvVar(_)
+        | DbError::InvalidConfig(_)
+        | DbError::Fixture(_) => (StatusCode::INTERNAL_SERVER_ERROR, "database_error"),
+    }
+}
@@ -0,0 +1,176 @@
+use axum::{
+    body::Body,
+    http::{header, HeaderMap, StatusCode},
+    response::Response,
+};
+use guest_types::{AggregatedFeeJournal, FeeProofJournal};
+use publisher::utils::Stark;
+use risc0_zkvm::sha::Digest;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tracing::error;
+use utoipa::ToSchema;
+
+use crate::errors::error_response;
+
+const OCTET_STREAM: &str = "application/octet-stream";
+const JSON: &str = "application/json";
+const CBOR: &str = "application/cbor";
+
+/// Encoding of a proof response, negotiated from the `Accept` header.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ResponseFormat {
+    /// bincode-encoded `Stark` values, as consumed by `fetch-fees-proof`
+    #[default]
+    Bincode,
+    /// [`StarkView`] documents
+    Json,
+    /// [`StarkView`] documents, encoded as CBOR
+    Cbor,
+}
+
+impl ResponseFormat {
+    /// Picks the supported media type with the highest quality. A missing
+    /// header or a wildcard keeps the bincode encoding existing clients expect.
+    pub fn negotiate(headers: &HeaderMap) -> Result<Self, String> {
+        let Some(accept) = headers.get(header::ACCEPT) else {
+            return Ok(Self::default());
+        };
+        let accept = accept
+            .to_str()
+            .map_err(|_| "Accept header is not valid ASCII".to_string())?;
+
+        let mut best: Option<(f32, Self)> = None;
+        for range in accept.split(',') {
+            let mut parts = range.split(';').map(str::trim);
+            let media_type = parts.next().unwrap_or_default().to_ascii_lowercase();
+            let quality = parts
+                .find_map(|param| param.strip_prefix("q="))
+                .and_then(|q| q.parse::<f32>().ok())
+                .unwrap_or(1.0);
+
+            let format = match media_type.as_str() {
+                OCTET_STREAM | "*/*" | "application/*" => Self::Bincode,
+                JSON => Self::Json,
+                CBOR => Self::Cbor,
+                _ => continue,
+            };
+            if quality > 0.0 && best.is_none_or(|(q, _)| quality > q) {
+                best = Some((quality, format));
+            }
+        }
+
+        best.map(|(_, format)| format).ok_or_else(|| {
+            format!(
+                "None of the accepted media types are supported: {}, expected one of {}, {}, {}",
+                accept, OCTET_STREAM, JSON, CBOR
+            )
+        })
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Bincode => OCTET_STREAM,
+            Self::Json => JSON,
+            Self::Cbor => CBOR,
+        }
+    }
+}
+
+/// Stark receipt with its binary parts hex-encoded and its journal decoded,
+/// for clients that do not use the Rust types.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct StarkView {
+    /// Image id of the guest that produced the receipt
+    pub method_id: String,
+    pub image_id: String,
+    /// bincode-encoded `InnerReceipt`; with `journal` it rebuilds the `Receipt`
+    pub seal: String,
+    /// Raw journal bytes committed by the guest
+    pub journal: String,
+    /// `journal` decoded, a `FeeProofJournal` or an `AggregatedFeeJournal`
+    /// depending on the route
+    #[schema(value_type = Object)]
+    pub decoded_journal: serde_json::Value,
+}
+
+impl StarkView {
+    /// Builds the view of a receipt whose journal decodes to a `J`.
+    pub fn from_stark<J: DeserializeOwned + Serialize>(stark: &Stark) -> Result<Self, String> {
+        let receipt = stark.receipt();
+        let seal = bincode::serialize(&receipt.inner).map_err(|e| e.to_string())?;
+        let journal = receipt.journal.decode::<J>().map_err(|e| e.to_string())?;
+        let decoded_journal = serde_json::to_value(journal).map_err(|e| e.to_string())?;
+        let image_id = stark.image_id().map_err(|e| e.to_string())?;
+
+        Ok(Self {
+            method_id: format!("0x{}", Digest::from(stark.method_id())),
+            image_id: format!("0x{}", hex::encode(image_id)),
+            seal: format!("0x{}", hex::encode(seal)),
+            journal: format!("0x{}", hex::encode(&receipt.journal.bytes)),
+            decoded_journal,
+        })
+    }
+}
+
+/// Encodes the bincode `Vec<Stark>` of per-batch fee proofs in `format`.
+pub(crate) fn fee_proofs_response(format: ResponseFormat, bytes: Vec<u8>) -> Response {
+    proof_response(format, bytes, |starks: Vec<Stark>| {
+        starks
+            .iter()
+            .map(StarkView::from_stark::<FeeProofJournal>)
+            .collect::<Result<Vec<_>, _>>()
+    })
+}
+
+/// Encodes the bincode `Stark` of an aggregated fee proof in `format`.
+pub(crate) fn aggregated_fee_proof_response(format: ResponseFormat, bytes: Vec<u8>) -> Response {
+    proof_response(format, bytes, |stark: Stark| {
+        StarkView::from_stark::<AggregatedFeeJournal>(&stark)
+    })
+}
+
+fn proof_response<T, V>(
+    format: ResponseFormat,
+    bytes: Vec<u8>,
+    view: impl FnOnce(T) -> Result<V, String>,
+) -> Response
+where
+    T: DeserializeOwned,
+    V: Serialize,
+{
+    // Proofs are produced and cached as bincode
+    if format == ResponseFormat::Bincode {
+        return body_response(format, bytes);
+    }
+
+    let encoded = bincode::deserialize::<T>(&bytes)
+        .map_err(|e| e.to_string())
+        .and_then(view)
+        .and_then(|view| match format {
+            ResponseFormat::Json => serde_json::to_vec(&view).map_err(|e| e.to_string()),
+            _ => {
+                let mut encoded = Vec::new();
+                ciborium::into_writer(&view, &mut encoded).map_err(|e| e.to_string())?;
+                Ok(encoded)
+            }
+        });
+
+    match encoded {
+        Ok(encoded) => body_response(format, encoded),
+        Err(e) => {
+            error!("Failed to encode proof response: {}", e);
+            error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to encode proof response: {}", e),
+            )
+        }
+    }
+}
+
+fn body_response(format: ResponseFormat, bytes: Vec<u8>) -> Response {
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, format.content_type())
+        .body(Body::from(bytes))
+        .unwrap()
+}
@@ -0,0 +1,448 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+pub use api_types::{JobInfo, JobKind, JobProgress, JobRequest, JobStatus};
+use axum::{
+    extract::{rejection::JsonRejection, Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse,
+    },
+    Extension, Json,
+};
+use methods::{AGGREGATE_FEE_PROOFS_ID, VALIDATE_BLOCKS_AND_EXTRACT_FEES_ID};
+use publisher::{
+    db::SamplingMode,
+    errors::DbError,
+    validator::{ProvingStage, ValidatorBuilder},
+};
+use tokio::sync::{watch, Semaphore};
+use tracing::{error, info};
+
+use crate::api::{check_range_size, negotiate, rejection_response, AppState};
+use crate::auth::ClientQuota;
+use crate::cache::cached_proof;
+use crate::errors::{coded_error_response, db_error_response, error_response, ErrorResponse};
+use crate::format::{aggregated_fee_proof_response, fee_proofs_response, StarkView};
+
+struct Job {
+    info: JobInfo,
+    result: Option<Arc<Vec<u8>>>,
+    /// Publishes every change of `info` to event stream subscribers
+    updates: watch::Sender<JobInfo>,
+    proving_started: Option<Instant>,
+    finished: Option<Instant>,
+}
+
+/// How long finished jobs, and their proofs, stay available by default.
+pub const DEFAULT_JOB_TTL: Duration = Duration::from_secs(60 * 60);
+/// Number of finished jobs kept by default, dropping the oldest first.
+pub const DEFAULT_MAX_FINISHED_JOBS: usize = 1000;
+
+/// In-memory table of proving jobs, with a bounded pool of workers so that
+/// only a few provers run at a time; other jobs wait in the `queued` state.
+/// Finished jobs are evicted once they outlive their TTL or exceed the number
+/// of finished jobs kept, whichever comes first.
+pub struct JobQueue {
+    jobs: RwLock<HashMap<String, Job>>,
+    workers: Arc<Semaphore>,
+    ttl: Duration,
+    max_finished_jobs: usize,
+}
+
+impl JobQueue {
+    pub fn new(max_concurrent_jobs: usize) -> Self {
+        Self {
+            jobs: RwLock::new(HashMap::new()),
+            workers: Arc::new(Semaphore::new(max_concurrent_jobs.max(1))),
+            ttl: DEFAULT_JOB_TTL,
+            max_finished_jobs: DEFAULT_MAX_FINISHED_JOBS,
+        }
+    }
+
+    /// Keeps finished jobs for `ttl`, and at most `max_finished_jobs` of them.
+    pub fn with_retention(mut self, ttl: Duration, max_finished_jobs: usize) -> Self {
+        self.ttl = ttl;
+        self.max_finished_jobs = max_finished_jobs;
+        self
+    }
+
+    pub fn get(&self, id: &str) -> Option<JobInfo> {
+        let jobs = self.jobs.read().unwrap();
+        jobs.get(id).map(|job| job.info.clone())
+    }
+
+    /// Encoded proof of a finished job, along with its current state.
+    pub fn result(&self, id: &str) -> Option<(JobInfo, Option<Arc<Vec<u8>>>)> {
+        let jobs = self.jobs.read().unwrap();
+        jobs.get(id)
+            .map(|job| (job.info.clone(), job.result.clone()))
+    }
+
+    fn insert(&self, request: &JobRequest) -> JobInfo {
+        let info = JobInfo {
+            id: uuid::Uuid::new_v4().to_string(),
+            kind: request.kind,
+            from_block: request.from_block,
+            to_block: request.to_block,
+            status: JobStatus::Queued,
+            progress: JobProgress::default(),
+            stage: None,
+            eta_secs: None,
+            error: None,
+            created_at: unix_now(),
+            finished_at: None,
+        };
+
+        let mut jobs = self.jobs.write().unwrap();
+        self.evict_finished(&mut jobs);
+        jobs.insert(
+            info.id.clone(),
+            Job {
+                info: info.clone(),
+                result: None,
+                updates: watch::channel(info.clone()).0,
+                proving_started: None,
+                finished: None,
+            },
+        );
+        info
+    }
+
+    fn evict_finished(&self, jobs: &mut HashMap<String, Job>) {
+        jobs.retain(|_, job| {
+            job.finished
+                .is_none_or(|finished| finished.elapsed() < self.ttl)
+        });
+
+        let mut finished: Vec<(Instant, String)> = jobs
+            .iter()
+            .filter_map(|(id, job)| job.finished.map(|finished| (finished, id.clone())))
+            .collect();
+        if finished.len() > self.max_finished_jobs {
+            finished.sort();
+            let excess = finished.len() - self.max_finished_jobs;
+            for (_, id) in finished.into_iter().take(excess) {
+                jobs.remove(&id);
+            }
+        }
+    }
+
+    fn update(&self, id: &str, f: impl FnOnce(&mut Job)) {
+        let mut jobs = self.jobs.write().unwrap();
+        if let Some(job) = jobs.get_mut(id) {
+            f(job);
+            job.updates.send_replace(job.info.clone());
+        }
+    }
+
+    fn subscribe(&self, id: &str) -> Option<watch::Receiver<JobInfo>> {
+        let jobs = self.jobs.read().unwrap();
+        jobs.get(id).map(|job| job.updates.subscribe())
+    }
+
+    fn set_stage(&self, id: &str, stage: ProvingStage) {
+        let now = Instant::now();
+        self.update(id, |job| {
+            job.info.eta_secs = match stage {
+                ProvingStage::ProvingBatch { batch, total } => {
+                    let started = *job.proving_started.get_or_insert(now);
+                    let proven = batch as u32 - 1;
+                    let remaining = (total + 1 - batch) as u32;
+                    (proven > 0)
+                        .then(|| (now.duration_since(started) / proven * remaining).as_secs())
+                }
+                _ => None,
+            };
+            job.info.stage = Some(stage);
+        });
+    }
+
+    fn set_progress(&self, id: &str, proven_batches: usize, total_batches: usize) {
+        self.update(id, |job| {
+            job.info.progress = JobProgress {
+                proven_batches,
+                total_batches,
+            };
+        });
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Queues a proving job and returns its id right away. Poll `GET /jobs/{id}`
+/// for its status and download the proof from `GET /jobs/{id}/result`.
+#[utoipa::path(
+    post,
+    path = "/jobs",
+    tag = "jobs",
+    request_body = JobRequest,
+    responses(
+        (status = 202, description = "Job queued", body = JobInfo),
+        (status = 400, description = "Invalid range or sampling mode", body = ErrorResponse),
+    )
+)]
+pub async fn create_job(
+    State(state): State<Arc<AppState>>,
+    quota: Option<Extension<ClientQuota>>,
+    request: Result<Json<JobRequest>, JsonRejection>,
+) -> impl IntoResponse {
+    let request = match request {
+        Ok(Json(request)) => request,
+        Err(rejection) => return rejection_response(rejection.status(), rejection.body_text()),
+    };
+
+    let sampling = match request.sampling() {
+        Ok(sampling) => sampling,
+        Err(e) => {
+            error!("Invalid job request: {}", e);
+            return db_error_response(&DbError::from(e));
+        }
+    };
+
+    if let Err(response) = check_range_size(
+        &state,
+        quota.as_deref(),
+        request.from_block,
+        request.to_block,
+    ) {
+        return response;
+    }
+
+    let info = state.jobs.insert(&request);
+    info!(
+        job_id = %info.id,
+        "Queued {:?} job for blocks {} to {}", request.kind, request.from_block, request.to_block
+    );
+
+    tokio::spawn(run_job(state.clone(), info.id.clone(), request, sampling));
+
+    (StatusCode::ACCEPTED, Json(info)).into_response()
+}
+
+#[utoipa::path(
+    get,
+    path = "/jobs/{id}",
+    tag = "jobs",
+    params(("id" = String, Path, description = "Job id")),
+    responses(
+        (status = 200, description = "Job state", body = JobInfo),
+        (status = 404, description = "Unknown job", body = ErrorResponse),
+    )
+)]
+pub async fn get_job(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    match state.jobs.get(&id) {
+        Some(info) => Json(info).into_response(),
+        None => error_response(StatusCode::NOT_FOUND, format!("Job {} not found", id)),
+    }
+}
+
+/// Returns the proof of a finished job, negotiated and encoded like the
+/// response of the matching synchronous route.
+#[utoipa::path(
+    get,
+    path = "/jobs/{id}/result",
+    tag = "jobs",
+    params(("id" = String, Path, description = "Job id")),
+    responses(
+        (status = 200, description = "Proof of the job, encoded as negotiated from `Accept`", content(
+            (Vec<u8> = "application/octet-stream"),
+            (Vec<StarkView> = "application/json"),
+            (Vec<StarkView> = "application/cbor"),
+        )),
+        (status = 404, description = "Unknown job", body = ErrorResponse),
+        (status = 406, description = "Unsupported `Accept` header", body = ErrorResponse),
+        (status = 409, description = "Job failed or not finished yet", body = ErrorResponse),
+    )
+)]
+pub async fn get_job_result(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let format = match negotiate(&headers) {
+        Ok(format) => format,
+        Err(response) => return response,
+    };
+
+    match state.jobs.result(&id) {
+        Some((info, Some(result))) => match info.kind {
+            JobKind::VerifyBlocks => fee_proofs_response(format, result.as_ref().clone()),
+            JobKind::VerifyBlocksAggregated => {
+                aggregated_fee_proof_response(format, result.as_ref().clone())
+            }
+        },
+        Some((info, None)) if info.status == JobStatus::Failed => coded_error_response(
+            StatusCode::CONFLICT,
+            "job_failed",
+            format!(
+                "Job {} failed: {}",
+                id,
+                info.error.unwrap_or_else(|| "unknown error".to_string())
+            ),
+        ),
+        Some((info, None)) => coded_error_response(
+            StatusCode::CONFLICT,
+            "job_not_finished",
+            format!("Job {} is still {:?}", id, info.status),
+        ),
+        None => error_response(StatusCode::NOT_FOUND, format!("Job {} not found", id)),
+    }
+}
+
+/// Streams the state of a job as server-sent `progress` events carrying its
+/// [`JobInfo`], one per change, ending once the job is done or failed.
+#[utoipa::path(
+    get,
+    path = "/jobs/{id}/events",
+    tag = "jobs",
+    params(("id" = String, Path, description = "Job id")),
+    responses(
+        (status = 200, description = "Server-sent `progress` events, each carrying a JobInfo", content_type = "text/event-stream", body = JobInfo),
+        (status = 404, description = "Unknown job", body = ErrorResponse),
+    )
+)]
+pub async fn job_events(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Some(updates) = state.jobs.subscribe(&id) else {
+        return error_response(StatusCode::NOT_FOUND, format!("Job {} not found", id));
+    };
+
+    let events = futures_util::stream::unfold(Some((updates, true)), |updates| async move {
+        let (mut updates, first) = updates?;
+        if !first {
+            updates.changed().await.ok()?;
+        }
+
+        let info = updates.borrow_and_update().clone();
+        let event = Event::default().event("progress").json_data(&info);
+        Some((event, (!info.is_finished()).then_some((updates, false))))
+    });
+
+    Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+async fn run_job(state: Arc<AppState>, id: String, request: JobRequest, sampling: SamplingMode) {
+    let _permit = match state.jobs.workers.clone().acquire_owned().await {
+        Ok(permit) => permit,
+        Err(e) => {
+            error!(job_id = %id, "Job worker pool closed: {}", e);
+            return;
+        }
+    };
+
+    state
+        .jobs
+        .update(&id, |job| job.info.status = JobStatus::Proving);
+    info!(job_id = %id, "Proving job started");
+
+    let result = prove(&state, &id, &request, sampling).await;
+
+    state.jobs.update(&id, |job| {
+        job.info.finished_at = Some(unix_now());
+        job.finished = Some(Instant::now());
+        job.info.stage = None;
+        job.info.eta_secs = None;
+        match result {
+            Ok(bytes) => {
+                job.info.status = JobStatus::Done;
+                job.result = Some(Arc::new(bytes));
+            }
+            Err(e) => {
+                error!(job_id = %job.info.id, "Proving job failed: {}", e);
+                job.info.status = JobStatus::Failed;
+                job.info.error = Some(e);
+            }
+        }
+    });
+}
+
+async fn prove(
+    state: &Arc<AppState>,
+    id: &str,
+    request: &JobRequest,
+    sampling: SamplingMode,
+) -> Result<Vec<u8>, String> {
+    let skip_proof = request
+        .skip_proof_verification
+        .unwrap_or(state.skip_proof_verification);
+
+    let image_id = match request.kind {
+        JobKind::VerifyBlocks => VALIDATE_BLOCKS_AND_EXTRACT_FEES_ID,
+        JobKind::VerifyBlocksAggregated => AGGREGATE_FEE_PROOFS_ID,
+    };
+
+    let progress_state = state.clone();
+    let progress_id = id.to_string();
+    let stage_state = state.clone();
+    let stage_id = id.to_string();
+    let serializing = || state.jobs.set_stage(id, ProvingStage::Serializing);
+    cached_proof(
+        state,
+        request.from_block,
+        request.to_block,
+        &sampling,
+        image_id,
+        skip_proof,
+        async {
+            let validator = ValidatorBuilder::new(
+                &state.rpc_url,
+                &state.l2_store_address,
+                state.chain_id,
+                state.batch_size,
+                skip_proof,
+                state.db_connection.clone().into(),
+            )
+            .await
+            .map_err(|e| e.to_string())?
+            .with_sampling(sampling.clone())
+            .with_progress(Arc::new(move |proven_batches, total_batches| {
+                progress_state
+                    .jobs
+                    .set_progress(&progress_id, proven_batches, total_batches)
+            }))
+            .with_stages(Arc::new(move |stage| {
+                stage_state.jobs.set_stage(&stage_id, stage)
+            }));
+
+            let encoded = match request.kind {
+                JobKind::VerifyBlocks => validator
+                    .validate_blocks_and_extract_fees(request.from_block, request.to_block)
+                    .await
+                    .map(|proofs| {
+                        serializing();
+                        bincode::serialize(&proofs)
+                    }),
+                JobKind::VerifyBlocksAggregated => validator
+                    .validate_blocks_and_extract_fees_aggregated(
+                        request.from_block,
+                        request.to_block,
+                    )
+                    .await
+                    .map(|proof| {
+                        serializing();
+                        bincode::serialize(&proof)
+                    }),
+            };
+
+            encoded
+                .map_err(|e| e.to_string())?
+                .map_err(|e| format!("Failed to serialize proof: {}", e))
+        },
+    )
+    .await
+}
@@ -1,3 +1,18 @@
-pub use crate::api::{verify_blocks, AppState};
+pub use crate::api::{
+    block_proof, get_batch, list_batches, verify_blocks, verify_blocks_aggregated, AppState,
+};
+pub use crate::auth::{ApiKeys, AuthConfig, ClientQuota};
+pub use crate::cache::{ProofCache, ProofCacheKey};
+pub use crate::errors::{publisher_error_code, ErrorResponse};
+pub use crate::format::{ResponseFormat, StarkView};
+pub use crate::jobs::{create_job, get_job, get_job_result, job_events, JobQueue};
+pub use crate::openapi::{openapi, router};
 
 pub mod api;
+pub mod auth;
+pub mod cache;
+pub mod errors;
+pub mod format;
+pub mod jobs;
+pub mod middleware;
+pub mod openapi;
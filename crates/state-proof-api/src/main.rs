@@ -1,11 +1,18 @@
-use axum_server::Server;
+use axum_server::tls_rustls::RustlsConfig;
 use clap::Parser;
-use common::{get_env_var, initialize_logger_and_env};
-use state_proof_api::api::{verify_blocks, AppState};
-use std::net::SocketAddr;
+use common::{get_env_var, get_ethereum_chain_id, initialize_logger_and_env};
+use publisher::db::DbConnection;
+use state_proof_api::api::AppState;
+use state_proof_api::auth::{require_api_key, ApiKeys, AuthConfig};
+use state_proof_api::cache::ProofCache;
+use state_proof_api::jobs::{JobQueue, DEFAULT_JOB_TTL, DEFAULT_MAX_FINISHED_JOBS};
+use state_proof_api::middleware::{cors_layer, request_timeout};
+use state_proof_api::openapi::router;
+use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
+use std::time::Duration;
 
-use axum::{routing::get, Router};
+use axum::{extract::DefaultBodyLimit, middleware};
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -21,6 +28,63 @@ struct Args {
     /// Skip proof verification by default
     #[arg(long, default_value = "false")]
     skip_proof_verification: bool,
+
+    /// Maximum number of proving jobs run concurrently; others stay queued
+    #[arg(long, default_value_t = 1)]
+    max_concurrent_jobs: usize,
+
+    /// Seconds a finished job and its proof stay available
+    #[arg(long, default_value_t = DEFAULT_JOB_TTL.as_secs())]
+    job_ttl_secs: u64,
+
+    /// Maximum number of finished jobs kept, dropping the oldest first
+    #[arg(long, default_value_t = DEFAULT_MAX_FINISHED_JOBS)]
+    max_finished_jobs: usize,
+
+    /// SQLite file caching generated proofs; caching is disabled when unset
+    #[arg(long)]
+    proof_cache: Option<String>,
+
+    /// Size budget of the proof cache in megabytes
+    #[arg(long, default_value_t = 1024)]
+    proof_cache_max_mb: u64,
+
+    /// Address to listen on
+    #[arg(long, env = "STATE_PROOF_API_HOST", default_value = "127.0.0.1")]
+    host: IpAddr,
+
+    /// Port to listen on
+    #[arg(long, env = "STATE_PROOF_API_PORT", default_value_t = 3000)]
+    port: u16,
+
+    /// PEM certificate chain; serves HTTPS together with --tls-key
+    #[arg(long, env = "STATE_PROOF_API_TLS_CERT", requires = "tls_key")]
+    tls_cert: Option<String>,
+
+    /// PEM private key of the TLS certificate
+    #[arg(long, env = "STATE_PROOF_API_TLS_KEY", requires = "tls_cert")]
+    tls_key: Option<String>,
+
+    /// Comma-separated origins allowed by CORS, or * for any; CORS is disabled when unset
+    #[arg(long, env = "STATE_PROOF_API_CORS_ORIGINS", value_delimiter = ',')]
+    cors_origins: Vec<String>,
+
+    /// Maximum size of a request body in bytes
+    #[arg(long, env = "STATE_PROOF_API_MAX_BODY_BYTES", default_value_t = 1024 * 1024)]
+    max_body_bytes: usize,
+
+    /// Abort requests running longer than this many seconds; unlimited when unset
+    #[arg(long, env = "STATE_PROOF_API_REQUEST_TIMEOUT_SECS")]
+    request_timeout_secs: Option<u64>,
+
+    /// JSON file listing the API keys allowed to call the API and their quotas;
+    /// the API is open when unset
+    #[arg(long, env = "STATE_PROOF_API_KEYS_FILE")]
+    api_keys_file: Option<String>,
+
+    /// Largest block range any client may request at once
+    #[arg(long, env = "STATE_PROOF_API_MAX_BLOCKS_PER_REQUEST")]
+    max_blocks_per_request: Option<u64>,
 }
 
 #[tokio::main]
@@ -35,9 +99,20 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Get required environment variables
     let rpc_url = get_env_var("STARKNET_RPC_URL")?;
     let l2_store_address = get_env_var("FOSSIL_STORE")?;
-    let chain_id = get_env_var("CHAIN_ID")?
-        .parse::<u64>()
-        .expect("CHAIN_ID must be a valid number");
+    let chain_id = get_ethereum_chain_id()?;
+
+    // A single pool is shared by every request handled by this process
+    let db_connection = DbConnection::new().await?;
+
+    let proof_cache = match &args.proof_cache {
+        Some(path) => {
+            tracing::info!("caching proofs in {}", path);
+            Some(Arc::new(
+                ProofCache::open(path, args.proof_cache_max_mb * 1024 * 1024).await?,
+            ))
+        }
+        None => None,
+    };
 
     let state = AppState {
         rpc_url,
@@ -45,23 +120,57 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         chain_id,
         skip_proof_verification: args.skip_proof_verification,
         batch_size: args.batch_size,
+        db_connection,
+        jobs: Arc::new(JobQueue::new(args.max_concurrent_jobs).with_retention(
+            Duration::from_secs(args.job_ttl_secs),
+            args.max_finished_jobs,
+        )),
+        proof_cache,
+        max_blocks_per_request: args.max_blocks_per_request,
     };
 
-    let app = Router::new()
-        .route("/verify-blocks", get(verify_blocks))
-        .with_state(Arc::new(state));
+    let mut app = router(Arc::new(state)).layer(DefaultBodyLimit::max(args.max_body_bytes));
+
+    if let Some(path) = &args.api_keys_file {
+        let keys = ApiKeys::new(AuthConfig::load(path)?)?;
+        tracing::info!("requiring API keys from {}", path);
+        app = app.layer(middleware::from_fn_with_state(
+            Arc::new(keys),
+            require_api_key,
+        ));
+    }
+    if let Some(secs) = args.request_timeout_secs {
+        app = app.layer(middleware::from_fn_with_state(
+            Duration::from_secs(secs),
+            request_timeout,
+        ));
+    }
+    // Outermost, so that preflight requests and error responses carry the headers
+    if !args.cors_origins.is_empty() {
+        app = app.layer(cors_layer(&args.cors_origins)?);
+    }
 
-    let addr = SocketAddr::from(([127, 0, 0, 1], 3000));
-    tracing::info!("listening on {}", addr);
+    let addr = SocketAddr::new(args.host, args.port);
     tracing::info!("using environment file: {}", args.env_file);
     tracing::info!(
         "default skip_proof_verification: {}",
         args.skip_proof_verification
     );
 
-    Server::bind(addr)
-        .serve(app.into_make_service())
-        .await
-        .unwrap();
+    match (&args.tls_cert, &args.tls_key) {
+        (Some(cert), Some(key)) => {
+            let config = RustlsConfig::from_pem_file(cert, key).await?;
+            tracing::info!("listening on https://{}", addr);
+            axum_server::bind_rustls(addr, config)
+                .serve(app.into_make_service())
+                .await?;
+        }
+        _ => {
+            tracing::info!("listening on http://{}", addr);
+            axum_server::bind(addr)
+                .serve(app.into_make_service())
+                .await?;
+        }
+    }
     Ok(())
 }
@@ -0,0 +1,61 @@
+use std::time::Duration;
+
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderName, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+use tracing::warn;
+
+use crate::auth::API_KEY_HEADER;
+use crate::errors::coded_error_response;
+
+/// Aborts requests that run longer than the given duration with a coded
+/// `408` error. Queued proving jobs run outside the request and are not
+/// affected, so long ranges should go through `/jobs` when a limit is set.
+pub async fn request_timeout(
+    State(timeout): State<Duration>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let uri = request.uri().clone();
+    match tokio::time::timeout(timeout, next.run(request)).await {
+        Ok(response) => response,
+        Err(_) => {
+            warn!("Request to {} timed out after {:?}", uri, timeout);
+            coded_error_response(
+                StatusCode::REQUEST_TIMEOUT,
+                "request_timeout",
+                format!("Request did not complete within {:?}", timeout),
+            )
+        }
+    }
+}
+
+/// CORS policy for browser dashboards. `*` allows any origin; otherwise only
+/// the listed origins are allowed.
+pub fn cors_layer(origins: &[String]) -> Result<CorsLayer, String> {
+    let layer = CorsLayer::new()
+        .allow_methods([Method::GET, Method::POST])
+        .allow_headers([
+            header::ACCEPT,
+            header::CONTENT_TYPE,
+            header::AUTHORIZATION,
+            HeaderName::from_static(API_KEY_HEADER),
+        ]);
+
+    if origins.iter().any(|origin| origin == "*") {
+        return Ok(layer.allow_origin(Any));
+    }
+
+    let origins = origins
+        .iter()
+        .map(|origin| {
+            HeaderValue::from_str(origin)
+                .map_err(|e| format!("Invalid CORS origin {}: {}", origin, e))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(layer.allow_origin(AllowOrigin::list(origins)))
+}
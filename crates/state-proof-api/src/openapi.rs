@@ -0,0 +1,55 @@
+use std::sync::Arc;
+
+use axum::{routing::get, Json, Router};
+use utoipa::OpenApi;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::api::{self, AppState};
+use crate::jobs;
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Fossil state proof API",
+        description = "Fee proofs of Ethereum block ranges verified against the Fossil Store batch MMRs"
+    ),
+    tags(
+        (name = "proofs", description = "Synchronous proof generation"),
+        (name = "batches", description = "Batch MMRs of the Fossil Store and the local databases"),
+        (name = "jobs", description = "Queued proof generation"),
+    )
+)]
+struct ApiDoc;
+
+fn openapi_router() -> OpenApiRouter<Arc<AppState>> {
+    OpenApiRouter::with_openapi(ApiDoc::openapi())
+        .routes(routes!(api::verify_blocks))
+        .routes(routes!(api::verify_blocks_aggregated))
+        .routes(routes!(api::block_proof))
+        .routes(routes!(api::list_batches))
+        .routes(routes!(api::get_batch))
+        .routes(routes!(jobs::create_job))
+        .routes(routes!(jobs::get_job))
+        .routes(routes!(jobs::get_job_result))
+        .routes(routes!(jobs::job_events))
+}
+
+/// OpenAPI document of every route of [`router`].
+pub fn openapi() -> utoipa::openapi::OpenApi {
+    openapi_router().split_for_parts().1
+}
+
+/// Every route of the API, plus `GET /openapi.json` serving the document
+/// generated from them.
+pub fn router(state: Arc<AppState>) -> Router {
+    let (router, doc) = openapi_router().split_for_parts();
+    router
+        .route(
+            "/openapi.json",
+            get(move || {
+                let doc = doc.clone();
+                async move { Json(doc) }
+            }),
+        )
+        .with_state(state)
+}
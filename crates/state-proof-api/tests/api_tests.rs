@@ -1,9 +1,10 @@
 use axum::{
     body::Body,
     http::{Request, StatusCode},
-    routing::get,
+    routing::{get, post},
     Router,
 };
+use publisher::db::{DbConnection, SamplingMode};
 use state_proof_api::*; // You'll need to make necessary items public in lib.rs
 use std::sync::Arc;
 use tower::ServiceExt;
@@ -16,6 +17,10 @@ fn create_test_state() -> Arc<AppState> {
         chain_id: 1,
         skip_proof_verification: false,
         batch_size: 1024,
+        db_connection: DbConnection::unreachable_for_tests(),
+        jobs: Arc::new(JobQueue::new(1)),
+        proof_cache: None,
+        max_blocks_per_request: None,
     })
 }
 
@@ -45,6 +50,19 @@ async fn test_verify_blocks_endpoint() {
             "/verify-blocks?from_block=1&to_block=def",
             StatusCode::BAD_REQUEST,
         ),
+        // Sampling mode cases
+        (
+            "/verify-blocks?from_block=1&to_block=10&sampling=every_nth:2",
+            StatusCode::INTERNAL_SERVER_ERROR,
+        ),
+        (
+            "/verify-blocks?from_block=1&to_block=10&sampling=daily",
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            "/verify-blocks?from_block=1&to_block=10&sampling=every_nth:x",
+            StatusCode::BAD_REQUEST,
+        ),
     ];
 
     for (uri, expected_status) in test_cases {
@@ -112,6 +130,76 @@ async fn test_skip_proof_verification_combinations() {
     }
 }
 
+#[tokio::test]
+async fn test_verify_blocks_aggregated_endpoint() {
+    let app = Router::new()
+        .route("/verify-blocks/aggregated", get(verify_blocks_aggregated))
+        .with_state(create_test_state());
+
+    let test_cases = vec![
+        // Will fail fast due to connection refused
+        (
+            "/verify-blocks/aggregated?from_block=1&to_block=10",
+            StatusCode::INTERNAL_SERVER_ERROR,
+        ),
+        ("/verify-blocks/aggregated", StatusCode::BAD_REQUEST),
+        (
+            "/verify-blocks/aggregated?from_block=1&to_block=10&sampling=daily",
+            StatusCode::BAD_REQUEST,
+        ),
+    ];
+
+    for (uri, expected_status) in test_cases {
+        let response = app
+            .clone()
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            response.status(),
+            expected_status,
+            "URI '{}' should return status {}",
+            uri,
+            expected_status
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_block_proof_endpoint() {
+    let app = Router::new()
+        .route("/block-proof/{block}", get(block_proof))
+        .with_state(create_test_state());
+
+    let block_hash = format!("/block-proof/0x{}", "ab".repeat(32));
+    let test_cases = vec![
+        // Valid identifiers - will fail fast due to connection refused
+        ("/block-proof/19426587", StatusCode::INTERNAL_SERVER_ERROR),
+        (block_hash.as_str(), StatusCode::INTERNAL_SERVER_ERROR),
+        // Invalid identifiers
+        ("/block-proof/latest", StatusCode::BAD_REQUEST),
+        ("/block-proof/0x1234", StatusCode::BAD_REQUEST),
+        ("/block-proof/-1", StatusCode::BAD_REQUEST),
+    ];
+
+    for (uri, expected_status) in test_cases {
+        let response = app
+            .clone()
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            response.status(),
+            expected_status,
+            "URI '{}' should return status {}",
+            uri,
+            expected_status
+        );
+    }
+}
+
 #[tokio::test]
 async fn test_batch_size_boundaries() {
     let test_cases = vec![
@@ -128,6 +216,10 @@ async fn test_batch_size_boundaries() {
             chain_id: 1,
             skip_proof_verification: false,
             batch_size,
+            db_connection: DbConnection::unreachable_for_tests(),
+            jobs: Arc::new(JobQueue::new(1)),
+            proof_cache: None,
+            max_blocks_per_request: None,
         });
 
         let app = Router::new()
@@ -153,14 +245,18 @@ async fn test_batch_size_boundaries() {
     }
 }
 
-#[test]
-fn test_app_state_creation() {
+#[tokio::test]
+async fn test_app_state_creation() {
     let state = AppState {
         rpc_url: "http://localhost:8545".to_string(),
         l2_store_address: "0x123".to_string(),
         chain_id: 1,
         skip_proof_verification: false,
         batch_size: 1024,
+        db_connection: DbConnection::unreachable_for_tests(),
+        jobs: Arc::new(JobQueue::new(1)),
+        proof_cache: None,
+        max_blocks_per_request: None,
     };
 
     assert_eq!(state.rpc_url, "http://localhost:8545");
@@ -169,3 +265,727 @@ fn test_app_state_creation() {
     assert_eq!(state.skip_proof_verification, false);
     assert_eq!(state.batch_size, 1024);
 }
+
+#[tokio::test]
+async fn test_jobs_endpoints() {
+    let app = Router::new()
+        .route("/jobs", post(create_job))
+        .route("/jobs/{id}", get(get_job))
+        .route("/jobs/{id}/result", get(get_job_result))
+        .with_state(create_test_state());
+
+    let post_job = |body: &'static str| {
+        Request::builder()
+            .method("POST")
+            .uri("/jobs")
+            .header("content-type", "application/json")
+            .body(Body::from(body))
+            .unwrap()
+    };
+
+    // Invalid requests are rejected before queuing
+    for body in [
+        r#"{"from_block": 10, "to_block": 1}"#,
+        r#"{"from_block": 1, "to_block": 10, "sampling": "daily"}"#,
+    ] {
+        let response = app.clone().oneshot(post_job(body)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{}", body);
+    }
+
+    let response = app
+        .clone()
+        .oneshot(post_job(r#"{"from_block": 1, "to_block": 10}"#))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let job: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let id = job["id"].as_str().unwrap().to_string();
+    assert_eq!(job["kind"], "verify_blocks");
+
+    // The job fails fast due to connection refused
+    let mut status = String::new();
+    for _ in 0..50 {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/jobs/{}", id))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let job: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        status = job["status"].as_str().unwrap().to_string();
+        if status == "failed" {
+            assert!(job["error"].is_string());
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    assert_eq!(status, "failed");
+
+    let test_cases = vec![
+        (format!("/jobs/{}/result", id), StatusCode::CONFLICT),
+        ("/jobs/unknown".to_string(), StatusCode::NOT_FOUND),
+        ("/jobs/unknown/result".to_string(), StatusCode::NOT_FOUND),
+    ];
+
+    for (uri, expected_status) in test_cases {
+        let response = app
+            .clone()
+            .oneshot(Request::builder().uri(&uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            response.status(),
+            expected_status,
+            "URI '{}' should return status {}",
+            uri,
+            expected_status
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_finished_jobs_are_evicted() {
+    async fn finish_job(app: &Router) -> String {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/jobs")
+                    .header("content-type", "application/json")
+                    .body(Body::from(r#"{"from_block": 1, "to_block": 10}"#))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let job: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let id = job["id"].as_str().unwrap().to_string();
+
+        // The job fails fast due to connection refused
+        for _ in 0..50 {
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .uri(format!("/jobs/{}", id))
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            let job: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            if job["status"] == "failed" {
+                return id;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+        panic!("Job {} did not finish", id);
+    }
+
+    async fn job_status(app: &Router, id: &str) -> StatusCode {
+        app.clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/jobs/{}", id))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+            .status()
+    }
+
+    let app_with_jobs = |jobs: JobQueue| {
+        let mut state = Arc::into_inner(create_test_state()).unwrap();
+        state.jobs = Arc::new(jobs);
+        Router::new()
+            .route("/jobs", post(create_job))
+            .route("/jobs/{id}", get(get_job))
+            .with_state(Arc::new(state))
+    };
+
+    // Expired jobs are dropped once another job is queued
+    let app = app_with_jobs(JobQueue::new(1).with_retention(std::time::Duration::ZERO, 10));
+    let expired = finish_job(&app).await;
+    assert_eq!(job_status(&app, &expired).await, StatusCode::OK);
+    let latest = finish_job(&app).await;
+    assert_eq!(job_status(&app, &expired).await, StatusCode::NOT_FOUND);
+    assert_eq!(job_status(&app, &latest).await, StatusCode::OK);
+
+    // Past the capacity, the oldest finished jobs go first
+    let app =
+        app_with_jobs(JobQueue::new(1).with_retention(std::time::Duration::from_secs(3600), 1));
+    let oldest = finish_job(&app).await;
+    let older = finish_job(&app).await;
+    let latest = finish_job(&app).await;
+    assert_eq!(job_status(&app, &oldest).await, StatusCode::NOT_FOUND);
+    assert_eq!(job_status(&app, &older).await, StatusCode::OK);
+    assert_eq!(job_status(&app, &latest).await, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_proof_cache() {
+    let cache = ProofCache::open("sqlite::memory:", 10).await.unwrap();
+    let sampling = SamplingMode::default();
+    let image_id = [1u32; 8];
+    let roots = vec![(0, "0xaa".to_string())];
+
+    let key = ProofCacheKey::new(0, 100, &sampling, image_id, &roots);
+    assert_eq!(cache.get(&key).await.unwrap(), None);
+
+    cache.put(&key, &[1, 2, 3, 4]).await.unwrap();
+    assert_eq!(cache.get(&key).await.unwrap(), Some(vec![1, 2, 3, 4]));
+
+    // A different image id is a different entry
+    let other_method = ProofCacheKey::new(0, 100, &sampling, [2u32; 8], &roots);
+    assert_eq!(cache.get(&other_method).await.unwrap(), None);
+
+    // An updated on-chain root invalidates the entry
+    let updated = ProofCacheKey::new(0, 100, &sampling, image_id, &[(0, "0xbb".to_string())]);
+    assert_eq!(cache.get(&updated).await.unwrap(), None);
+    assert_eq!(cache.get(&key).await.unwrap(), None);
+    assert_eq!(cache.stats().await.unwrap(), (0, 0));
+
+    // Least recently used entries are evicted past the size budget
+    let first = ProofCacheKey::new(0, 10, &sampling, image_id, &roots);
+    let second = ProofCacheKey::new(0, 20, &sampling, image_id, &roots);
+    let third = ProofCacheKey::new(0, 30, &sampling, image_id, &roots);
+    cache.put(&first, &[0; 4]).await.unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+    cache.put(&second, &[0; 4]).await.unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+    cache.put(&third, &[0; 4]).await.unwrap();
+
+    assert_eq!(cache.stats().await.unwrap(), (2, 8));
+    assert_eq!(cache.get(&first).await.unwrap(), None);
+    assert!(cache.get(&third).await.unwrap().is_some());
+}
+
+#[test]
+fn test_response_format_negotiation() {
+    let negotiate = |accept: Option<&str>| {
+        let mut headers = axum::http::HeaderMap::new();
+        if let Some(accept) = accept {
+            headers.insert(axum::http::header::ACCEPT, accept.parse().unwrap());
+        }
+        ResponseFormat::negotiate(&headers)
+    };
+
+    assert_eq!(negotiate(None), Ok(ResponseFormat::Bincode));
+    assert_eq!(negotiate(Some("*/*")), Ok(ResponseFormat::Bincode));
+    assert_eq!(
+        negotiate(Some("application/octet-stream")),
+        Ok(ResponseFormat::Bincode)
+    );
+    assert_eq!(
+        negotiate(Some("application/json")),
+        Ok(ResponseFormat::Json)
+    );
+    assert_eq!(
+        negotiate(Some("application/cbor")),
+        Ok(ResponseFormat::Cbor)
+    );
+    assert_eq!(
+        negotiate(Some(
+            "text/html, application/cbor;q=0.5, application/json;q=0.9"
+        )),
+        Ok(ResponseFormat::Json)
+    );
+    assert_eq!(
+        negotiate(Some("application/json;q=0, */*;q=0.1")),
+        Ok(ResponseFormat::Bincode)
+    );
+    assert!(negotiate(Some("text/html")).is_err());
+}
+
+#[tokio::test]
+async fn test_structured_errors() {
+    let app = Router::new()
+        .route("/verify-blocks", get(verify_blocks))
+        .route("/block-proof/{block}", get(block_proof))
+        .with_state(create_test_state());
+
+    let test_cases = vec![
+        (
+            "/verify-blocks?from_block=1&to_block=10",
+            Some("text/html"),
+            StatusCode::NOT_ACCEPTABLE,
+            "not_acceptable",
+        ),
+        (
+            "/verify-blocks?from_block=1&to_block=10&sampling=daily",
+            Some("application/json"),
+            StatusCode::BAD_REQUEST,
+            "invalid_sampling",
+        ),
+        (
+            "/verify-blocks?from_block=abc&to_block=10",
+            None,
+            StatusCode::BAD_REQUEST,
+            "invalid_request",
+        ),
+        (
+            "/block-proof/latest",
+            None,
+            StatusCode::BAD_REQUEST,
+            "invalid_block_id",
+        ),
+    ];
+
+    for (uri, accept, expected_status, expected_code) in test_cases {
+        let mut request = Request::builder().uri(uri);
+        if let Some(accept) = accept {
+            request = request.header("accept", accept);
+        }
+        let response = app
+            .clone()
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), expected_status, "{}", uri);
+        assert_eq!(
+            response.headers()["content-type"],
+            "application/json",
+            "{}",
+            uri
+        );
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(error.code, expected_code, "{}", uri);
+        assert!(!error.error.is_empty());
+    }
+}
+
+#[tokio::test]
+async fn test_request_timeout() {
+    let app = Router::new()
+        .route(
+            "/slow",
+            get(|| async {
+                tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+                "done"
+            }),
+        )
+        .route("/fast", get(|| async { "done" }))
+        .layer(axum::middleware::from_fn_with_state(
+            std::time::Duration::from_millis(50),
+            middleware::request_timeout,
+        ));
+
+    let response = app
+        .clone()
+        .oneshot(Request::builder().uri("/fast").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app
+        .oneshot(Request::builder().uri("/slow").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::REQUEST_TIMEOUT);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error.code, "request_timeout");
+}
+
+#[tokio::test]
+async fn test_cors_layer() {
+    assert!(middleware::cors_layer(&["not\nan origin".to_string()]).is_err());
+
+    let app = Router::new()
+        .route("/verify-blocks", get(verify_blocks))
+        .with_state(create_test_state())
+        .layer(middleware::cors_layer(&["https://dashboard.example".to_string()]).unwrap());
+
+    let preflight = |origin: &str| {
+        Request::builder()
+            .method("OPTIONS")
+            .uri("/verify-blocks")
+            .header("origin", origin)
+            .header("access-control-request-method", "GET")
+            .header("access-control-request-headers", "x-api-key")
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    let response = app
+        .clone()
+        .oneshot(preflight("https://dashboard.example"))
+        .await
+        .unwrap();
+    assert_eq!(
+        response.headers()["access-control-allow-origin"],
+        "https://dashboard.example"
+    );
+    assert!(response.headers()["access-control-allow-headers"]
+        .to_str()
+        .unwrap()
+        .contains("x-api-key"));
+
+    let response = app
+        .oneshot(preflight("https://other.example"))
+        .await
+        .unwrap();
+    assert!(!response
+        .headers()
+        .contains_key("access-control-allow-origin"));
+}
+
+#[tokio::test]
+async fn test_api_key_auth_and_quotas() {
+    let config: AuthConfig = serde_json::from_str(
+        r#"{"keys": [
+            {"name": "dashboard", "key": "secret", "max_blocks_per_request": 10, "requests_per_minute": 2},
+            {"name": "ops", "key": "unlimited"}
+        ]}"#,
+    )
+    .unwrap();
+    let keys = Arc::new(ApiKeys::new(config).unwrap());
+
+    let app = Router::new()
+        .route("/verify-blocks", get(verify_blocks))
+        .with_state(create_test_state())
+        .layer(axum::middleware::from_fn_with_state(
+            keys,
+            auth::require_api_key,
+        ));
+
+    let test_cases = vec![
+        (
+            "/verify-blocks?from_block=1&to_block=5",
+            None,
+            StatusCode::UNAUTHORIZED,
+        ),
+        (
+            "/verify-blocks?from_block=1&to_block=5",
+            Some(("authorization", "Bearer wrong")),
+            StatusCode::UNAUTHORIZED,
+        ),
+        // Over the client's range quota, rejected before fetching anything
+        (
+            "/verify-blocks?from_block=1&to_block=20",
+            Some(("authorization", "Bearer secret")),
+            StatusCode::BAD_REQUEST,
+        ),
+        // Will fail fast due to connection refused
+        (
+            "/verify-blocks?from_block=1&to_block=5",
+            Some(("x-api-key", "secret")),
+            StatusCode::INTERNAL_SERVER_ERROR,
+        ),
+        // Third request within a minute
+        (
+            "/verify-blocks?from_block=1&to_block=5",
+            Some(("x-api-key", "secret")),
+            StatusCode::TOO_MANY_REQUESTS,
+        ),
+        (
+            "/verify-blocks?from_block=1&to_block=20",
+            Some(("x-api-key", "unlimited")),
+            StatusCode::INTERNAL_SERVER_ERROR,
+        ),
+    ];
+
+    for (uri, key, expected_status) in test_cases {
+        let mut request = Request::builder().uri(uri);
+        if let Some((name, value)) = key {
+            request = request.header(name, value);
+        }
+        let response = app
+            .clone()
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), expected_status, "{} {:?}", uri, key);
+        if expected_status == StatusCode::TOO_MANY_REQUESTS {
+            assert!(response.headers().contains_key("retry-after"));
+        }
+    }
+
+    assert!(ApiKeys::new(
+        serde_json::from_str(r#"{"keys": [{"name": "a", "key": "k"}, {"name": "b", "key": "k"}]}"#)
+            .unwrap()
+    )
+    .is_err());
+}
+
+#[tokio::test]
+async fn test_max_blocks_per_request() {
+    let mut state = (*create_test_state()).clone();
+    state.max_blocks_per_request = Some(5);
+
+    let app = Router::new()
+        .route("/verify-blocks", get(verify_blocks))
+        .route("/jobs", post(create_job))
+        .with_state(Arc::new(state));
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/verify-blocks?from_block=1&to_block=6")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error.code, "range_too_large");
+
+    // The widest range must not wrap around to an empty one
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/verify-blocks?from_block=0&to_block=18446744073709551615")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error.code, "range_too_large");
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/verify-blocks?from_block=6&to_block=1")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error.code, "invalid_block_range");
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/jobs")
+                .header("content-type", "application/json")
+                .body(Body::from(r#"{"from_block": 1, "to_block": 6}"#))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_batch_endpoints() {
+    let app = Router::new()
+        .route("/batches", get(list_batches))
+        .route("/batches/{index}", get(get_batch))
+        .with_state(create_test_state());
+
+    let test_cases = vec![
+        // Will fail fast due to connection refused
+        ("/batches", StatusCode::INTERNAL_SERVER_ERROR),
+        ("/batches/3", StatusCode::INTERNAL_SERVER_ERROR),
+        // Invalid batch index
+        ("/batches/latest", StatusCode::BAD_REQUEST),
+        ("/batches/-1", StatusCode::BAD_REQUEST),
+    ];
+
+    for (uri, expected_status) in test_cases {
+        let response = app
+            .clone()
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            response.status(),
+            expected_status,
+            "URI '{}' should return status {}",
+            uri,
+            expected_status
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_job_events_stream() {
+    let app = Router::new()
+        .route("/jobs", post(create_job))
+        .route("/jobs/{id}/events", get(job_events))
+        .with_state(create_test_state());
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/jobs")
+                .header("content-type", "application/json")
+                .body(Body::from(r#"{"from_block": 1, "to_block": 10}"#))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let job: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let id = job["id"].as_str().unwrap().to_string();
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(format!("/jobs/{}/events", id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "text/event-stream");
+
+    // The stream ends once the job fails due to connection refused
+    let body = tokio::time::timeout(
+        std::time::Duration::from_secs(10),
+        axum::body::to_bytes(response.into_body(), usize::MAX),
+    )
+    .await
+    .expect("event stream should end with the job")
+    .unwrap();
+    let body = String::from_utf8(body.to_vec()).unwrap();
+
+    let events: Vec<serde_json::Value> = body
+        .lines()
+        .filter_map(|line| line.strip_prefix("data: "))
+        .map(|data| serde_json::from_str(data).unwrap())
+        .collect();
+    assert!(body.contains("event: progress"));
+    assert_eq!(events.last().unwrap()["status"], "failed");
+    assert!(events.iter().all(|event| event["id"] == id.as_str()));
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/jobs/unknown/events")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_openapi_document_matches_router() {
+    let app = router(create_test_state());
+    let expected = serde_json::to_value(openapi()).unwrap();
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/openapi.json")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let served: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(served, expected);
+
+    let paths = expected["paths"].as_object().unwrap();
+    for path in [
+        "/verify-blocks",
+        "/verify-blocks/aggregated",
+        "/block-proof/{block}",
+        "/batches",
+        "/batches/{index}",
+        "/jobs",
+        "/jobs/{id}",
+        "/jobs/{id}/result",
+        "/jobs/{id}/events",
+    ] {
+        assert!(paths.contains_key(path), "{} is not documented", path);
+    }
+
+    // Every documented operation must reach a handler: the router answers
+    // unknown paths with an empty 404 and unknown methods with a 405
+    for (path, item) in paths {
+        let uri = path
+            .replace("{block}", "1")
+            .replace("{index}", "0")
+            .replace("{id}", "unknown");
+        for method in item.as_object().unwrap().keys() {
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .method(method.to_uppercase().as_str())
+                        .uri(&uri)
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+
+            assert_ne!(
+                response.status(),
+                StatusCode::METHOD_NOT_ALLOWED,
+                "{} {} is documented but not routed",
+                method,
+                path
+            );
+            if response.status() == StatusCode::NOT_FOUND {
+                assert_eq!(
+                    response.headers()["content-type"],
+                    "application/json",
+                    "{} {} is documented but not routed",
+                    method,
+                    path
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,390 @@
+use std::collections::HashSet;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use api_types::Stark;
+use guest_types::{AggregatedFeeJournal, BatchRoot, BlockFeeRecord, FeeProofJournal};
+use methods::{AGGREGATE_FEE_PROOFS_ID, VALIDATE_BLOCKS_AND_EXTRACT_FEES_ID};
+use risc0_zkvm::sha::Digest;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::ClientError;
+use crate::verify::{verify_aggregated_fee_proof, verify_fee_proof, AggregatedFeeProof, FeeProof};
+
+/// Version of the [`ProofBundle`] layout.
+pub const PROOF_BUNDLE_VERSION: u32 = 1;
+
+/// Which proofs a bundle holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BundleKind {
+    /// One fee proof per batch
+    FeeProofs,
+    /// A single proof aggregating the fee proofs of every batch
+    AggregatedFeeProof,
+}
+
+impl BundleKind {
+    /// Image id the receipts of the bundle must verify against.
+    pub fn image_id(&self) -> [u32; 8] {
+        match self {
+            Self::FeeProofs => VALIDATE_BLOCKS_AND_EXTRACT_FEES_ID,
+            Self::AggregatedFeeProof => AGGREGATE_FEE_PROOFS_ID,
+        }
+    }
+}
+
+/// Fee proofs saved for later, offline verification.
+///
+/// Stored as JSON: the metadata is readable without decoding the receipts,
+/// which are bincode-encoded `Stark` values in hex. The metadata is checked
+/// against the receipt journals when the bundle is verified.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofBundle {
+    pub version: u32,
+    pub kind: BundleKind,
+    /// Image id of the guest that produced the receipts, `0x`-prefixed
+    pub image_id: String,
+    /// First and last block covered by the proofs
+    pub block_range: (u64, u64),
+    /// Block sampling mode the proofs were requested with
+    pub sampling: Option<String>,
+    /// MMR each batch was proven against
+    pub batches: Vec<BatchRoot>,
+    /// Unix timestamp, in seconds
+    pub created_at: u64,
+    pub receipts: Vec<String>,
+}
+
+/// Result of verifying a [`ProofBundle`].
+#[derive(Debug, Clone)]
+pub struct VerifiedBundle {
+    pub block_range: (u64, u64),
+    pub batches: Vec<BatchRoot>,
+    /// Fees of every sampled block, in block order
+    pub fees: Vec<BlockFeeRecord>,
+}
+
+impl ProofBundle {
+    pub fn from_fee_proofs(
+        proofs: &[FeeProof],
+        sampling: Option<String>,
+    ) -> Result<Self, ClientError> {
+        let (first, last) = match (proofs.first(), proofs.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return Err(ClientError::Bundle("no fee proofs to bundle".to_string())),
+        };
+        let batches = proofs
+            .iter()
+            .map(|proof| BatchRoot {
+                batch_index: proof.journal.batch_index,
+                mmr_root: proof.journal.mmr_root.clone(),
+                elements_count: proof.journal.elements_count,
+            })
+            .collect();
+        let receipts = proofs
+            .iter()
+            .map(|proof| encode_receipt(&proof.stark))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self::new(
+            BundleKind::FeeProofs,
+            (first.journal.block_range.0, last.journal.block_range.1),
+            sampling,
+            batches,
+            receipts,
+        ))
+    }
+
+    pub fn from_aggregated_fee_proof(
+        proof: &AggregatedFeeProof,
+        sampling: Option<String>,
+    ) -> Result<Self, ClientError> {
+        Ok(Self::new(
+            BundleKind::AggregatedFeeProof,
+            proof.journal.block_range,
+            sampling,
+            proof.journal.batches.clone(),
+            vec![encode_receipt(&proof.stark)?],
+        ))
+    }
+
+    fn new(
+        kind: BundleKind,
+        block_range: (u64, u64),
+        sampling: Option<String>,
+        batches: Vec<BatchRoot>,
+        receipts: Vec<String>,
+    ) -> Self {
+        Self {
+            version: PROOF_BUNDLE_VERSION,
+            kind,
+            image_id: image_id_hex(kind.image_id()),
+            block_range,
+            sampling,
+            batches,
+            created_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            receipts,
+        }
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ClientError> {
+        let json = serde_json::to_vec_pretty(self)?;
+        std::fs::write(path.as_ref(), json).map_err(|e| {
+            ClientError::Bundle(format!(
+                "failed to write {}: {}",
+                path.as_ref().display(),
+                e
+            ))
+        })
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ClientError> {
+        let json = std::fs::read(path.as_ref()).map_err(|e| {
+            ClientError::Bundle(format!("failed to read {}: {}", path.as_ref().display(), e))
+        })?;
+        Ok(serde_json::from_slice(&json)?)
+    }
+
+    /// Verifies every receipt against the image id of the bundle kind, and
+    /// that the metadata of the bundle is the one committed by the receipts.
+    /// Checking the MMR roots against the Fossil Store is left to the caller.
+    pub fn verify(&self) -> Result<VerifiedBundle, ClientError> {
+        if self.version != PROOF_BUNDLE_VERSION {
+            return Err(ClientError::Bundle(format!(
+                "unsupported bundle version {}, expected {}",
+                self.version, PROOF_BUNDLE_VERSION
+            )));
+        }
+        if self.image_id != image_id_hex(self.kind.image_id()) {
+            return Err(ClientError::Bundle(format!(
+                "image id {} is not the one of {:?} bundles",
+                self.image_id, self.kind
+            )));
+        }
+
+        let mut seen = HashSet::new();
+        if let Some(index) = self
+            .receipts
+            .iter()
+            .position(|receipt| !seen.insert(receipt.trim_start_matches("0x")))
+        {
+            return Err(ClientError::Bundle(format!(
+                "receipt {} repeats an earlier receipt",
+                index
+            )));
+        }
+
+        let starks = self
+            .receipts
+            .iter()
+            .map(|receipt| decode_receipt(receipt))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let (block_range, batches, mut fees) = match self.kind {
+            BundleKind::FeeProofs => {
+                let proofs = starks
+                    .into_iter()
+                    .map(verify_fee_proof)
+                    .collect::<Result<Vec<_>, _>>()?;
+                if let Some(proof) = proofs.iter().find(|proof| !proof.journal.ok) {
+                    return Err(ClientError::Verification(format!(
+                        "proof of batch {} attests blocks {} to {} are invalid",
+                        proof.journal.batch_index,
+                        proof.journal.block_range.0,
+                        proof.journal.block_range.1
+                    )));
+                }
+                let journals: Vec<_> = proofs.into_iter().map(|proof| proof.journal).collect();
+                let journal = combine_fee_journals(&journals)?;
+                (journal.block_range, journal.batches, journal.fees)
+            }
+            BundleKind::AggregatedFeeProof => {
+                let [stark] = <[Stark; 1]>::try_from(starks).map_err(|starks| {
+                    ClientError::Bundle(format!(
+                        "aggregated bundles hold a single receipt, found {}",
+                        starks.len()
+                    ))
+                })?;
+                let journal = verify_aggregated_fee_proof(stark)?.journal;
+                (journal.block_range, journal.batches, journal.fees)
+            }
+        };
+
+        if block_range != self.block_range {
+            return Err(ClientError::Bundle(format!(
+                "receipts prove blocks {} to {}, the bundle claims {} to {}",
+                block_range.0, block_range.1, self.block_range.0, self.block_range.1
+            )));
+        }
+        if batches != self.batches {
+            return Err(ClientError::Bundle(
+                "batch MMR roots of the bundle differ from the ones proven".to_string(),
+            ));
+        }
+
+        fees.sort_by_key(|record| record.block_number);
+        Ok(VerifiedBundle {
+            block_range,
+            batches,
+            fees,
+        })
+    }
+}
+
+/// Combines per-batch journals the way the aggregation guest does, so that
+/// batches must be strictly increasing and cover non-overlapping blocks.
+fn combine_fee_journals(journals: &[FeeProofJournal]) -> Result<AggregatedFeeJournal, ClientError> {
+    AggregatedFeeJournal::aggregate(VALIDATE_BLOCKS_AND_EXTRACT_FEES_ID, journals)
+        .map_err(ClientError::Verification)
+}
+
+fn image_id_hex(image_id: [u32; 8]) -> String {
+    format!("0x{}", Digest::from(image_id))
+}
+
+fn encode_receipt(stark: &Stark) -> Result<String, ClientError> {
+    Ok(hex::encode(bincode::serialize(stark)?))
+}
+
+fn decode_receipt(receipt: &str) -> Result<Stark, ClientError> {
+    let bytes = hex::decode(receipt.trim_start_matches("0x"))
+        .map_err(|e| ClientError::Bundle(format!("receipt is not hex: {}", e)))?;
+    Ok(bincode::deserialize(&bytes)?)
+}
+
+/// Writes fee records as CSV, with empty cells for the blob fields of
+/// pre-Cancun blocks.
+pub fn fees_to_csv(fees: &[BlockFeeRecord]) -> String {
+    let mut csv = String::from(
+        "block_number,timestamp,base_fee_per_gas,gas_used,gas_limit,blob_gas_used,excess_blob_gas\n",
+    );
+    let optional = |value: Option<u64>| value.map(|value| value.to_string()).unwrap_or_default();
+    for record in fees {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{}\n",
+            record.block_number,
+            record.timestamp,
+            record.base_fee_per_gas,
+            record.gas_used,
+            record.gas_limit,
+            optional(record.blob_gas_used),
+            optional(record.excess_blob_gas)
+        ));
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(block_number: u64, blob_gas_used: Option<u64>) -> BlockFeeRecord {
+        BlockFeeRecord {
+            block_number,
+            timestamp: 1_700_000_000 + block_number,
+            base_fee_per_gas: 7,
+            gas_used: 15_000_000,
+            gas_limit: 30_000_000,
+            blob_gas_used,
+            excess_blob_gas: blob_gas_used.map(|_| 0),
+        }
+    }
+
+    fn bundle(kind: BundleKind) -> ProofBundle {
+        ProofBundle::new(
+            kind,
+            (1024, 2047),
+            None,
+            vec![BatchRoot {
+                batch_index: 1,
+                mmr_root: "0x01".to_string(),
+                elements_count: 2047,
+            }],
+            Vec::new(),
+        )
+    }
+
+    #[test]
+    fn test_fees_to_csv() {
+        let csv = fees_to_csv(&[record(1, None), record(2, Some(131072))]);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1], "1,1700000001,7,15000000,30000000,,");
+        assert_eq!(lines[2], "2,1700000002,7,15000000,30000000,131072,0");
+    }
+
+    #[test]
+    fn test_bundle_round_trip() {
+        let bundle = bundle(BundleKind::FeeProofs);
+        let path = std::env::temp_dir().join(format!("bundle-{}.json", std::process::id()));
+        bundle.save(&path).unwrap();
+        let loaded = ProofBundle::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, bundle);
+    }
+
+    #[test]
+    fn test_verify_rejects_tampered_metadata() {
+        let mut tampered = bundle(BundleKind::FeeProofs);
+        tampered.version = PROOF_BUNDLE_VERSION + 1;
+        assert!(matches!(tampered.verify(), Err(ClientError::Bundle(_))));
+
+        let mut tampered = bundle(BundleKind::AggregatedFeeProof);
+        tampered.image_id = image_id_hex(VALIDATE_BLOCKS_AND_EXTRACT_FEES_ID);
+        assert!(matches!(tampered.verify(), Err(ClientError::Bundle(_))));
+
+        let mut tampered = bundle(BundleKind::FeeProofs);
+        tampered.receipts = vec!["not hex".to_string()];
+        assert!(matches!(tampered.verify(), Err(ClientError::Bundle(_))));
+
+        // An aggregated bundle without its receipt
+        assert!(matches!(
+            bundle(BundleKind::AggregatedFeeProof).verify(),
+            Err(ClientError::Bundle(_))
+        ));
+    }
+
+    #[test]
+    fn test_verify_rejects_duplicated_receipts() {
+        let mut duplicated = bundle(BundleKind::FeeProofs);
+        duplicated.receipts = vec!["0xabcd".to_string(), "abcd".to_string()];
+        let Err(ClientError::Bundle(error)) = duplicated.verify() else {
+            panic!("duplicated receipts must be rejected");
+        };
+        assert!(error.contains("receipt 1 repeats"), "{}", error);
+    }
+
+    #[test]
+    fn test_combine_fee_journals_checks_order() {
+        let journal = |batch_index: u64, block_range: (u64, u64)| {
+            FeeProofJournal::valid(
+                "0x01".to_string(),
+                2047,
+                batch_index,
+                block_range,
+                vec![record(block_range.0, None)],
+            )
+        };
+        let first = journal(1, (1024, 2047));
+        let second = journal(2, (2048, 3071));
+
+        let combined = combine_fee_journals(&[first.clone(), second.clone()]).unwrap();
+        assert_eq!(combined.block_range, (1024, 3071));
+        assert_eq!(combined.fees.len(), 2);
+
+        for journals in [
+            vec![first.clone(), first.clone()],
+            vec![second.clone(), first.clone()],
+            vec![first, journal(2, (2000, 3071))],
+        ] {
+            assert!(matches!(
+                combine_fee_journals(&journals),
+                Err(ClientError::Verification(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,501 @@
+use std::time::Duration;
+
+use api_types::{BatchInfo, BatchListing, BlockId, BlockInclusionProof, Stark};
+use futures_util::{stream, Stream, StreamExt};
+use reqwest::{header, Method, RequestBuilder, Response, StatusCode, Url};
+use serde::de::DeserializeOwned;
+use tracing::{debug, warn};
+
+use crate::errors::ClientError;
+use crate::types::{ErrorResponse, JobInfo, JobKind, ProofRequest};
+use crate::verify::{
+    decode_aggregated_fee_proof, decode_fee_proof, verify_aggregated_fee_proof, verify_fee_proof,
+    AggregatedFeeProof, FeeProof,
+};
+
+const OCTET_STREAM: &str = "application/octet-stream";
+const EVENT_STREAM: &str = "text/event-stream";
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How failed requests are retried, with exponential backoff between attempts.
+///
+/// Connection failures and `429 Too Many Requests` are retried for every
+/// request. Timeouts and `502`, `503` and `504` responses are only retried for
+/// requests that are safe to repeat, i.e. not for `POST /jobs`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn none() -> Self {
+        Self {
+            max_retries: 0,
+            ..Default::default()
+        }
+    }
+
+    fn backoff(&self, attempt: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_backoff)
+    }
+}
+
+/// Async client of the state proof API.
+///
+/// Proofs are requested as bincode and, unless disabled with
+/// [`StateProofClient::with_receipt_verification`], their receipts are
+/// verified against the image ids of the fee guests before being returned.
+#[derive(Debug, Clone)]
+pub struct StateProofClient {
+    http: reqwest::Client,
+    base_url: Url,
+    api_key: Option<String>,
+    timeout: Option<Duration>,
+    retry: RetryPolicy,
+    verify_receipts: bool,
+}
+
+impl StateProofClient {
+    pub fn new(base_url: &str) -> Result<Self, ClientError> {
+        let base_url = Url::parse(base_url.trim_end_matches('/'))
+            .map_err(|e| ClientError::InvalidUrl(format!("{}: {}", base_url, e)))?;
+        if base_url.cannot_be_a_base() {
+            return Err(ClientError::InvalidUrl(base_url.to_string()));
+        }
+        let http = reqwest::Client::builder()
+            .connect_timeout(CONNECT_TIMEOUT)
+            .build()?;
+
+        Ok(Self {
+            http,
+            base_url,
+            api_key: None,
+            timeout: None,
+            retry: RetryPolicy::default(),
+            verify_receipts: true,
+        })
+    }
+
+    /// Sends the key as `Authorization: Bearer <key>`
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    /// Limits how long each attempt of a request may take. Synchronous proofs
+    /// of long ranges can take minutes; prefer jobs over a short timeout.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Only decode the journals of returned proofs, e.g. against a server
+    /// proving in RISC Zero dev mode
+    pub fn with_receipt_verification(mut self, verify_receipts: bool) -> Self {
+        self.verify_receipts = verify_receipts;
+        self
+    }
+
+    /// `GET /verify-blocks`: one fee proof per batch of the range.
+    pub async fn verify_blocks(
+        &self,
+        request: &ProofRequest,
+    ) -> Result<Vec<FeeProof>, ClientError> {
+        let query = request.query();
+        let bytes = self
+            .bytes(|| self.request(Method::GET, "verify-blocks").query(&query))
+            .await?;
+        self.fee_proofs(&bytes)
+    }
+
+    /// `GET /verify-blocks/aggregated`: a single proof of the whole range.
+    pub async fn verify_blocks_aggregated(
+        &self,
+        request: &ProofRequest,
+    ) -> Result<AggregatedFeeProof, ClientError> {
+        let query = request.query();
+        let bytes = self
+            .bytes(|| {
+                self.request(Method::GET, "verify-blocks/aggregated")
+                    .query(&query)
+            })
+            .await?;
+        self.aggregated_fee_proof(&bytes)
+    }
+
+    /// `GET /block-proof/{block}`: MMR inclusion proof of a block.
+    pub async fn block_proof(&self, block: &BlockId) -> Result<BlockInclusionProof, ClientError> {
+        let path = format!("block-proof/{}", block);
+        self.json(|| self.request(Method::GET, &path)).await
+    }
+
+    /// `GET /batches`: batches of the Fossil Store and the server's databases.
+    pub async fn list_batches(&self) -> Result<BatchListing, ClientError> {
+        self.json(|| self.request(Method::GET, "batches")).await
+    }
+
+    /// `GET /batches/{index}`
+    pub async fn get_batch(&self, batch_index: u64) -> Result<BatchInfo, ClientError> {
+        let path = format!("batches/{}", batch_index);
+        self.json(|| self.request(Method::GET, &path)).await
+    }
+
+    /// `POST /jobs`: queues the proof of `request` and returns right away.
+    pub async fn create_job(
+        &self,
+        request: &ProofRequest,
+        kind: JobKind,
+    ) -> Result<JobInfo, ClientError> {
+        let body = request.job(kind);
+        let response = self
+            .send(|| self.request(Method::POST, "jobs").json(&body), false)
+            .await?;
+        Ok(response.json().await?)
+    }
+
+    /// `GET /jobs/{id}`
+    pub async fn get_job(&self, id: &str) -> Result<JobInfo, ClientError> {
+        let path = format!("jobs/{}", id);
+        self.json(|| self.request(Method::GET, &path)).await
+    }
+
+    /// Polls a job until it is done or failed.
+    pub async fn wait_for_job(
+        &self,
+        id: &str,
+        poll_interval: Duration,
+    ) -> Result<JobInfo, ClientError> {
+        loop {
+            let job = self.get_job(id).await?;
+            if job.is_finished() {
+                return Ok(job);
+            }
+            debug!(id, status = ?job.status, stage = ?job.stage, "Waiting for job");
+            tokio::time::sleep(poll_interval).await;
+        }
+    }
+
+    /// `GET /jobs/{id}/result` of a [`JobKind::VerifyBlocks`] job.
+    pub async fn job_fee_proofs(&self, id: &str) -> Result<Vec<FeeProof>, ClientError> {
+        let path = format!("jobs/{}/result", id);
+        let bytes = self.bytes(|| self.request(Method::GET, &path)).await?;
+        self.fee_proofs(&bytes)
+    }
+
+    /// `GET /jobs/{id}/result` of a [`JobKind::VerifyBlocksAggregated`] job.
+    pub async fn job_aggregated_fee_proof(
+        &self,
+        id: &str,
+    ) -> Result<AggregatedFeeProof, ClientError> {
+        let path = format!("jobs/{}/result", id);
+        let bytes = self.bytes(|| self.request(Method::GET, &path)).await?;
+        self.aggregated_fee_proof(&bytes)
+    }
+
+    /// `GET /jobs/{id}/events`: every change of the job until it finishes.
+    pub async fn job_events(
+        &self,
+        id: &str,
+    ) -> Result<impl Stream<Item = Result<JobInfo, ClientError>>, ClientError> {
+        let url = self.url(&format!("jobs/{}/events", id));
+        // No timeout: the stream lasts as long as the job
+        let response = self
+            .send(
+                || {
+                    self.authorized(self.http.get(url.clone()))
+                        .header(header::ACCEPT, EVENT_STREAM)
+                },
+                true,
+            )
+            .await?;
+
+        let chunks = Box::pin(response.bytes_stream());
+        Ok(stream::try_unfold(
+            (chunks, Vec::new()),
+            |(mut chunks, mut buffer)| async move {
+                loop {
+                    if let Some(data) = take_event(&mut buffer) {
+                        let job = serde_json::from_str::<JobInfo>(&data)?;
+                        return Ok::<_, ClientError>(Some((job, (chunks, buffer))));
+                    }
+                    match chunks.next().await {
+                        Some(chunk) => buffer.extend_from_slice(&chunk?),
+                        None => return Ok(None),
+                    }
+                }
+            },
+        ))
+    }
+
+    /// `GET /openapi.json`
+    pub async fn openapi(&self) -> Result<serde_json::Value, ClientError> {
+        self.json(|| self.request(Method::GET, "openapi.json"))
+            .await
+    }
+
+    fn fee_proofs(&self, bytes: &[u8]) -> Result<Vec<FeeProof>, ClientError> {
+        let starks = bincode::deserialize::<Vec<Stark>>(bytes)?;
+        debug!("Received {} fee proofs", starks.len());
+        starks
+            .into_iter()
+            .map(|stark| match self.verify_receipts {
+                true => verify_fee_proof(stark),
+                false => decode_fee_proof(stark),
+            })
+            .collect()
+    }
+
+    fn aggregated_fee_proof(&self, bytes: &[u8]) -> Result<AggregatedFeeProof, ClientError> {
+        let stark = bincode::deserialize::<Stark>(bytes)?;
+        match self.verify_receipts {
+            true => verify_aggregated_fee_proof(stark),
+            false => decode_aggregated_fee_proof(stark),
+        }
+    }
+
+    fn url(&self, path: &str) -> Url {
+        let mut url = self.base_url.clone();
+        url.set_path(&format!(
+            "{}/{}",
+            self.base_url.path().trim_end_matches('/'),
+            path
+        ));
+        url
+    }
+
+    fn authorized(&self, request: RequestBuilder) -> RequestBuilder {
+        match &self.api_key {
+            Some(api_key) => request.bearer_auth(api_key),
+            None => request,
+        }
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let request = self.authorized(self.http.request(method, self.url(path)));
+        match self.timeout {
+            Some(timeout) => request.timeout(timeout),
+            None => request,
+        }
+    }
+
+    async fn json<T: DeserializeOwned>(
+        &self,
+        request: impl Fn() -> RequestBuilder,
+    ) -> Result<T, ClientError> {
+        let response = self.send(request, true).await?;
+        Ok(response.json().await?)
+    }
+
+    async fn bytes(&self, request: impl Fn() -> RequestBuilder) -> Result<Vec<u8>, ClientError> {
+        let response = self
+            .send(|| request().header(header::ACCEPT, OCTET_STREAM), true)
+            .await?;
+        let bytes = response.bytes().await?;
+        debug!("Received {} bytes from API", bytes.len());
+        Ok(bytes.to_vec())
+    }
+
+    /// Sends the request built by `request`, retrying it according to the
+    /// retry policy. `idempotent` requests are also retried on timeouts and
+    /// gateway errors.
+    async fn send(
+        &self,
+        request: impl Fn() -> RequestBuilder,
+        idempotent: bool,
+    ) -> Result<Response, ClientError> {
+        let mut attempt = 0;
+        loop {
+            let retry_after = match request().send().await {
+                Ok(response) if response.status().is_success() => return Ok(response),
+                Ok(response) => {
+                    let status = response.status();
+                    let retryable = status == StatusCode::TOO_MANY_REQUESTS
+                        || (idempotent
+                            && matches!(
+                                status,
+                                StatusCode::BAD_GATEWAY
+                                    | StatusCode::SERVICE_UNAVAILABLE
+                                    | StatusCode::GATEWAY_TIMEOUT
+                            ));
+                    if !retryable || attempt >= self.retry.max_retries {
+                        return Err(api_error(response).await);
+                    }
+                    warn!(status = %status, url = %response.url(), "Request failed, retrying...");
+                    retry_after(&response)
+                }
+                Err(e) => {
+                    let retryable = e.is_connect() || (idempotent && e.is_timeout());
+                    if !retryable || attempt >= self.retry.max_retries {
+                        return Err(e.into());
+                    }
+                    warn!(error = %e, "Request failed, retrying...");
+                    None
+                }
+            };
+
+            let backoff = retry_after.unwrap_or_else(|| self.retry.backoff(attempt));
+            debug!(attempt = attempt + 1, retry_in = ?backoff, "Backing off");
+            tokio::time::sleep(backoff).await;
+            attempt += 1;
+        }
+    }
+}
+
+fn retry_after(response: &Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .parse()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+async fn api_error(response: Response) -> ClientError {
+    let status = response.status();
+    let body = match response.text().await {
+        Ok(body) => body,
+        Err(e) => return e.into(),
+    };
+    // Routes and rejections outside the API's handlers answer in plain text
+    let (code, message) = match serde_json::from_str::<ErrorResponse>(&body) {
+        Ok(error) => (error.code, error.error),
+        Err(_) => ("http_error".to_string(), body),
+    };
+    ClientError::Api {
+        status: status.as_u16(),
+        code,
+        message,
+    }
+}
+
+/// Removes the first complete server-sent event from `buffer`, returning its
+/// data. Events without data, such as keep-alive comments, are skipped.
+fn take_event(buffer: &mut Vec<u8>) -> Option<String> {
+    loop {
+        let end = buffer.windows(2).position(|window| window == b"\n\n")?;
+        let event: Vec<u8> = buffer.drain(..end + 2).collect();
+        let event = String::from_utf8_lossy(&event);
+
+        let data: Vec<&str> = event
+            .lines()
+            .filter_map(|line| line.strip_prefix("data:"))
+            .map(|data| data.strip_prefix(' ').unwrap_or(data))
+            .collect();
+        if !data.is_empty() {
+            return Some(data.join("\n"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_backoff() {
+        let retry = RetryPolicy {
+            max_retries: 10,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(1),
+        };
+        assert_eq!(retry.backoff(0), Duration::from_millis(100));
+        assert_eq!(retry.backoff(2), Duration::from_millis(400));
+        assert_eq!(retry.backoff(4), Duration::from_secs(1));
+        assert_eq!(retry.backoff(40), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_take_event() {
+        let mut buffer =
+            b": keep-alive\n\nevent: progress\ndata: {\"id\":\"a\"}\n\nevent: progress\ndata: {"
+                .to_vec();
+        assert_eq!(take_event(&mut buffer).as_deref(), Some("{\"id\":\"a\"}"));
+        // The second event is incomplete
+        assert_eq!(take_event(&mut buffer), None);
+
+        buffer.extend_from_slice(b"\"id\":\"b\"}\n\n");
+        assert_eq!(take_event(&mut buffer).as_deref(), Some("{\"id\":\"b\"}"));
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn test_urls() {
+        let client = StateProofClient::new("http://127.0.0.1:3000/").unwrap();
+        assert_eq!(
+            client.url("verify-blocks").as_str(),
+            "http://127.0.0.1:3000/verify-blocks"
+        );
+
+        let client = StateProofClient::new("https://example.com/state-proof").unwrap();
+        assert_eq!(
+            client.url("jobs/abc/result").as_str(),
+            "https://example.com/state-proof/jobs/abc/result"
+        );
+
+        assert!(StateProofClient::new("not a url").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_retries_rate_limited_requests() {
+        use axum::{http::StatusCode as AxumStatus, routing::get, Json, Router};
+        use std::sync::atomic::{AtomicU32, Ordering};
+        use std::sync::Arc;
+
+        let calls = Arc::new(AtomicU32::new(0));
+        let counter = calls.clone();
+        let app = Router::new().route(
+            "/batches/{index}",
+            get(move || {
+                let counter = counter.clone();
+                async move {
+                    let (status, code) = match counter.fetch_add(1, Ordering::SeqCst) {
+                        0 => (AxumStatus::TOO_MANY_REQUESTS, "rate_limited"),
+                        _ => (AxumStatus::NOT_FOUND, "batch_not_found"),
+                    };
+                    (
+                        status,
+                        [("retry-after", "0")],
+                        Json(serde_json::json!({"code": code, "error": "test"})),
+                    )
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let client = StateProofClient::new(&format!("http://{}", addr))
+            .unwrap()
+            .with_retry_policy(RetryPolicy {
+                max_retries: 2,
+                initial_backoff: Duration::from_millis(10),
+                max_backoff: Duration::from_millis(10),
+            });
+
+        // The 429 is retried, the 404 is returned with its code
+        let error = client.get_batch(3).await.unwrap_err();
+        assert_eq!(error.code(), Some("batch_not_found"));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+}
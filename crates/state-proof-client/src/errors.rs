@@ -0,0 +1,46 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ClientError {
+    #[error("Invalid API URL {0}")]
+    InvalidUrl(String),
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+    /// Coded error returned by the API, see `ErrorResponse` of the server
+    #[error("API error {status} ({code}): {message}")]
+    Api {
+        status: u16,
+        code: String,
+        message: String,
+    },
+    #[error("Failed to decode response: {0}")]
+    Decode(String),
+    #[error("Receipt verification failed: {0}")]
+    Verification(String),
+    #[error("Unsupported journal version {actual}, expected {expected}")]
+    JournalVersion { expected: u32, actual: u32 },
+    #[error("Invalid proof bundle: {0}")]
+    Bundle(String),
+}
+
+impl ClientError {
+    /// Code of an API error, e.g. `missing_blocks` or `rate_limited`.
+    pub fn code(&self) -> Option<&str> {
+        match self {
+            Self::Api { code, .. } => Some(code),
+            _ => None,
+        }
+    }
+}
+
+impl From<bincode::Error> for ClientError {
+    fn from(error: bincode::Error) -> Self {
+        Self::Decode(error.to_string())
+    }
+}
+
+impl From<serde_json::Error> for ClientError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Decode(error.to_string())
+    }
+}
@@ -0,0 +1,11 @@
+pub mod bundle;
+pub mod client;
+pub mod errors;
+pub mod types;
+pub mod verify;
+
+pub use bundle::{fees_to_csv, BundleKind, ProofBundle, VerifiedBundle, PROOF_BUNDLE_VERSION};
+pub use client::{RetryPolicy, StateProofClient};
+pub use errors::ClientError;
+pub use types::{JobInfo, JobKind, JobProgress, JobRequest, JobStatus, ProofRequest};
+pub use verify::{verify_aggregated_fee_proof, verify_fee_proof, AggregatedFeeProof, FeeProof};
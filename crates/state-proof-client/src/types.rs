@@ -0,0 +1,57 @@
+use api_types::SamplingMode;
+pub use api_types::{ErrorResponse, JobInfo, JobKind, JobProgress, JobRequest, JobStatus};
+
+/// Block range and options of a proof request.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProofRequest {
+    pub from_block: u64,
+    pub to_block: u64,
+    /// Overrides the server's skip_proof_verification default
+    pub skip_proof_verification: Option<bool>,
+    /// Defaults to one block per hour on the server
+    pub sampling: Option<SamplingMode>,
+}
+
+impl ProofRequest {
+    pub fn new(from_block: u64, to_block: u64) -> Self {
+        Self {
+            from_block,
+            to_block,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_skip_proof_verification(mut self, skip: bool) -> Self {
+        self.skip_proof_verification = Some(skip);
+        self
+    }
+
+    pub fn with_sampling(mut self, sampling: SamplingMode) -> Self {
+        self.sampling = Some(sampling);
+        self
+    }
+
+    pub(crate) fn query(&self) -> Vec<(&'static str, String)> {
+        let mut query = vec![
+            ("from_block", self.from_block.to_string()),
+            ("to_block", self.to_block.to_string()),
+        ];
+        if let Some(skip) = self.skip_proof_verification {
+            query.push(("skip_proof_verification", skip.to_string()));
+        }
+        if let Some(sampling) = &self.sampling {
+            query.push(("sampling", sampling.to_string()));
+        }
+        query
+    }
+
+    pub(crate) fn job(&self, kind: JobKind) -> JobRequest {
+        JobRequest {
+            from_block: self.from_block,
+            to_block: self.to_block,
+            kind,
+            skip_proof_verification: self.skip_proof_verification,
+            sampling: self.sampling.as_ref().map(ToString::to_string),
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use api_types::Stark;
+use guest_types::{
+    AggregatedFeeJournal, FeeProofJournal, AGGREGATED_FEE_JOURNAL_VERSION,
+    FEE_PROOF_JOURNAL_VERSION,
+};
+use methods::{AGGREGATE_FEE_PROOFS_ID, VALIDATE_BLOCKS_AND_EXTRACT_FEES_ID};
+use serde::de::DeserializeOwned;
+
+use crate::errors::ClientError;
+
+/// Per-batch fee proof with its decoded journal.
+#[derive(Debug, Clone)]
+pub struct FeeProof {
+    pub stark: Stark,
+    pub journal: FeeProofJournal,
+}
+
+/// Proof aggregating the fee proofs of several batches, with its decoded journal.
+#[derive(Debug, Clone)]
+pub struct AggregatedFeeProof {
+    pub stark: Stark,
+    pub journal: AggregatedFeeJournal,
+}
+
+/// Verifies a per-batch fee proof against `VALIDATE_BLOCKS_AND_EXTRACT_FEES_ID`
+/// and decodes its journal. Whether the journal attests valid blocks (`ok`)
+/// and matches the Fossil Store is left to the caller.
+pub fn verify_fee_proof(stark: Stark) -> Result<FeeProof, ClientError> {
+    verify_receipt(&stark, VALIDATE_BLOCKS_AND_EXTRACT_FEES_ID)?;
+    decode_fee_proof(stark)
+}
+
+/// Verifies an aggregated fee proof against `AGGREGATE_FEE_PROOFS_ID` and
+/// decodes its journal.
+pub fn verify_aggregated_fee_proof(stark: Stark) -> Result<AggregatedFeeProof, ClientError> {
+    verify_receipt(&stark, AGGREGATE_FEE_PROOFS_ID)?;
+    decode_aggregated_fee_proof(stark)
+}
+
+pub(crate) fn decode_fee_proof(stark: Stark) -> Result<FeeProof, ClientError> {
+    let journal = decode_journal::<FeeProofJournal>(&stark)?;
+    if journal.version != FEE_PROOF_JOURNAL_VERSION {
+        return Err(ClientError::JournalVersion {
+            expected: FEE_PROOF_JOURNAL_VERSION,
+            actual: journal.version,
+        });
+    }
+    Ok(FeeProof { stark, journal })
+}
+
+pub(crate) fn decode_aggregated_fee_proof(stark: Stark) -> Result<AggregatedFeeProof, ClientError> {
+    let journal = decode_journal::<AggregatedFeeJournal>(&stark)?;
+    if journal.version != AGGREGATED_FEE_JOURNAL_VERSION {
+        return Err(ClientError::JournalVersion {
+            expected: AGGREGATED_FEE_JOURNAL_VERSION,
+            actual: journal.version,
+        });
+    }
+    // The aggregation guest only checks receipts of the image id it is given
+    if journal.fee_image_id != VALIDATE_BLOCKS_AND_EXTRACT_FEES_ID {
+        return Err(ClientError::Verification(
+            "aggregated proof was built from an unexpected fee guest".to_string(),
+        ));
+    }
+    Ok(AggregatedFeeProof { stark, journal })
+}
+
+fn verify_receipt(stark: &Stark, image_id: [u32; 8]) -> Result<(), ClientError> {
+    stark
+        .receipt()
+        .verify(image_id)
+        .map_err(|e| ClientError::Verification(e.to_string()))
+}
+
+fn decode_journal<J: DeserializeOwned>(stark: &Stark) -> Result<J, ClientError> {
+    stark
+        .journal()
+        .decode()
+        .map_err(|e| ClientError::Decode(format!("journal: {}", e)))
+}
@@ -24,6 +24,20 @@ fi
 source "$ENV_FILE"
 cat "$ENV_FILE"
 
+# The Fossil Verifier only accepts proofs of these guest images
+if [ -z "$MMR_APPEND_IMAGE_ID" ]; then
+    echo "MMR_APPEND_IMAGE_ID must hold the eight words of MMR_APPEND_ID"
+    exit 1
+fi
+if [ -z "$MMR_CHAIN_IMAGE_ID" ]; then
+    echo "MMR_CHAIN_IMAGE_ID must hold the eight words of CHAIN_MMR_APPEND_PROOFS_ID"
+    exit 1
+fi
+if [ -z "$FEE_IMAGE_ID" ]; then
+    echo "FEE_IMAGE_ID must hold the eight words of VALIDATE_BLOCKS_AND_EXTRACT_FEES_ID"
+    exit 1
+fi
+
 # Define color codes
 BLUE='\033[0;34m'
 NC='\033[0m' # No Color
@@ -99,7 +113,7 @@ FOSSIL_VERIFIER_HASH=$(starkli declare ./target/dev/verifier_FossilVerifier.cont
 echo "Class hash declared: $FOSSIL_VERIFIER_HASH"
 
 echo "Deploying Fossil Verifier contract..."
-FOSSIL_VERIFIER_ADDRESS=$(starkli deploy $FOSSIL_VERIFIER_HASH $VERIFIER_ADDRESS $FOSSILSTORE_ADDRESS  -w | grep -o '0x[a-fA-F0-9]\{64\}' | head -1)
+FOSSIL_VERIFIER_ADDRESS=$(starkli deploy $FOSSIL_VERIFIER_HASH $VERIFIER_ADDRESS $FOSSILSTORE_ADDRESS 8 $MMR_APPEND_IMAGE_ID 8 $MMR_CHAIN_IMAGE_ID 8 $FEE_IMAGE_ID -w | grep -o '0x[a-fA-F0-9]\{64\}' | head -1)
 echo "Contract deployed at: $FOSSIL_VERIFIER_ADDRESS"
 
 if [ "$DEPLOYMENT_VERSION" = "local" ] || [ "$DEPLOYMENT_VERSION" = "sepolia" ]; then
@@ -69,6 +69,20 @@ NC='\033[0m' # No Color
 BOLD='\033[1m'
 RED='\033[0;31m'
 
+# The Fossil Verifier only accepts proofs of these guest images
+if [ -z "$MMR_APPEND_IMAGE_ID" ]; then
+    echo -e "${RED}MMR_APPEND_IMAGE_ID must hold the eight words of MMR_APPEND_ID${NC}"
+    exit 1
+fi
+if [ -z "$MMR_CHAIN_IMAGE_ID" ]; then
+    echo -e "${RED}MMR_CHAIN_IMAGE_ID must hold the eight words of CHAIN_MMR_APPEND_PROOFS_ID${NC}"
+    exit 1
+fi
+if [ -z "$FEE_IMAGE_ID" ]; then
+    echo -e "${RED}FEE_IMAGE_ID must hold the eight words of VALIDATE_BLOCKS_AND_EXTRACT_FEES_ID${NC}"
+    exit 1
+fi
+
 # Now deploy Starknet contracts
 echo -e "\n${BLUE}${BOLD}Building Starknet contracts...${NC}"
 cd "$STARKNET_DIR"
@@ -121,7 +135,7 @@ echo -e "${GREEN}Class hash declared: ${BOLD}$FOSSIL_VERIFIER_HASH${NC}"
 echo
 
 echo -e "${YELLOW}Deploying Fossil Verifier contract...${NC}"
-FOSSIL_VERIFIER_ADDRESS=$(starkli deploy $FOSSIL_VERIFIER_HASH $VERIFIER_ADDRESS $FOSSILSTORE_ADDRESS --account $STARKNET_ACCOUNT --rpc $STARKNET_RPC_URL --salt 1 -w | grep -o '0x[a-fA-F0-9]\{64\}' | head -1)
+FOSSIL_VERIFIER_ADDRESS=$(starkli deploy $FOSSIL_VERIFIER_HASH $VERIFIER_ADDRESS $FOSSILSTORE_ADDRESS 8 $MMR_APPEND_IMAGE_ID 8 $MMR_CHAIN_IMAGE_ID 8 $FEE_IMAGE_ID --account $STARKNET_ACCOUNT --rpc $STARKNET_RPC_URL --salt 1 -w | grep -o '0x[a-fA-F0-9]\{64\}' | head -1)
 echo -e "${GREEN}Contract deployed at: ${BOLD}$FOSSIL_VERIFIER_ADDRESS${NC}"
 echo
 